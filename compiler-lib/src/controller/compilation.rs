@@ -1,5 +1,6 @@
+use std::path::Path;
 use crate::{ frontend::{parsetree::{PTStatement, PTStatementValue}, buildtree::BuildTree, preprocess::preprocess, parser::{parse_eard}}, unbundle::{buildunbundle::build_unbundle, linearize::linearize}, middleend::{reduce::reduce, checking::run_checking, broadtyping::broad_type, narrowtyping::narrow_type, constfold::const_fold, culdesac::culdesac, reuse::reuse, spill::spill, reorder::reorder, generate::generate, large::large}, libcore::libcore::libcore_sources, model::{step::Step, compiled::{Metadata, CompiledCode}}};
-use super::{compiler::EardCompiler, source::{CombinedSourceSourceBuilder, FixedSourceSource, ParsePosition, CombinedSourceSource, SourceSourceImpl}, compiled::make_program};
+use super::{compiler::EardCompiler, definitions::{Definition, definitions}, diagnostic::{Diagnostic, Diagnostics}, source::{CombinedSourceSourceBuilder, FixedSourceSource, ParsePosition, CombinedSourceSource, SourceSourceImpl}, compiled::make_program};
 
 pub struct EardCompilation<'a> {
     pub(crate) compiler: &'a EardCompiler,
@@ -20,56 +21,88 @@ impl<'a> EardCompilation<'a> {
         })
     }
 
+    /* Use contents for the file at path, eg an editor's unsaved buffer, rather than reading it. */
+    pub fn add_buffer(&mut self, path: &Path, contents: &str) {
+        self.soso_builder.add_buffer(path,contents);
+    }
+
     pub(crate) fn compiler(&self) -> &EardCompiler { &self.compiler }
 
-    pub(crate) fn parse_part(&mut self, position: &ParsePosition, path: &str, fixed: bool) -> Result<Vec<PTStatement>,String> {
+    pub(crate) fn parse_part(&mut self, position: &ParsePosition, path: &str, fixed: bool) -> Result<Vec<PTStatement>,Diagnostic> {
+        Ok(self.include_part(position,path,fixed)?.0)
+    }
+
+    /* also returns the context given to the file, as an empty file has no statements to carry it */
+    pub(crate) fn include_part(&mut self, position: &ParsePosition, path: &str, fixed: bool) -> Result<(Vec<PTStatement>,usize),Diagnostic> {
         self.context += 1;
         let context = self.context;
-        parse_eard(position,path,fixed,self.compiler().optimise(),context)
+        Ok((parse_eard(position,path,fixed,self.compiler().optimise(),context)?,context))
     }
 
-    fn add_libcore(&mut self, position: &ParsePosition) -> Result<Vec<PTStatement>,String> {
+    fn add_libcore(&mut self, position: &ParsePosition) -> Result<Vec<PTStatement>,Diagnostic> {
         if self.compiler().has_flag("no-libcore") { return Ok(vec![]); }
         self.context += 1;
         let context = self.context;
         Ok(vec![PTStatement { 
-            value: PTStatementValue::Include("libcore".to_string(),true),
+            value: PTStatementValue::Include("libcore".to_string(),true,None),
             position: position.clone(),
             context 
         }])
     }
 
-    pub(crate) fn parse(&mut self, position: &ParsePosition, path: &str, fixed: bool) -> Result<Vec<PTStatement>,String> {
+    pub(crate) fn parse(&mut self, position: &ParsePosition, path: &str, fixed: bool) -> Result<Vec<PTStatement>,Diagnostic> {
         let mut out = self.add_libcore(position)?;
         out.append(&mut self.parse_part(position,path,fixed)?);
         Ok(out)
     }
 
-    pub(crate) fn preprocess(&mut self, parse_tree: Vec<PTStatement>) -> Result<Vec<PTStatement>,String> {
+    pub(crate) fn preprocess(&mut self, parse_tree: Vec<PTStatement>) -> Result<Vec<PTStatement>,Diagnostic> {
         preprocess(self,parse_tree)
     }
 
-    pub(crate) fn build(&mut self, input: Vec<PTStatement>) -> Result<BuildTree,String> {
+    pub(crate) fn build(&mut self, input: Vec<PTStatement>) -> Result<BuildTree,Diagnostics> {
         PTStatement::to_build_tree(input,self.compiler().target_version())
     }
 
-    pub(crate) fn frontend(&mut self, filename: &str) -> Result<BuildTree,String> {
+    fn root_position(&self) -> ParsePosition {
         let soso = CombinedSourceSource::new(&self.soso_builder);
-        let position = ParsePosition::root(SourceSourceImpl::new(soso),"included");
+        ParsePosition::root(SourceSourceImpl::new(soso),"included")
+    }
+
+    pub(crate) fn frontend(&mut self, filename: &str) -> Result<BuildTree,Diagnostics> {
+        let position = self.root_position();
         let stmts = self.parse(&position,filename,false)?;
         let stmts = self.preprocess(stmts)?;
         self.build(stmts)
     }
 
-    pub(crate) fn middleend(&mut self, tree: &BuildTree) -> Result<(Vec<Step>,Metadata),String> {
+    /* Definitions visible in a file once includes and macros are expanded. With fixed set, the
+     * name is that of a library source, and libcore isn't added.
+     */
+    pub fn definitions(&mut self, filename: &str, fixed: bool) -> Result<Vec<Definition>,Diagnostic> {
+        let position = self.root_position();
+        let stmts = if fixed {
+            self.parse_part(&position,filename,true)?
+        } else {
+            self.parse(&position,filename,false)?
+        };
+        let stmts = self.preprocess(stmts)?;
+        Ok(definitions(&stmts))
+    }
+
+    pub(crate) fn middleend(&mut self, tree: &BuildTree) -> Result<(Vec<Step>,Vec<ParsePosition>,Metadata),Diagnostics> {
+        self.run_middleend(tree,true)
+    }
+
+    fn run_middleend(&mut self, tree: &BuildTree, need_header: bool) -> Result<(Vec<Step>,Vec<ParsePosition>,Metadata),Diagnostics> {
         let verbose = self.compiler.verbose();
         let bundles = build_unbundle(&tree)?;
-        let (linear,mut allocator,metadata) = linearize(&tree,&bundles,verbose)?;
+        let (linear,mut allocator,metadata) = linearize(&tree,&bundles,need_header,verbose)?;
         let linear = reduce(&linear,verbose)?;
         let (mut broad,block_indexes) = broad_type(&tree,&linear)?;
         let linear = run_checking(&tree,&linear,&block_indexes,&mut allocator,&mut broad,verbose)?;
         let mut narrow = narrow_type(&tree,&block_indexes,&broad,&linear)?;
-        let opers = const_fold(&self,tree,&block_indexes,&narrow,&linear,verbose);
+        let opers = const_fold(&self,tree,&block_indexes,&narrow,&linear,verbose)?;
         let opers = culdesac(tree,&block_indexes,&opers,verbose);
         let opers = reuse(tree,&broad,&narrow,&block_indexes,&opers,verbose)?;
         let opers = reorder(&tree,&block_indexes,&opers)?;
@@ -78,14 +111,30 @@ impl<'a> EardCompilation<'a> {
         if !self.compiler().has_flag("no-call-up") {
             opers = large(tree,&block_indexes,&mut allocator,&opers)?;
         }
-        let steps = generate(&tree,&block_indexes,&narrow,&opers,verbose).expect("generate failed");
-        Ok((steps,metadata))
+        let (steps,positions) = generate(&tree,&block_indexes,&narrow,&opers,verbose).expect("generate failed");
+        Ok((steps,positions,metadata))
+    }
+
+    /* Everything compile does, so fails exactly when it would, but without making the program. */
+    pub fn check(&mut self, filename: &str) -> Result<(),Diagnostics> {
+        let tree = self.frontend(filename)?;
+        self.middleend(&tree)?;
+        Ok(())
+    }
+
+    /* As check, but a file without a program header is checked as a file which is only ever
+     * included, as libraries are, rather than being an error.
+     */
+    pub fn check_include(&mut self, filename: &str) -> Result<(),Diagnostics> {
+        let tree = self.frontend(filename)?;
+        self.run_middleend(&tree,false)?;
+        Ok(())
     }
 
-    pub fn compile(&mut self, filename: &str) -> Result<CompiledCode,String> {
+    pub fn compile(&mut self, filename: &str) -> Result<CompiledCode,Diagnostics> {
         let tree = self.frontend(filename)?;
-        let (steps,metadata) = self.middleend(&tree)?;
-        let program = make_program(&steps,&metadata);
+        let (steps,positions,metadata) = self.middleend(&tree)?;
+        let program = make_program(&steps,&positions,&metadata);
         Ok(program)
     }
 }
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use crate::model::{compiled::{CompiledBlock, Metadata, CompiledCode}, step::Step};
+use super::source::ParsePosition;
 
 fn find_entries(steps: &[Step]) -> Vec<String> {
     let out = steps.iter().filter_map(|step| {
@@ -12,21 +13,24 @@ fn find_entries(steps: &[Step]) -> Vec<String> {
     if out.len() == 0 { vec!["main".to_string()] } else { out }
 }
 
-fn make_block(steps: &[Step], entry: &str) -> CompiledBlock {
+fn make_block(steps: &[Step], positions: &[ParsePosition], entry: &str) -> CompiledBlock {
     let mut constants = vec![];
     let mut program = vec![];
+    let mut debug = vec![];
     let mut on = true;
-    for step in steps {
+    for (step,position) in steps.iter().zip(positions.iter()) {
         match step {
             Step::Constant(r,c) => {
                 if on {
                     program.push((0,vec![r.clone(),constants.len()]));
                     constants.push(c.clone());
+                    debug.push(position.spans());
                 }
             },
             Step::Opcode(code,args) => {
                 if on {
                     program.push((*code,args.to_vec()));
+                    debug.push(position.spans());
                 }
             },
             Step::Entry(this_entry) =>{
@@ -34,11 +38,11 @@ fn make_block(steps: &[Step], entry: &str) -> CompiledBlock {
             },
         }
     }
-    CompiledBlock { constants, program }
+    CompiledBlock { constants, program, debug }
 }
 
-pub(crate) fn make_program(steps: &[Step], metadata: &Metadata) -> CompiledCode {
+pub(crate) fn make_program(steps: &[Step], positions: &[ParsePosition], metadata: &Metadata) -> CompiledCode {
     let entries = find_entries(steps);
-    let code = entries.iter().map(|entry| (entry.to_string(),make_block(steps,entry))).collect::<HashMap<_,_>>();
+    let code = entries.iter().map(|entry| (entry.to_string(),make_block(steps,positions,entry))).collect::<HashMap<_,_>>();
     CompiledCode { code, metadata: metadata.clone() }
 }
@@ -0,0 +1,96 @@
+use std::{collections::HashMap, fmt};
+use crate::{frontend::parsetree::{PTStatement, PTStatementValue, FuncProcModifier}, model::codeblocks::CodeBlock, test::testutil::sepfmt};
+use super::diagnostic::SourceSpan;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum DefinitionKind {
+    Function,
+    Procedure,
+    Code,
+    Const,
+    Record
+}
+
+impl fmt::Display for DefinitionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"{}",match self {
+            DefinitionKind::Function => "function",
+            DefinitionKind::Procedure => "procedure",
+            DefinitionKind::Code => "code",
+            DefinitionKind::Const => "const",
+            DefinitionKind::Record => "record"
+        })
+    }
+}
+
+/* A function, procedure, code block, const or record found in a file or anything it includes, for use by tools. */
+#[derive(Debug,Clone)]
+pub struct Definition {
+    pub name: String,
+    pub kind: DefinitionKind,
+    pub exported: bool,
+    pub signature: String,
+    pub span: Option<SourceSpan>,
+    pub namespaces: Vec<String>, // aliases under which an export can be reached, eg a in a.f()
+    pub fields: Vec<String> // of a record
+}
+
+fn code_signature(code: &CodeBlock) -> String {
+    let args = code.arguments.iter().map(|a| format!("{:?}",a).trim().to_string()).collect::<Vec<_>>();
+    let mut out = format!("code {}({})",code.name,args.join(", "));
+    if !code.results.is_empty() {
+        let results = code.results.iter().map(|a| format!("{:?}",a).trim().to_string()).collect::<Vec<_>>();
+        out.push_str(&format!(" -> ({})",results.join(", ")));
+    }
+    if !code.modifiers.is_empty() {
+        out = format!("{} {}",sepfmt(&mut code.modifiers.iter()," ",""),out);
+    }
+    out
+}
+
+fn definition(stmt: &PTStatement, aliases: &HashMap<usize,Vec<String>>) -> Option<Definition> {
+    let mut fields = vec![];
+    let (name,kind,exported,signature) = match &stmt.value {
+        PTStatementValue::FuncDef(f) => {
+            let exported = f.modifiers.contains(&FuncProcModifier::Export);
+            let mut sig = format!("function {}({})",f.name,sepfmt(&mut f.args.iter(),", ",""));
+            if let Some(value_type) = &f.value_type {
+                sig.push_str(&format!(" -> {:?}",value_type));
+            }
+            (f.name.clone(),DefinitionKind::Function,exported,sig)
+        },
+        PTStatementValue::ProcDef(p) => {
+            let exported = p.modifiers.contains(&FuncProcModifier::Export);
+            let mut sig = format!("procedure {}({})",p.name,sepfmt(&mut p.args.iter(),", ",""));
+            if let Some(ret_type) = &p.ret_type {
+                sig.push_str(&format!(" -> ({})",sepfmt(&mut ret_type.iter(),", ","")));
+            }
+            (p.name.clone(),DefinitionKind::Procedure,exported,sig)
+        },
+        PTStatementValue::Code(c) => {
+            (c.name.clone(),DefinitionKind::Code,false,code_signature(c))
+        },
+        PTStatementValue::ConstDef(name,_,exported) => {
+            (name.clone(),DefinitionKind::Const,*exported,format!("const {}",name))
+        },
+        PTStatementValue::RecordDef(r) => {
+            let sig = format!("record {} {{ {} }}",r.name,sepfmt(&mut r.fields.iter(),", ",""));
+            fields = r.fields.iter().map(|f| f.id.clone()).collect();
+            (r.name.clone(),DefinitionKind::Record,r.export,sig)
+        },
+        _ => { return None; }
+    };
+    let signature = if exported { format!("export {}",signature) } else { signature };
+    let namespaces = if exported { aliases.get(&stmt.context).cloned().unwrap_or_default() } else { vec![] };
+    Some(Definition { name, kind, exported, signature, span: stmt.position.span(), namespaces, fields })
+}
+
+pub(crate) fn definitions(stmts: &[PTStatement]) -> Vec<Definition> {
+    let mut aliases : HashMap<usize,Vec<String>> = HashMap::new();
+    for stmt in stmts {
+        if let PTStatementValue::Namespace(alias,context) = &stmt.value {
+            aliases.entry(*context).or_default().push(alias.clone());
+        }
+    }
+    stmts.iter().filter_map(|stmt| definition(stmt,&aliases)).collect()
+}
@@ -0,0 +1,205 @@
+use std::fmt;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Severity {
+    Error,
+    Warning
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"{}",match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning"
+        })
+    }
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum DiagnosticCode {
+    General,
+    Parse,
+    Include,
+    Macro,
+    Build,
+    Unbundle,
+    Linearize,
+    BroadType,
+    Check,
+    NarrowType,
+    Codegen
+}
+
+impl DiagnosticCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticCode::General => "general",
+            DiagnosticCode::Parse => "parse",
+            DiagnosticCode::Include => "include",
+            DiagnosticCode::Macro => "macro",
+            DiagnosticCode::Build => "build",
+            DiagnosticCode::Unbundle => "unbundle",
+            DiagnosticCode::Linearize => "linearize",
+            DiagnosticCode::BroadType => "broad-type",
+            DiagnosticCode::Check => "check",
+            DiagnosticCode::NarrowType => "narrow-type",
+            DiagnosticCode::Codegen => "codegen"
+        }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct SourceSpan {
+    pub filename: String,
+    pub path: Option<String>, // location on disk, where the file came from one
+    pub line: u32,
+    pub column: u32,
+    pub source: Option<String> // text of the line, where known
+}
+
+impl fmt::Display for SourceSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"{}:{}",self.filename,self.line)
+    }
+}
+
+#[derive(Debug,Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub message: String,
+    pub span: Option<Box<SourceSpan>>, // boxed to keep Result<_,Diagnostic> small
+    pub chain: Vec<SourceSpan>, // innermost first
+    pub chain_kind: String,
+    pub notes: Vec<String>
+}
+
+impl Diagnostic {
+    pub fn new(code: DiagnosticCode, message: &str) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            message: message.to_string(),
+            span: None,
+            chain: vec![],
+            chain_kind: "included".to_string(),
+            notes: vec![]
+        }
+    }
+
+    pub fn with_note(mut self, note: &str) -> Diagnostic {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub(crate) fn with_source(mut self, source: &str) -> Diagnostic {
+        if let Some(span) = &mut self.span {
+            span.source = Some(source.to_string());
+        }
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = match self.code {
+            DiagnosticCode::General => format!("{}: {}\n",self.severity,self.message),
+            code => format!("{}[{}]: {}\n",self.severity,code.as_str(),self.message)
+        };
+        let gutter = self.span.as_ref().map(|s| s.line.to_string().len()).unwrap_or(1);
+        let pad = " ".repeat(gutter);
+        if let Some(span) = &self.span {
+            out.push_str(&format!("{}--> {}:{}:{}\n",pad,span.filename,span.line,span.column));
+            if let Some(source) = &span.source {
+                let caret = " ".repeat((span.column as usize).max(1)-1);
+                out.push_str(&format!("{} |\n",pad));
+                out.push_str(&format!("{} | {}\n",span.line,source));
+                out.push_str(&format!("{} | {}^\n",pad,caret));
+            }
+        }
+        for parent in &self.chain {
+            out.push_str(&format!("{} = note: {} from {}:{}:{}\n",pad,self.chain_kind,parent.filename,parent.line,parent.column));
+        }
+        for note in &self.notes {
+            out.push_str(&format!("{} = note: {}\n",pad,note));
+        }
+        out
+    }
+}
+
+impl From<String> for Diagnostic {
+    fn from(message: String) -> Diagnostic {
+        Diagnostic::new(DiagnosticCode::General,&message)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"{}",self.message)?;
+        if let Some(span) = &self.span {
+            write!(f," at {}",span)?;
+        }
+        for parent in &self.chain {
+            write!(f," ({} from {})",self.chain_kind,parent)?;
+        }
+        Ok(())
+    }
+}
+
+/* Phases which can carry on past an error stop collecting once they reach this many. */
+pub(crate) const MAX_DIAGNOSTICS : usize = 20;
+
+#[derive(Debug,Clone)]
+pub struct Diagnostics {
+    list: Vec<Diagnostic>,
+    truncated: bool
+}
+
+impl Diagnostics {
+    pub(crate) fn new() -> Diagnostics {
+        Diagnostics { list: vec![], truncated: false }
+    }
+
+    /* The same problem can be found more than once, eg by checks on several arguments, so report it once */
+    pub(crate) fn add(&mut self, diagnostic: Diagnostic) {
+        let duplicate = self.list.iter().any(|d| {
+            d.code == diagnostic.code && d.message == diagnostic.message && d.span == diagnostic.span && d.chain == diagnostic.chain
+        });
+        if duplicate { return; }
+        if self.list.len() < MAX_DIAGNOSTICS {
+            self.list.push(diagnostic);
+        } else {
+            self.truncated = true;
+        }
+    }
+
+    /* true once a diagnostic has been dropped, so there's no point looking for more */
+    pub(crate) fn full(&self) -> bool { self.truncated }
+
+    pub(crate) fn result<T>(self, value: T) -> Result<T,Diagnostics> {
+        if !self.list.is_empty() { Err(self) } else { Ok(value) }
+    }
+
+    pub fn len(&self) -> usize { self.list.len() }
+    pub fn is_empty(&self) -> bool { self.list.is_empty() }
+    pub fn iter(&self) -> impl Iterator<Item=&Diagnostic> { self.list.iter() }
+
+    pub fn render(&self) -> String {
+        let mut out = self.list.iter().map(|d| d.render()).collect::<Vec<_>>().join("\n");
+        if self.full() {
+            out.push_str(&format!("\nerror: too many errors, stopped after the first {}\n",self.list.len()));
+        }
+        out
+    }
+}
+
+impl From<Diagnostic> for Diagnostics {
+    fn from(diagnostic: Diagnostic) -> Diagnostics {
+        Diagnostics { list: vec![diagnostic], truncated: false }
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = self.list.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+        write!(f,"{}",lines.join("\n"))
+    }
+}
@@ -41,14 +41,35 @@ impl OpcodeVersion {
     }
 }
 
+/* Shared by the command line tools, so they offer the same choices. */
+#[derive(Debug,Copy,Clone,PartialEq,Eq,PartialOrd,Ord)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum OutputFormat {
+    /// Standard format (binary)
+    #[cfg_attr(feature = "clap", value(alias("s")))]
+    Standard,
+    /// Expanded (for debugging)
+    #[cfg_attr(feature = "clap", value(alias("x")))]
+    Expanded,
+    /// Dump (for debugging)
+    #[cfg_attr(feature = "clap", value(alias("d")))]
+    Dump
+}
+
 #[derive(Debug)]
 pub struct EardSerializeCode {
-    code: Vec<CompiledCode>
+    code: Vec<CompiledCode>,
+    debug: bool
 }
 
 impl EardSerializeCode {
     pub fn new() -> EardSerializeCode {
-        EardSerializeCode { code: vec![] }
+        EardSerializeCode { code: vec![], debug: false }
+    }
+
+    /* include source positions of each step in the output */
+    pub fn set_debug(&mut self, yn: bool) {
+        self.debug = yn;
     }
 
     pub fn add(&mut self, code: CompiledCode) {
@@ -60,14 +81,14 @@ impl EardSerializeCode {
         let mut encoder = Encoder::new(&mut buffer);
         encoder.begin_array()?;
         for code in &self.code {
-            code.encode(&mut encoder)?;
+            code.encode(&mut encoder,self.debug)?;
         }
         encoder.end()?;
         Ok(buffer)
     }
 
     fn encode_json(&self) -> JsonValue {
-        JsonValue::Array(self.code.iter().map(|c| c.encode_json()).collect())
+        JsonValue::Array(self.code.iter().map(|c| c.encode_json(self.debug)).collect())
     }
 
     pub fn serialize(&self) -> Result<Vec<u8>,String> {
@@ -78,4 +99,12 @@ impl EardSerializeCode {
         compactify(&self.encode_json().pretty(2))
 
     }
+
+    pub fn output(&self, format: OutputFormat) -> Result<Vec<u8>,String> {
+        Ok(match format {
+            OutputFormat::Standard => self.serialize()?,
+            OutputFormat::Expanded => self.serialize_json().as_bytes().to_vec(),
+            OutputFormat::Dump => format!("{:?}",self).as_bytes().to_vec()
+        })
+    }
 }
@@ -1,7 +1,9 @@
-use std::{sync::Arc, collections::HashMap, fmt, path::PathBuf, env::current_dir, fs::read_to_string};
+use std::{sync::Arc, collections::HashMap, fmt, path::{Path, PathBuf}, env::current_dir, fs::read_to_string};
+use super::diagnostic::{Diagnostic, DiagnosticCode, SourceSpan};
 
 pub(crate) trait SourceSource {
     fn lookup(&self, filename: &str, fixed: bool) -> Result<(SourceSourceImpl,String),String>;
+    fn path(&self, _filename: &str, _fixed: bool) -> Option<PathBuf> { None }
 }
 
 pub(crate) struct SourceSourceImpl(Box<dyn SourceSource>);
@@ -16,6 +18,10 @@ impl SourceSource for SourceSourceImpl {
     fn lookup(&self,filename: &str, fixed: bool) -> Result<(SourceSourceImpl,String),String> {
         self.0.lookup(filename,fixed)
     }
+
+    fn path(&self, filename: &str, fixed: bool) -> Option<PathBuf> {
+        self.0.path(filename,fixed)
+    }
 }
 
 #[derive(Clone)]
@@ -29,12 +35,19 @@ impl SourceSource for NoneSourceSource {
 
 #[derive(Clone,Debug)]
 pub struct FixedSourceSource {
-    files: Arc<HashMap<String,String>>
+    files: Arc<HashMap<String,String>>,
+    dir: Option<PathBuf>
 }
 
 impl FixedSourceSource {
     pub fn new(files: HashMap<String,String>) -> FixedSourceSource {
-        FixedSourceSource { files: Arc::new(files) }
+        FixedSourceSource { files: Arc::new(files), dir: None }
+    }
+
+    /* Each file was built from dir/<name>.eard, so tools such as the language server can show it. */
+    pub fn with_dir(mut self, dir: &str) -> FixedSourceSource {
+        self.dir = Some(PathBuf::from(dir));
+        self
     }
 
     pub fn new_vec(mut files: Vec<(&str,&str)>) -> FixedSourceSource {
@@ -51,17 +64,23 @@ impl SourceSource for FixedSourceSource {
         let src = self.files.get(filename).cloned().ok_or_else(|| format!("cannot find '{}'",filename))?;
         Ok((SourceSourceImpl::new(self.clone()),src.to_string()))
     }
+
+    fn path(&self, filename: &str, fixed: bool) -> Option<PathBuf> {
+        if !fixed || !self.files.contains_key(filename) { return None; }
+        self.dir.as_ref().map(|dir| dir.join(format!("{}.eard",filename)))
+    }
 }
 
 #[derive(Clone)]
 pub(crate) struct FileSourceSource {
-    rel_path: PathBuf
+    rel_path: PathBuf,
+    buffers: Arc<HashMap<PathBuf,String>>
 }
 
 impl FileSourceSource {
     fn new() -> Result<FileSourceSource,String> {
         let cwd = current_dir().map_err(|e| format!("couldn't get current directory: {}",e))?;
-        Ok(FileSourceSource { rel_path: cwd })
+        Ok(FileSourceSource { rel_path: cwd, buffers: Arc::new(HashMap::new()) })
     }
 
     fn lookup_file(&self, filename: &str, fixed: bool) -> Result<(FileSourceSource,String),String> {
@@ -71,8 +90,11 @@ impl FileSourceSource {
         let mut new_path = self.rel_path.clone();
         new_path.push(filename);
         let rel_path = new_path.parent().ok_or_else(|| format!("Cannot find parent directory of {}",filename))?;
-        let new_source = FileSourceSource{ rel_path: rel_path.to_path_buf() };
-        let contents = read_to_string(new_path).map_err(|e| format!("cannot read {}: {}",filename,e))?;
+        let new_source = FileSourceSource{ rel_path: rel_path.to_path_buf(), buffers: self.buffers.clone() };
+        let contents = match self.buffers.get(&new_path) {
+            Some(contents) => contents.clone(),
+            None => read_to_string(new_path).map_err(|e| format!("cannot read {}: {}",filename,e))?
+        };
         Ok((new_source,contents))
     }
 }
@@ -82,6 +104,13 @@ impl SourceSource for FileSourceSource {
         let (source,input) = self.lookup_file(filename,fixed)?;
         Ok((SourceSourceImpl::new(source),input))
     }
+
+    fn path(&self, filename: &str, fixed: bool) -> Option<PathBuf> {
+        if fixed { return None; }
+        let mut path = self.rel_path.clone();
+        path.push(filename);
+        Some(path)
+    }
 }
 
 pub(crate) struct CombinedSourceSourceBuilder {
@@ -97,6 +126,13 @@ impl CombinedSourceSourceBuilder {
     pub(crate) fn add_fixed(&mut self, source: &FixedSourceSource) {
         self.fixed.push(source.clone());
     }
+
+    /* contents to use for the file at path in place of what's on disk */
+    pub(crate) fn add_buffer(&mut self, path: &Path, contents: &str) {
+        let mut path_buf = self.file.rel_path.clone();
+        path_buf.push(path);
+        Arc::make_mut(&mut self.file.buffers).insert(path_buf,contents.to_string());
+    }
 }
 
 pub(crate) struct CombinedSourceSource {
@@ -127,23 +163,43 @@ impl SourceSource for CombinedSourceSource {
             Ok((SourceSourceImpl::new(soso),input))
         }
     }
+
+    fn path(&self, filename: &str, fixed: bool) -> Option<PathBuf> {
+        if fixed {
+            self.fixed.iter().find_map(|src| src.path(filename,true))
+        } else {
+            self.file.path(filename,fixed)
+        }
+    }
 }
 
 #[derive(Clone)]
 pub(crate) struct FilePosition {
     soso: Arc<SourceSourceImpl>,
     filename: String,
+    path: Option<PathBuf>,
     suppress: bool,
-    line_no: u32
+    line_no: u32,
+    column: u32
 }
 
 impl FilePosition {
     fn anon(soso: SourceSourceImpl) -> FilePosition {
-        FilePosition { soso: Arc::new(soso), filename: "*anon*".to_string(), line_no: 0, suppress: true }
+        FilePosition { soso: Arc::new(soso), filename: "*anon*".to_string(), path: None, line_no: 0, column: 0, suppress: true }
+    }
+
+    fn new(soso: SourceSourceImpl, filename: &str, path: Option<PathBuf>) -> FilePosition {
+        FilePosition { soso: Arc::new(soso), filename: filename.to_string(), path, line_no: 0, column: 0, suppress: false }
     }
 
-    fn new(soso: SourceSourceImpl, filename: &str) -> FilePosition {
-        FilePosition { soso: Arc::new(soso), filename: filename.to_string(), line_no: 0, suppress: false }
+    fn span(&self) -> SourceSpan {
+        SourceSpan {
+            filename: self.filename.clone(),
+            path: self.path.as_ref().map(|p| p.to_string_lossy().to_string()),
+            line: self.line_no,
+            column: self.column,
+            source: None
+        }
     }
 }
 
@@ -168,6 +224,15 @@ impl PositionNode {
         }
     }
 
+    fn chain(&self, out: &mut Vec<SourceSpan>) {
+        if !self.1.suppress {
+            out.push(self.1.span());
+        }
+        if let Some(parent) = &self.0 {
+            parent.chain(out);
+        }
+    }
+
     pub(crate) fn contains(&self, filename: &str) -> bool {
         if filename == self.1.filename { return true; }
         self.0.as_ref().map(|p| p.contains(filename)).unwrap_or(false)
@@ -190,9 +255,10 @@ impl ParsePosition {
         Self::root(SourceSourceImpl::new(NoneSourceSource),variety)
     }
 
-    pub(crate) fn at_line(&self, line_no: u32) -> ParsePosition {
+    pub(crate) fn at_line(&self, line_no: u32, column: u32) -> ParsePosition {
         let mut out = self.clone();
         (out.0).1.line_no = line_no;
+        (out.0).1.column = column;
         out
     }
 
@@ -206,13 +272,19 @@ impl ParsePosition {
     }
 
     pub(crate) fn push(&self, filename: &str, fixed: bool) -> Result<(String,ParsePosition),String> {
+        let path = self.last().soso.path(filename,fixed);
         let (soso,input) = self.last().soso.lookup(filename,fixed)?;
-        let new_pos = self.add(&FilePosition::new(soso,filename));
+        let new_pos = self.add(&FilePosition::new(soso,filename,path));
         Ok((input,new_pos))
     }
 
     pub(crate) fn last(&self) -> &FilePosition { &(self.0).1 }
 
+    /* the position this one was called or included from */
+    pub(crate) fn caller(&self) -> Option<ParsePosition> {
+        (self.0).0.as_ref().map(|parent| ParsePosition(parent.as_ref().clone(),self.1.clone()))
+    }
+
     pub(crate) fn last_str(&self) -> String { format!("{:?}",self.last()) }
 
     pub(crate) fn full_str(&self) -> String {
@@ -222,8 +294,32 @@ impl ParsePosition {
         format!("{:?}{}",(self.0).1,rest)
     }
 
-    pub(crate) fn message(&self, msg: &str) -> String {
-        format!("{} at {}",msg,self.full_str())
+    pub(crate) fn span(&self) -> Option<SourceSpan> {
+        let last = self.last();
+        if last.suppress { None } else { Some(last.span()) }
+    }
+
+    /* this position followed by those it was included or called from, innermost first */
+    pub(crate) fn spans(&self) -> Vec<SourceSpan> {
+        let mut out = vec![];
+        self.0.chain(&mut out);
+        out
+    }
+
+    pub(crate) fn diagnostic(&self, code: DiagnosticCode, msg: &str) -> Diagnostic {
+        self.locate(Diagnostic::new(code,msg))
+    }
+
+    /* attach this position to a diagnostic which doesn't yet have one */
+    pub(crate) fn locate(&self, mut diagnostic: Diagnostic) -> Diagnostic {
+        if diagnostic.span.is_some() { return diagnostic; }
+        diagnostic.span = self.span().map(Box::new);
+        diagnostic.chain = vec![];
+        if let Some(parent) = &(self.0).0 {
+            parent.chain(&mut diagnostic.chain);
+        }
+        diagnostic.chain_kind = self.1.to_string();
+        diagnostic
     }
 }
 
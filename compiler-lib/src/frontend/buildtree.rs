@@ -163,7 +163,8 @@ pub(crate) struct BTFuncProcDefinition {
     pub(crate) block: Vec<BTStatement>,
     pub(crate) ret: Vec<OrBundle<BTExpression>>,
     pub(crate) ret_type: Option<Vec<ArgTypeSpec>>,
-    pub(crate) entry: bool
+    pub(crate) entry: bool,
+    pub(crate) record: Option<String> // when this is the constructor of a record
 }
 
 impl fmt::Debug for BTFuncProcDefinition {
@@ -225,12 +226,13 @@ pub(crate) struct BuildTree {
     pub(crate) statements: Vec<BTStatement>,
     definitions: Vec<BTDefinition>,
     specials: HashMap<String,usize>,
-    entries: Vec<(usize,String)>
+    entries: Vec<(usize,String)>,
+    consts: Vec<(String,ParsePosition)>
 }
 
 impl BuildTree {
     pub(crate) fn new() -> BuildTree {
-        BuildTree { statements: vec![], definitions: vec![], specials: HashMap::new(), entries: vec![] }
+        BuildTree { statements: vec![], definitions: vec![], specials: HashMap::new(), entries: vec![], consts: vec![] }
     }
 
     pub(crate) fn finish(&mut self) {
@@ -250,6 +252,16 @@ impl BuildTree {
         self.entries.push((id,name.to_string()));
     }
 
+    /* consts are passed to __operator_const by index so that errors can cite the declaration */
+    pub(super) fn add_const(&mut self, name: &str, position: &ParsePosition) -> usize {
+        self.consts.push((name.to_string(),position.clone()));
+        self.consts.len()-1
+    }
+
+    pub(crate) fn get_const(&self, index: usize) -> Option<&(String,ParsePosition)> {
+        self.consts.get(index)
+    }
+
     pub(super) fn add_definition(&mut self, defn: BTDefinition) -> usize {
         let id = self.definitions.len();
         self.definitions.push(defn);
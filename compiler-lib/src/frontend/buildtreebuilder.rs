@@ -1,6 +1,7 @@
-use std::{collections::{BTreeMap, HashSet}};
-use crate::{model::{checkstypes::{TypedArgument, ArgTypeSpec, TypeSpec, Check}, codeblocks::{CodeDefinition, CodeBlock}}, controller::source::ParsePosition};
-use super::{buildtree::{BuildTree, BTStatementValue, BTStatement, BTExpression, BTDefinitionVariety, BTFuncProcDefinition, BTDefinition, BTFuncCall, BTLValue, BTProcCall, BTRegisterType, Variable}, parsetree::{PTExpression, PTCall, PTStatement, PTStatementValue, PTFuncDef, PTProcDef, FuncProcModifier}, femodel::{OrBundle, OrBundleRepeater}};
+use std::{collections::{BTreeMap, HashSet}, cell::Cell};
+use ordered_float::OrderedFloat;
+use crate::{model::{checkstypes::{TypedArgument, ArgTypeSpec, TypeSpec, Check}, codeblocks::{CodeDefinition, CodeBlock}, constants::Constant}, controller::{source::ParsePosition, diagnostic::{Diagnostics, DiagnosticCode}}};
+use super::{buildtree::{BuildTree, BTStatementValue, BTStatement, BTExpression, BTDefinitionVariety, BTFuncProcDefinition, BTDefinition, BTFuncCall, BTLValue, BTProcCall, BTRegisterType, Variable}, parsetree::{PTExpression, PTCall, PTStatement, PTStatementValue, PTFuncDef, PTProcDef, PTRecordDef, FuncProcModifier}, femodel::{OrBundle, OrBundleRepeater}};
 
 #[derive(Debug,Clone)]
 pub(super) enum DefName {
@@ -78,7 +79,8 @@ impl CurrentFuncProcDefinition {
             ret_type: self.ret_type.clone(),
             ret: ret.to_vec(),
             block: self.block.clone(),
-            entry: self.entry
+            entry: self.entry,
+            record: self.name.strip_prefix("__record_").map(|x| x.to_string())
         })
     }
 }
@@ -88,9 +90,20 @@ pub(crate) struct BuildContext {
     location: ParsePosition,
     file_context: usize,
     defnames: BTreeMap<(Option<usize>,String),DefName>,
+    consts: BTreeMap<(Option<usize>,String),(usize,PTExpression,usize)>,
+    building_consts: Vec<String>,
+    records: BTreeMap<(Option<usize>,String),Vec<String>>,
+    record_vars: BTreeMap<String,(String,Vec<String>)>,
+    namespaces: BTreeMap<(usize,String),usize>,
+    namespaced: HashSet<usize>,
+    ns_exports: HashSet<(usize,String)>,
+    export_origins: BTreeMap<String,String>,
     next_register: usize,
     funcproc_target: Option<CurrentFuncProcDefinition>,
-    next_call_index: usize
+    next_call_index: usize,
+    errors: Diagnostics,
+    failed: HashSet<String>,
+    used_failed: Cell<bool>
 }
 
 impl BuildContext {
@@ -100,9 +113,20 @@ impl BuildContext {
             location: ParsePosition::empty("included"),
             file_context: 0,
             defnames: BTreeMap::new(),
+            consts: BTreeMap::new(),
+            building_consts: vec![],
+            records: BTreeMap::new(),
+            record_vars: BTreeMap::new(),
+            namespaces: BTreeMap::new(),
+            namespaced: HashSet::new(),
+            ns_exports: HashSet::new(),
+            export_origins: BTreeMap::new(),
             next_register: 0,
             funcproc_target: None,
-            next_call_index: 0
+            next_call_index: 0,
+            errors: Diagnostics::new(),
+            failed: HashSet::new(),
+            used_failed: Cell::new(false)
         }
     }
 
@@ -146,6 +170,15 @@ impl BuildContext {
 
     pub(crate) fn pop_funcproc_target(&mut self, ret: &[OrBundle<BTExpression>], bt: &mut BuildTree) -> Result<(),String> {
         let ctx = self.funcproc_target.take().expect("pop without push");
+        let name = ctx.name.clone();
+        let out = self.finish_funcproc(ctx,ret,bt);
+        if out.is_err() {
+            self.failed.insert(name);
+        }
+        out
+    }
+
+    fn finish_funcproc(&mut self, ctx: CurrentFuncProcDefinition, ret: &[OrBundle<BTExpression>], bt: &mut BuildTree) -> Result<(),String> {
         if !self.versions_ok(&ctx.versions) { return Ok(()); }
         let defn_id = match &ctx.variety {
             BTDefinitionVariety::Func => {                
@@ -179,21 +212,62 @@ impl BuildContext {
         self.location = position.clone();
     }
 
-    pub(crate) fn location(&self) -> &ParsePosition { &self.location }
+    /* errors caused by using a definition which itself failed are just noise, so are dropped */
+    pub(crate) fn add_error(&mut self, msg: &str) {
+        if self.used_failed.replace(false) { return; }
+        let diagnostic = self.location.diagnostic(DiagnosticCode::Build,msg);
+        self.errors.add(diagnostic);
+    }
+
+    pub(crate) fn too_many_errors(&self) -> bool { self.errors.full() }
+
+    /* after an error we abandon any definition in progress, so later statements land at top level */
+    pub(crate) fn abandon_funcproc(&mut self) {
+        if let Some(target) = self.funcproc_target.take() {
+            self.failed.insert(target.name);
+        }
+    }
+
+    pub(crate) fn take_errors(&mut self) -> Diagnostics {
+        std::mem::replace(&mut self.errors,Diagnostics::new())
+    }
+
+    fn namespace(&self, alias: &str) -> Result<usize,String> {
+        self.namespaces.get(&(self.file_context,alias.to_string())).cloned()
+            .ok_or_else(|| format!("no such namespace {}",alias))
+    }
+
+    /* only exports of a file included with "as" can be reached through its alias */
+    fn lookup_qualified(&self, alias: &str, name: &str) -> Result<DefName,String> {
+        let context = self.namespace(alias)?;
+        if !self.ns_exports.contains(&(context,name.to_string())) {
+            if self.failed.contains(name) { self.used_failed.set(true); }
+            return Err(format!("{} does not export {}",alias,name));
+        }
+        self.defnames.get(&(Some(context),name.to_string())).cloned()
+            .ok_or_else(|| format!("No such function/procedure {}.{}",alias,name))
+    }
 
     fn lookup(&self, name: &str) -> Result<DefName,String> {
+        if let Some((alias,name)) = name.split_once('.') {
+            return self.lookup_qualified(alias,name);
+        }
         self.defnames
             .get(&(Some(self.file_context),name.to_string()))
             .or_else(||
                 self.defnames.get(&(None,name.to_string()))
             )
-            .ok_or_else(|| format!("No such function/procedure {}",name))
+            .ok_or_else(|| {
+                if self.failed.contains(name) { self.used_failed.set(true); }
+                format!("No such function/procedure {}",name)
+            })
             .cloned()
     }
 
     pub(crate) fn lookup_func(&self, name: &str) -> Result<usize,String> {
         match self.lookup(name) {
             Ok(DefName::Func(x)) => Ok(x),
+            Err(e) if name.contains('.') => Err(e),
             _ => Err(format!("cannot find function {}",name))
         }
     }
@@ -202,28 +276,151 @@ impl BuildContext {
         match self.lookup(name) {
             Ok(DefName::Proc(x)) => Ok(x),
             Ok(DefName::Code(x)) => Ok(x),
+            Err(e) if name.contains('.') => Err(e),
             _ => Err(format!("cannot find procedure/code {}",name))
         }
     }
 
+    fn lookup_const(&self, name: &str) -> Option<&(usize,PTExpression,usize)> {
+        self.consts
+            .get(&(Some(self.file_context),name.to_string()))
+            .or_else(||
+                self.consts.get(&(None,name.to_string()))
+            )
+    }
+
+    fn lookup_var_const(&self, var: &Variable) -> Option<&(usize,PTExpression,usize)> {
+        match &var.prefix {
+            None => self.lookup_const(&var.name),
+            Some(alias) => {
+                let context = self.namespace(alias).ok()?;
+                if !self.ns_exports.contains(&(context,var.name.to_string())) { return None; }
+                self.consts.get(&(Some(context),var.name.to_string()))
+            }
+        }
+    }
+
+    fn check_not_const(&self, var: &Variable) -> Result<(),String> {
+        if self.lookup_var_const(var).is_some() {
+            return Err(format!("cannot assign to const {}",var));
+        }
+        Ok(())
+    }
+
+    /* returns the fields and the name of the procedure which checks and returns them */
+    fn lookup_record(&self, name: &str) -> Result<(Vec<String>,String),String> {
+        let (fields,ctor) = if let Some((alias,record)) = name.split_once('.') {
+            let context = self.namespace(alias)?;
+            if !self.ns_exports.contains(&(context,record.to_string())) {
+                return Err(format!("{} does not export {}",alias,record));
+            }
+            (self.records.get(&(Some(context),record.to_string())),format!("{}.__record_{}",alias,record))
+        } else {
+            let fields = self.records
+                .get(&(Some(self.file_context),name.to_string()))
+                .or_else(|| self.records.get(&(None,name.to_string())));
+            (fields,format!("__record_{}",name))
+        };
+        fields.map(|f| (f.clone(),ctor)).ok_or_else(|| format!("no such record {}",name))
+    }
+
+    fn check_record_field(&self, var: &Variable) -> Result<(),String> {
+        if let Some(prefix) = &var.prefix {
+            if let Some((record,fields)) = self.record_vars.get(prefix) {
+                if !fields.contains(&var.name) {
+                    return Err(format!("{} is a {} record, which has no field {}",prefix,record,var.name));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn allocate_register(&mut self) -> usize {
         self.next_register += 1;
         self.next_register
     }
 
+    pub(crate) fn define_namespace(&mut self, alias: &str, context: usize) -> Result<(),String> {
+        let key = (self.file_context,alias.to_string());
+        if self.namespaces.contains_key(&key) {
+            return Err(format!("namespace {} already used",alias));
+        }
+        self.namespaces.insert(key,context);
+        self.namespaced.insert(context);
+        Ok(())
+    }
+
+    /* exports of a file included with "as" stay in its own context, others are global */
+    fn export_key(&mut self, name: &str, export: bool) -> Result<(Option<usize>,String),String> {
+        let namespaced = self.namespaced.contains(&self.file_context);
+        if !export || namespaced {
+            if export { self.ns_exports.insert((self.file_context,name.to_string())); }
+            return Ok((Some(self.file_context),name.to_string()));
+        }
+        let here = self.location.last_str();
+        if let Some(other) = self.export_origins.get(name) {
+            if other != &here {
+                return Err(format!("{} is exported both here and at {}, include one of them with \"as\" to keep them apart",name,other));
+            }
+        }
+        self.export_origins.insert(name.to_string(),here);
+        Ok((None,name.to_string()))
+    }
+
     pub(super) fn define_funcproc(&mut self, name: &str, fpdefn: BTFuncProcDefinition, is_proc: bool, bt: &mut BuildTree, export: bool) -> Result<usize,String> {
-        let definition = if is_proc { BTDefinition::Proc(fpdefn) } else { BTDefinition::Func(fpdefn) };
-        let id = bt.add_definition(definition);
-        let name_id = if is_proc { DefName::Proc(id) } else { DefName::Func(id) };
-        let context = if export { None } else { Some(self.file_context) };
-        let key = (context,name.to_string());
+        let key = self.export_key(name,export)?;
         if self.defnames.contains_key(&key) {
             return Err(format!("duplicate definition for {}",name));
         }
+        let definition = if is_proc { BTDefinition::Proc(fpdefn) } else { BTDefinition::Func(fpdefn) };
+        let id = bt.add_definition(definition);
+        let name_id = if is_proc { DefName::Proc(id) } else { DefName::Func(id) };
         self.defnames.insert(key,name_id);
         Ok(id)
     }
 
+    fn define_const(&mut self, bt: &mut BuildTree, name: &str, expr: &PTExpression, export: bool) -> Result<(),String> {
+        let key = self.export_key(name,export)?;
+        if self.consts.contains_key(&key) {
+            return Err(format!("duplicate definition for {}",name));
+        }
+        let index = bt.add_const(name,&self.location);
+        self.consts.insert(key,(self.file_context,expr.clone(),index));
+        Ok(())
+    }
+
+    /* A record is a procedure taking its fields in order and returning them unchanged, so its
+     * argument types and checks are enforced wherever a record is built.
+     */
+    fn define_record(&mut self, bt: &mut BuildTree, def: &PTRecordDef) -> Result<(),String> {
+        let mut fields: Vec<String> = vec![];
+        for field in &def.fields {
+            if fields.contains(&field.id) {
+                return Err(format!("duplicate field {} in record {}",field.id,def.name));
+            }
+            fields.push(field.id.clone());
+        }
+        let key = self.export_key(&def.name,def.export)?;
+        if self.records.contains_key(&key) {
+            return Err(format!("duplicate definition for {}",def.name));
+        }
+        self.records.insert(key,fields);
+        let procdef = PTProcDef {
+            name: format!("__record_{}",def.name),
+            modifiers: if def.export { vec![FuncProcModifier::Export] } else { vec![] },
+            args: def.fields.iter().map(|f| OrBundle::Normal(f.clone())).collect(),
+            captures: vec![],
+            block: vec![],
+            ret: def.fields.iter().map(|f| {
+                OrBundle::Normal(PTExpression::Variable(Variable { prefix: None, name: f.id.clone() }))
+            }).collect(),
+            ret_type: Some(def.fields.iter().map(|f| {
+                ArgTypeSpec { arg_types: f.typespec.arg_types.clone(), checks: vec![] }
+            }).collect())
+        };
+        self.build_procdef(bt,&procdef)
+    }
+
     pub(crate) fn define_code(&mut self, block: &CodeBlock, bt: &mut BuildTree) -> Result<(),String> {
         let key = (Some(self.file_context),block.name.to_string());
         if !self.defnames.contains_key(&key) {
@@ -299,6 +496,10 @@ impl BuildContext {
         /* Step 1: allocate temporaries */
         let mut regs = vec![];
         for v in vv.iter() {
+            if let OrBundleRepeater::Normal((var,_)) = v {
+                self.check_not_const(var)?;
+                self.check_record_field(var)?;
+            }
             let reg = match v {
                 OrBundleRepeater::Normal(_) => { 
                     OrBundleRepeater::Normal(BTLValue::Register(self.allocate_register(),BTRegisterType::Normal))
@@ -344,6 +545,9 @@ impl BuildContext {
         if declare {
             /* Step 3: declare variables */
             for v in vv.iter() {
+                if let OrBundleRepeater::Bundle(b) = v {
+                    self.record_vars.remove(b);
+                }
                 match v {
                     OrBundleRepeater::Repeater(_) => {},
                     _ => { self.build_declare(bt,v)? },
@@ -442,7 +646,13 @@ impl BuildContext {
                 })
             },
 
-            PTExpression::Variable(v) => BTExpression::Variable(v.clone()),
+            PTExpression::Variable(v) => {
+                self.check_record_field(v)?;
+                match self.lookup_var_const(v).cloned() {
+                    Some((context,expr,index)) => self.build_const(bt,&v.to_string(),context,&expr,index)?,
+                    None => BTExpression::Variable(v.clone())
+                }
+            },
             PTExpression::Call(c) => {
                 BTExpression::Function(self.build_func(bt,c)?)
             },
@@ -454,6 +664,32 @@ impl BuildContext {
         })
     }
 
+    /* A const is rebuilt at each use, in the context of its definition, wrapped in a call which
+     * the constant folder must eliminate. So a const never occupies a variable of its own.
+     */
+    fn build_const(&mut self, bt: &mut BuildTree, name: &str, context: usize, expr: &PTExpression, index: usize) -> Result<BTExpression,String> {
+        if self.building_consts.iter().any(|x| x == name) {
+            /* reported at the declaration, and not again at each use */
+            let msg = format!("const {} refers to itself",name);
+            if let Some((_,position)) = bt.get_const(index) {
+                self.errors.add(position.diagnostic(DiagnosticCode::Build,&msg));
+                self.used_failed.set(true);
+            }
+            return Err(msg);
+        }
+        let func = self.lookup_func("__operator_const")?;
+        let file_context = self.file_context;
+        self.file_context = context;
+        self.building_consts.push(name.to_string());
+        let value = self.build_expression(bt,expr);
+        self.building_consts.pop();
+        self.file_context = file_context;
+        Ok(BTExpression::Function(self.make_function_call(func,vec![
+            OrBundleRepeater::Normal(BTExpression::Constant(Constant::Number(OrderedFloat(index as f64)))),
+            OrBundleRepeater::Normal(value?)
+        ])))
+    }
+
     fn build_expr_ob(&mut self, bt: &mut BuildTree, expr: &OrBundle<PTExpression>) -> Result<OrBundle<BTExpression>,String> {
         Ok(match expr {
             OrBundle::Normal(n) => OrBundle::Normal(self.build_expression(bt,n)?),
@@ -461,35 +697,120 @@ impl BuildContext {
         })
     }
 
+    /* statements in a body carry their own position, so that later stages can cite the line */
+    fn build_block(&mut self, bt: &mut BuildTree, block: &[PTStatement]) {
+        let location = self.location.clone();
+        for stmt in block {
+            self.set_location(&stmt.position);
+            if let Err(e) = self.build_statement(bt,stmt) {
+                self.add_error(&e);
+            }
+        }
+        self.set_location(&location);
+    }
+
+    fn check_args_not_const(&self, args: &[OrBundle<TypedArgument>]) -> Result<(),String> {
+        for arg in args {
+            if let OrBundle::Normal(arg) = arg {
+                if self.lookup_const(&arg.id).is_some() {
+                    return Err(format!("argument {} has the same name as a const",arg.id));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn build_funcdef(&mut self, bt: &mut BuildTree, def: &PTFuncDef) -> Result<(),String> {
+        self.check_args_not_const(&def.args)?;
         let ret_type = def.value_type.as_ref().map(|x| vec![x.clone()]);
         let export = def.modifiers.contains(&FuncProcModifier::Export);
         let versions = def.versions();
         self.push_funcproc_target(false,&def.name,&def.args,ret_type,&def.captures,export,false,versions);
-        for stmt in &def.block {
-            self.build_statement(bt,stmt)?;
-        }
+        self.build_block(bt,&def.block);
         let expr = self.build_expr_ob(bt,&def.value)?;
         self.pop_funcproc_target(&[expr],bt)?;
         Ok(())
     }
 
     fn build_procdef(&mut self, bt: &mut BuildTree, def: &PTProcDef) -> Result<(),String> {
+        self.check_args_not_const(&def.args)?;
         let versions = def.versions();
         let export = def.modifiers.contains(&FuncProcModifier::Export);
         let entry = def.modifiers.contains(&FuncProcModifier::Entry);
         self.push_funcproc_target(true,&def.name,&def.args,def.ret_type.clone(),&def.captures,export,entry,versions);
-        for stmt in &def.block {
-            self.build_statement(bt,stmt)?;
-        }
+        self.build_block(bt,&def.block);
         let ret = def.ret.iter().map(|x| self.build_expr_ob(bt,x)).collect::<Result<Vec<_>,_>>()?;
         self.pop_funcproc_target(&ret,bt)?;
         Ok(())
     }
 
+    /* Eard has no branches, so each assignment in a when block becomes x = __operator_when(x,mask,value)
+     * which keeps x where the mask is false. The mask is evaluated once, before any assignment.
+     */
+    fn build_when(&mut self, bt: &mut BuildTree, mask: &PTExpression, assigns: &[(Variable,PTExpression)]) -> Result<(),String> {
+        let when = self.lookup_func("__operator_when")?;
+        let mask_reg = self.allocate_register();
+        let mask = self.build_expression(bt,mask)?;
+        let stmt = self.make_statement_value(None,vec![OrBundleRepeater::Normal(mask)],
+            Some(vec![OrBundleRepeater::Normal(BTLValue::Register(mask_reg,BTRegisterType::Normal))]));
+        self.add_statement(bt,stmt)?;
+        for (var,expr) in assigns {
+            self.check_not_const(var)?;
+            self.check_record_field(var)?;
+            let value = self.build_expression(bt,expr)?;
+            let call = self.make_function_call(when,vec![
+                OrBundleRepeater::Normal(BTExpression::Variable(var.clone())),
+                OrBundleRepeater::Normal(BTExpression::RegisterValue(mask_reg,BTRegisterType::Normal)),
+                OrBundleRepeater::Normal(value)
+            ]);
+            let stmt = self.make_statement_value(None,vec![OrBundleRepeater::Normal(BTExpression::Function(call))],
+                Some(vec![OrBundleRepeater::Normal(BTLValue::Variable(var.clone()))]));
+            self.add_statement(bt,stmt)?;
+        }
+        Ok(())
+    }
+
+    /* let tr = Transcript { .. } is let tr.start, tr.end, .. = __record_Transcript(..) in field order */
+    fn build_record_let(&mut self, bt: &mut BuildTree, prefix: &str, record: &str, values: &[(String,PTExpression)]) -> Result<(),String> {
+        let (fields,ctor) = self.lookup_record(record)?;
+        for (i,(name,_)) in values.iter().enumerate() {
+            if !fields.contains(name) {
+                return Err(format!("record {} has no field {}",record,name));
+            }
+            if values[..i].iter().any(|(other,_)| other == name) {
+                return Err(format!("field {} given more than once",name));
+            }
+        }
+        let mut args = vec![];
+        for field in &fields {
+            match values.iter().find(|(name,_)| name == field) {
+                Some((_,value)) => { args.push(OrBundleRepeater::Normal(value.clone())); },
+                None => { return Err(format!("record {} is missing field {}",record,field)); }
+            }
+        }
+        let vv = fields.iter().map(|field| {
+            OrBundleRepeater::Normal((Variable { prefix: Some(prefix.to_string()), name: field.clone() },vec![]))
+        }).collect::<Vec<_>>();
+        let xx = vec![OrBundle::Normal(PTExpression::Call(PTCall { name: ctor, args, is_macro: false }))];
+        self.record_vars.remove(prefix);
+        self.make_statement(&vv,&xx,true,bt)?;
+        self.record_vars.insert(prefix.to_string(),(record.to_string(),fields));
+        Ok(())
+    }
+
+    /* record variables outside a function or procedure are not visible inside it */
+    fn build_without_records<F>(&mut self, bt: &mut BuildTree, cb: F) -> Result<(),String>
+            where F: FnOnce(&mut Self,&mut BuildTree) -> Result<(),String> {
+        let record_vars = std::mem::take(&mut self.record_vars);
+        let out = cb(self,bt);
+        self.record_vars = record_vars;
+        out
+    }
+
     pub(super) fn build_statement(&mut self, bt: &mut BuildTree, stmt: &PTStatement) -> Result<(),String> {
+        self.used_failed.set(false);
         match &stmt.value {
-            PTStatementValue::Include(_,_) | PTStatementValue::MacroCall(_) => {
+            PTStatementValue::Include(_,_,_) | PTStatementValue::MacroCall(_) => {
                 panic!("item should have been eliminated from build tree");
             },
             PTStatementValue::Header(group,name,version) => {
@@ -498,9 +819,12 @@ impl BuildContext {
             PTStatementValue::Version(name,a,b) => {
                 self.add_statement(bt,BTStatementValue::Version(name.to_string(),*a,*b))?;
             }
-            PTStatementValue::FuncDef(f) => { self.build_funcdef(bt,f)?; },
-            PTStatementValue::ProcDef(p) => { self.build_procdef(bt,p)?; },
+            PTStatementValue::FuncDef(f) => { self.build_without_records(bt,|this,bt| this.build_funcdef(bt,f))?; },
+            PTStatementValue::ProcDef(p) => { self.build_without_records(bt,|this,bt| this.build_procdef(bt,p))?; },
+            PTStatementValue::Namespace(alias,context) => { self.define_namespace(alias,*context)?; },
             PTStatementValue::Code(c) => { self.define_code(c,bt)?; },
+            PTStatementValue::ConstDef(name,expr,export) => { self.define_const(bt,name,expr,*export)?; },
+            PTStatementValue::RecordDef(r) => { self.build_without_records(bt,|this,bt| this.define_record(bt,r))?; },
 
             PTStatementValue::LetStatement(vv,xx) => {
                 self.make_statement(vv,xx,true,bt)?;
@@ -521,6 +845,12 @@ impl BuildContext {
                 let xx = xx.iter().map(|x| OrBundle::Normal(x.clone())).collect::<Vec<_>>();
                 self.make_statement(&vv,&xx,false,bt)?;
             },
+            PTStatementValue::WhenStatement(mask,assigns) => {
+                self.build_when(bt,mask,assigns)?;
+            },
+            PTStatementValue::RecordLet(prefix,record,values) => {
+                self.build_record_let(bt,prefix,record,values)?;
+            },
             PTStatementValue::Expression(expr) => {
                 if let PTExpression::Call(c) = expr {
                     match self.lookup(&c.name)? {
@@ -0,0 +1,585 @@
+use std::fs;
+use pest::{Parser, iterators::Pair, error::LineColLocation};
+use crate::controller::diagnostic::{Diagnostic, DiagnosticCode, SourceSpan};
+use super::parser::{EardParser, Rule};
+
+const INDENT : &str = "    ";
+const MAX_WIDTH : usize = 100;
+const SEPARATOR : &str = ", ";
+
+struct Comment {
+    start: usize,
+    end: usize,
+    text: String
+}
+
+fn squash(pair: &Pair<Rule>) -> String {
+    pair.as_str().split_whitespace().collect::<Vec<_>>().join("")
+}
+
+fn starts_with_paren(pair: &Pair<Rule>, skip: usize) -> bool {
+    pair.as_str().get(skip..).unwrap_or("").trim_start().starts_with('(')
+}
+
+/* Parenthesised tuples are told apart from parenthesised expressions by where the first part starts. */
+fn is_tuple(pair: &Pair<Rule>) -> bool {
+    pair.clone().into_inner().next().map(|first| first.as_span().start() != pair.as_span().start()).unwrap_or(false)
+}
+
+fn indent(level: usize) -> String { INDENT.repeat(level) }
+
+struct Formatter<'a> {
+    input: &'a str,
+    comments: Vec<Comment>,
+    next: usize,
+    out: String,
+    last: usize
+}
+
+impl<'a> Formatter<'a> {
+    fn new(input: &'a str, comments: Vec<Comment>) -> Formatter<'a> {
+        Formatter { input, comments, next: 0, out: String::new(), last: 0 }
+    }
+
+    fn newlines(&self, from: usize, to: usize) -> usize {
+        if from >= to { return 0; }
+        self.input[from..to].matches('\n').count()
+    }
+
+    fn has_comment_within(&self, start: usize, end: usize) -> bool {
+        self.comments[self.next..].iter().any(|c| c.start >= start && c.start < end)
+    }
+
+    /* at most one blank line is kept between things, and never at the start of a block */
+    fn gap(&mut self, at: usize, first: bool) {
+        if !first && self.newlines(self.last,at) >= 2 {
+            self.out.push('\n');
+        }
+    }
+
+    fn comments_before(&mut self, pos: usize, level: usize, first: &mut bool) {
+        while self.next < self.comments.len() && self.comments[self.next].start < pos {
+            let start = self.comments[self.next].start;
+            self.gap(start,*first);
+            let line = format!("{}{}\n",indent(level),self.comments[self.next].text);
+            self.out.push_str(&line);
+            self.last = self.comments[self.next].end;
+            self.next += 1;
+            *first = false;
+        }
+    }
+
+    /* Comments inside an item which weren't placed elsewhere, and those on the line where it ends,
+     * go at the end of the item.
+     */
+    fn finish_item(&mut self, end: usize, level: usize, close: usize) {
+        let mut count = 0;
+        self.last = end;
+        while self.next < self.comments.len() {
+            let comment = &self.comments[self.next];
+            if comment.start >= close { break; }
+            if comment.start >= end && self.newlines(end,comment.start) > 0 { break; }
+            if count == 0 {
+                self.out.push(' ');
+            } else {
+                self.out.push_str(&format!("\n{}",indent(level)));
+            }
+            self.out.push_str(&comment.text);
+            self.last = self.last.max(comment.end);
+            self.next += 1;
+            count += 1;
+        }
+        self.out.push('\n');
+    }
+
+    fn items(&mut self, items: Vec<Pair<Rule>>, level: usize, close: usize) {
+        let mut first = true;
+        for item in items {
+            let start = item.as_span().start();
+            let end = item.as_span().end();
+            self.comments_before(start,level,&mut first);
+            self.gap(start,first);
+            first = false;
+            self.out.push_str(&indent(level));
+            self.item(item,level);
+            self.finish_item(end,level,close);
+        }
+        self.comments_before(close,level,&mut first);
+    }
+
+    fn block(&mut self, head: String, items: Vec<Pair<Rule>>, level: usize, start: usize, close: usize) {
+        self.out.push_str(&head);
+        if items.is_empty() && !self.has_comment_within(start,close) {
+            self.out.push_str(" {}");
+            return;
+        }
+        self.out.push_str(" {\n");
+        self.items(items,level+1,close);
+        self.out.push_str(&format!("{}}}",indent(level)));
+    }
+
+    fn item(&mut self, pair: Pair<Rule>, level: usize) {
+        let start = pair.as_span().start();
+        let close = pair.as_span().end() - 1;
+        match pair.as_rule() {
+            Rule::code_block => {
+                let mut children = pair.into_inner();
+                let head = self.code_header(children.next().unwrap());
+                self.block(head,children.collect(),level,start,close);
+            },
+            Rule::code_impl => {
+                let mut children = pair.into_inner();
+                let head = self.impl_header(children.next().unwrap());
+                self.block(head,children.collect(),level,start,close);
+            },
+            Rule::function | Rule::procedure => {
+                let single_line = !pair.as_str().contains('\n') && !self.has_comment_within(start,close);
+                let (head,body) = self.funcproc(pair);
+                if single_line {
+                    let stmts = body.iter().map(|s| self.statement(s.clone(),level+1)).collect::<Vec<_>>();
+                    let line = format!("{} {{ {} }}",head,stmts.join(" "));
+                    if !line.contains('\n') && indent(level).len() + line.len() <= MAX_WIDTH {
+                        self.out.push_str(&line);
+                        return;
+                    }
+                }
+                self.block(head,body,level,start,close);
+            },
+            Rule::record_def => {
+                let single_line = !pair.as_str().contains('\n') && !self.has_comment_within(start,close);
+                let mut children = pair.into_inner().collect::<Vec<_>>();
+                let fields = children.split_off(children.iter().position(|c| c.as_rule() == Rule::identifier).unwrap()+1);
+                let export = if children.len() > 1 { "export " } else { "" };
+                let head = format!("{}record {}",export,children.pop().unwrap().as_str().trim());
+                if single_line {
+                    let fields = fields.iter().map(|f| self.record_field(f.clone())).collect::<Vec<_>>();
+                    let line = format!("{} {{ {} }}",head,fields.join(SEPARATOR));
+                    if !fields.is_empty() && indent(level).len() + line.len() <= MAX_WIDTH {
+                        self.out.push_str(&line);
+                        return;
+                    }
+                }
+                self.block(head,fields,level,start,close);
+            },
+            Rule::record_field => {
+                let text = format!("{},",self.record_field(pair));
+                self.out.push_str(&text);
+            },
+            Rule::inner_block if pair.clone().into_inner().next().map(|c| c.as_rule()) == Some(Rule::when_statement) => {
+                let mut children = pair.into_inner().next().unwrap().into_inner();
+                let head = format!("when ({})",self.expr(children.next().unwrap(),level));
+                self.block(head,children.collect(),level,start,close);
+            },
+            _ => {
+                let text = self.statement(pair,level);
+                self.out.push_str(&text);
+            }
+        }
+    }
+
+    fn statement(&self, pair: Pair<Rule>, level: usize) -> String {
+        match pair.as_rule() {
+            Rule::inner_block => {
+                let child = pair.into_inner().next().unwrap();
+                match child.as_rule() {
+                    Rule::let_statement | Rule::let_repeater_statement | Rule::let_record_statement |
+                    Rule::modify_statement | Rule::when_statement => {
+                        self.statement(child,level)
+                    },
+                    _ => format!("{};",self.expr(child,level))
+                }
+            },
+            Rule::header => {
+                let parts = pair.into_inner().map(|p| p.as_str().trim().to_string()).collect::<Vec<_>>();
+                format!("program {};",parts.join(" "))
+            },
+            Rule::lib_version => {
+                let parts = pair.into_inner().map(|p| squash(&p)).collect::<Vec<_>>();
+                format!("version {};",parts.join(" "))
+            },
+            Rule::include | Rule::fixed_include => {
+                let keyword = if pair.as_rule() == Rule::include { "include" } else { "refer" };
+                let parts = pair.into_inner().map(|p| p.as_str().trim().to_string()).collect::<Vec<_>>();
+                if parts.len() > 1 {
+                    format!("{} {} as {};",keyword,parts[0],parts[1])
+                } else {
+                    format!("{} {};",keyword,parts[0])
+                }
+            },
+            Rule::let_statement => {
+                let mut children = pair.into_inner();
+                let decls = self.let_decls(children.next().unwrap());
+                let rhs = self.rhs(children.next().unwrap(),level);
+                format!("let {} = {}",decls,rhs)
+            },
+            Rule::let_record_statement => {
+                let mut children = pair.into_inner();
+                let prefix = squash(&children.next().unwrap());
+                let record = squash(&children.next().unwrap());
+                let values = children.map(|v| {
+                    let mut parts = v.into_inner();
+                    let field = parts.next().unwrap().as_str().trim().to_string();
+                    format!("{}: {}",field,self.expr(parts.next().unwrap(),level))
+                }).collect::<Vec<_>>();
+                if !values.is_empty() {
+                    format!("let {} = {} {{ {} }};",prefix,record,values.join(SEPARATOR))
+                } else {
+                    format!("let {} = {} {{}};",prefix,record)
+                }
+            },
+            Rule::let_repeater_statement => {
+                let parts = pair.into_inner().map(|p| squash(&p)).collect::<Vec<_>>();
+                format!("let {} = {};",parts[0],parts[1])
+            },
+            Rule::modify_statement => {
+                let tuple = is_tuple(&pair);
+                let mut lvalues = vec![];
+                let mut rhs = String::new();
+                for child in pair.into_inner() {
+                    match child.as_rule() {
+                        Rule::variable => { lvalues.push(squash(&child)); },
+                        _ => { rhs = self.rhs(child,level); }
+                    }
+                }
+                if tuple {
+                    format!("({}) = {}",lvalues.join(SEPARATOR),rhs)
+                } else {
+                    format!("{} = {}",lvalues.join(SEPARATOR),rhs)
+                }
+            },
+            Rule::when_statement => {
+                let mut children = pair.into_inner();
+                let mask = self.expr(children.next().unwrap(),level);
+                let stmts = children.map(|a| self.statement(a,level+1)).collect::<Vec<_>>();
+                if !stmts.is_empty() {
+                    format!("when ({}) {{ {} }}",mask,stmts.join(" "))
+                } else {
+                    format!("when ({}) {{}}",mask)
+                }
+            },
+            Rule::when_assignment => {
+                let mut children = pair.into_inner();
+                let var = squash(&children.next().unwrap());
+                format!("{} = {};",var,self.expr(children.next().unwrap(),level))
+            },
+            Rule::const_decl => {
+                let mut children = pair.into_inner().collect::<Vec<_>>();
+                let value = self.expr(children.pop().unwrap(),level);
+                let name = children.pop().unwrap().as_str().trim().to_string();
+                let export = if !children.is_empty() { "export " } else { "" };
+                format!("{}const {} = {};",export,name,value)
+            },
+            Rule::capture_decl => {
+                let parts = pair.into_inner().map(|p| squash(&p)).collect::<Vec<_>>();
+                format!("capture {};",parts.join(SEPARATOR))
+            },
+            Rule::procedure_expression => {
+                let tuple = is_tuple(&pair);
+                let parts = pair.into_inner().collect::<Vec<_>>();
+                if tuple {
+                    self.list("(",")",parts,level)
+                } else {
+                    parts.into_iter().next().map(|p| self.expr(p,level)).unwrap_or_default()
+                }
+            },
+            Rule::opcode_statement => {
+                let mut children = pair.into_inner();
+                let mut out = format!("opcode {}",children.next().unwrap().as_str());
+                for reg in children.next().unwrap().into_inner() {
+                    out.push_str(&format!("{}{}",SEPARATOR,squash(&reg)));
+                }
+                out.push(';');
+                out
+            },
+            _ => self.expr(pair,level)
+        }
+    }
+
+    fn let_decls(&self, pair: Pair<Rule>) -> String {
+        let tuple = is_tuple(&pair);
+        let decls = pair.into_inner().map(|decl| {
+            let mut parts = decl.into_inner().map(|p| squash(&p)).collect::<Vec<_>>();
+            if parts.len() > 1 {
+                let checks = parts.split_off(1);
+                format!("{}: {}",parts[0],checks.join(" "))
+            } else {
+                parts.join("")
+            }
+        }).collect::<Vec<_>>();
+        if tuple { format!("({})",decls.join(SEPARATOR)) } else { decls.join(SEPARATOR) }
+    }
+
+    fn rhs(&self, pair: Pair<Rule>, level: usize) -> String {
+        let tuple = is_tuple(&pair);
+        let parts = pair.into_inner().collect::<Vec<_>>();
+        if tuple {
+            format!("{};",self.list("(",")",parts,level))
+        } else {
+            format!("{};",self.expr(parts.into_iter().next().unwrap(),level))
+        }
+    }
+
+    fn list(&self, open: &str, close: &str, items: Vec<Pair<Rule>>, level: usize) -> String {
+        let parts = items.iter().map(|p| self.expr(p.clone(),level)).collect::<Vec<_>>();
+        let line = format!("{}{}{}",open,parts.join(SEPARATOR),close);
+        if parts.len() < 2 || (!line.contains('\n') && indent(level).len() + line.len() <= MAX_WIDTH) {
+            return line;
+        }
+        let parts = items.into_iter().map(|p| self.expr(p,level+1)).collect::<Vec<_>>();
+        let sep = format!(",\n{}",indent(level+1));
+        format!("{}\n{}{}\n{}{}",open,indent(level+1),parts.join(&sep),indent(level),close)
+    }
+
+    fn expr(&self, pair: Pair<Rule>, level: usize) -> String {
+        match pair.as_rule() {
+            Rule::expression | Rule::expr_or_bundle | Rule::argument | Rule::function_value => {
+                self.expr(pair.into_inner().next().unwrap(),level)
+            },
+            Rule::expr1 | Rule::expr2 | Rule::expr3 | Rule::expr4 | Rule::expr5 | Rule::expr6 => {
+                pair.into_inner().map(|child| {
+                    match child.as_rule() {
+                        Rule::infix1 | Rule::infix2 | Rule::infix3 | Rule::infix4 | Rule::infix5 | Rule::infix6 => {
+                            format!(" {} ",child.as_str().trim())
+                        },
+                        _ => self.expr(child,level)
+                    }
+                }).collect::<Vec<_>>().join("")
+            },
+            Rule::simple_expression => {
+                let children = pair.into_inner().collect::<Vec<_>>();
+                match children[0].as_rule() {
+                    Rule::prefix_operators => {
+                        format!("{}{}",children[0].as_str().trim(),self.expr(children[1].clone(),level))
+                    },
+                    Rule::expression => format!("({})",self.expr(children[0].clone(),level)),
+                    _ => self.expr(children[0].clone(),level)
+                }
+            },
+            Rule::finite_seq => self.list("[","]",pair.into_inner().collect(),level),
+            Rule::infinite_seq => format!("[{},...]",self.expr(pair.into_inner().next().unwrap(),level)),
+            Rule::func_or_proc_call | Rule::macro_call => {
+                let mut children = pair.into_inner().peekable();
+                let mut name = children.next().unwrap().as_str().trim().to_string();
+                if let Some(id) = children.next_if(|c| c.as_rule() == Rule::identifier) {
+                    name = format!("{}.{}",name,id.as_str().trim());
+                }
+                self.list(&format!("{}(",name),")",children.collect(),level)
+            },
+            Rule::constant => pair.as_str().trim().to_string(),
+            _ => squash(&pair)
+        }
+    }
+
+    fn typed(&self, pair: Pair<Rule>) -> String {
+        pair.into_inner().map(|p| squash(&p)).collect::<Vec<_>>().join(" ")
+    }
+
+    fn record_field(&self, pair: Pair<Rule>) -> String {
+        let mut parts = pair.into_inner();
+        let name = parts.next().unwrap().as_str().trim().to_string();
+        format!("{}: {}",name,self.typed(parts.next().unwrap()))
+    }
+
+    fn funcproc<'p>(&self, pair: Pair<'p,Rule>) -> (String,Vec<Pair<'p,Rule>>) {
+        let keyword = if pair.as_rule() == Rule::function { "function" } else { "procedure" };
+        let mut head = vec![];
+        let mut body = vec![];
+        for child in pair.into_inner() {
+            match child.as_rule() {
+                Rule::func_modifiers | Rule::proc_modifiers => {
+                    for modifier in child.into_inner() {
+                        let modifier = modifier.into_inner().next().unwrap();
+                        match modifier.as_rule() {
+                            Rule::version => {
+                                let spec = modifier.into_inner().next().unwrap();
+                                let parts = spec.into_inner().map(|p| squash(&p)).collect::<Vec<_>>();
+                                head.push(format!("version({})",parts.join(" ")));
+                            },
+                            _ => { head.push(squash(&modifier)); }
+                        }
+                    }
+                    head.push(keyword.to_string());
+                },
+                Rule::identifier => { head.push(child.as_str().to_string()); },
+                Rule::funcproc_args => {
+                    let args = child.into_inner().map(|arg| {
+                        let arg = arg.into_inner().next().unwrap();
+                        if arg.as_rule() == Rule::funcproc_arg_named {
+                            let mut parts = arg.into_inner();
+                            let name = parts.next().unwrap().as_str().to_string();
+                            if let Some(extras) = parts.next() {
+                                return format!("{}: {}",name,self.typed(extras));
+                            }
+                            name
+                        } else {
+                            squash(&arg)
+                        }
+                    }).collect::<Vec<_>>();
+                    let name = head.pop().unwrap_or_default();
+                    head.push(format!("{}({})",name,args.join(SEPARATOR)));
+                },
+                Rule::function_return => {
+                    if let Some(ret) = child.into_inner().next() {
+                        head.push(format!("-> {}",self.typed(ret)));
+                    }
+                },
+                Rule::procedure_return_option => {
+                    if let Some(ret) = child.into_inner().next() {
+                        let tuple = starts_with_paren(&ret,2);
+                        let types = ret.into_inner().map(|t| self.typed(t)).collect::<Vec<_>>();
+                        if tuple {
+                            head.push(format!("-> ({})",types.join(SEPARATOR)));
+                        } else {
+                            head.push(format!("-> {}",types.join(SEPARATOR)));
+                        }
+                    }
+                },
+                Rule::capture_decls => { body.extend(child.into_inner()); },
+                Rule::procedure_expression => {
+                    if child.clone().into_inner().next().is_some() {
+                        body.push(child);
+                    }
+                },
+                _ => { body.push(child); }
+            }
+        }
+        (head.join(" "),body)
+    }
+
+    fn code_arg(&self, pair: Pair<Rule>) -> String {
+        let mut parts = vec![];
+        for child in pair.into_inner() {
+            match child.as_rule() {
+                Rule::code_arg_modifiers => {
+                    parts.extend(child.into_inner().map(|m| squash(&m)));
+                },
+                _ => { parts.push(squash(&child)); }
+            }
+        }
+        parts.join(" ")
+    }
+
+    fn code_header(&self, pair: Pair<Rule>) -> String {
+        let mut out = String::new();
+        for child in pair.into_inner() {
+            match child.as_rule() {
+                Rule::code_modifiers => {
+                    for modifier in child.into_inner() {
+                        out.push_str(&format!("{} ",squash(&modifier)));
+                    }
+                },
+                Rule::identifier => { out.push_str(&format!("code {}",child.as_str())); },
+                Rule::code_arguments => {
+                    let args = child.into_inner().map(|a| self.code_arg(a)).collect::<Vec<_>>();
+                    out.push_str(&format!("({})",args.join(SEPARATOR)));
+                },
+                Rule::code_return => {
+                    let tuple = starts_with_paren(&child,2);
+                    let rets = child.into_inner().map(|a| self.code_arg(a)).collect::<Vec<_>>();
+                    if tuple {
+                        out.push_str(&format!(" -> ({})",rets.join(SEPARATOR)));
+                    } else if !rets.is_empty() {
+                        out.push_str(&format!(" -> {}",rets.join(SEPARATOR)));
+                    }
+                },
+                _ => {}
+            }
+        }
+        out
+    }
+
+    fn impl_var(&self, pair: Pair<Rule>) -> String {
+        let var = pair.into_inner().next().unwrap();
+        match var.as_rule() {
+            Rule::impl_real_arg => {
+                let parts = var.into_inner().map(|p| squash(&p)).collect::<Vec<_>>();
+                format!("{}: {}",parts[0],parts[1])
+            },
+            Rule::constant => var.as_str().trim().to_string(),
+            _ => squash(&var)
+        }
+    }
+
+    fn impl_header(&self, pair: Pair<Rule>) -> String {
+        let mut children = pair.into_inner();
+        let args = children.next().unwrap().into_inner().map(|a| self.impl_var(a)).collect::<Vec<_>>();
+        let mut out = format!("impl ({})",args.join(SEPARATOR));
+        let ret = children.next().unwrap();
+        let tuple = starts_with_paren(&ret,2);
+        let rets = ret.into_inner().map(|a| self.impl_var(a)).collect::<Vec<_>>();
+        if tuple {
+            out.push_str(&format!(" -> ({})",rets.join(SEPARATOR)));
+        } else if !rets.is_empty() {
+            out.push_str(&format!(" -> {}",rets.join(SEPARATOR)));
+        }
+        out
+    }
+
+    fn file(mut self, pair: Pair<Rule>) -> String {
+        let items = pair.into_inner().filter_map(|block| {
+            if block.as_rule() == Rule::block { block.into_inner().next() } else { None }
+        }).collect::<Vec<_>>();
+        self.items(items,0,self.input.len());
+        self.out
+    }
+}
+
+fn parse_error(filename: &str, e: pest::error::Error<Rule>) -> Diagnostic {
+    let (line,column) = match e.line_col {
+        LineColLocation::Pos(pos) => pos,
+        LineColLocation::Span(start,_) => start
+    };
+    let mut diagnostic = Diagnostic::new(DiagnosticCode::Parse,&e.variant.message());
+    diagnostic.span = Some(Box::new(SourceSpan {
+        filename: filename.to_string(),
+        path: None,
+        line: line as u32,
+        column: column as u32,
+        source: Some(e.line().to_string())
+    }));
+    diagnostic
+}
+
+fn find_comments(input: &str) -> Vec<Comment> {
+    let mut out = vec![];
+    if let Ok(mut pairs) = EardParser::parse(Rule::comments,input) {
+        for pair in pairs.next().unwrap().into_inner() {
+            if pair.as_rule() == Rule::comment {
+                let text = pair.as_str().lines().map(|line| line.trim_end()).collect::<Vec<_>>().join("\n");
+                out.push(Comment { start: pair.as_span().start(), end: pair.as_span().end(), text });
+            }
+        }
+    }
+    out
+}
+
+/* Reformats eard source into the canonical layout, keeping comments. */
+pub fn format_source(filename: &str, input: &str) -> Result<String,Diagnostic> {
+    let mut pairs = EardParser::parse(Rule::file,input).map_err(|e| parse_error(filename,e))?;
+    let formatter = Formatter::new(input,find_comments(input));
+    Ok(formatter.file(pairs.next().unwrap()))
+}
+
+/* Reformats a file in place, or with check just looks. Returns whether it was already formatted. */
+pub fn format_file(filename: &str, check: bool) -> Result<bool,String> {
+    let input = fs::read_to_string(filename).map_err(|e| format!("cannot read {}: {}",filename,e))?;
+    let output = format_source(filename,&input).map_err(|e| e.render())?;
+    if output == input { return Ok(true); }
+    if !check {
+        fs::write(filename,output).map_err(|e| format!("cannot write {}: {}",filename,e))?;
+    }
+    Ok(false)
+}
+
+/* Formats each file, or with check lists those which aren't formatted. Returns false only when check finds one. */
+pub fn format_files(files: &[String], check: bool) -> Result<bool,String> {
+    let mut clean = true;
+    for filename in files {
+        if format_file(filename,check)? { continue; }
+        clean = false;
+        if check {
+            println!("{} is not formatted",filename);
+        }
+    }
+    Ok(clean || !check)
+}
@@ -1,11 +1,12 @@
 use ordered_float::OrderedFloat;
+use pest::error::LineColLocation;
 use pest_consume::{Parser, Error, match_nodes};
-use crate::{model::{checkstypes::{CheckType, Check, AtomicTypeSpec, TypeSpec, TypedArgument, ArgTypeSpec}, constants::Constant, compiled::Opcode, codeblocks::{CodeImplArgument, CodeReturn, CodeImplVariable, CodeArgument, ImplBlock, CodeBlock, CodeModifier}}, controller::source::ParsePosition};
-use super::{parsetree::{ PTExpression, PTCall, PTFuncDef, PTProcDef, PTStatement, PTStatementValue, FuncProcModifier, CodeArgModifier }, femodel::{OrBundle, OrBundleRepeater}, buildtree::Variable};
+use crate::{model::{checkstypes::{CheckType, Check, AtomicTypeSpec, TypeSpec, TypedArgument, ArgTypeSpec}, constants::Constant, compiled::Opcode, codeblocks::{CodeImplArgument, CodeReturn, CodeImplVariable, CodeArgument, ImplBlock, CodeBlock, CodeModifier}}, controller::{source::ParsePosition, diagnostic::{Diagnostic, DiagnosticCode}}};
+use super::{parsetree::{ PTExpression, PTCall, PTFuncDef, PTProcDef, PTRecordDef, PTStatement, PTStatementValue, FuncProcModifier, CodeArgModifier }, femodel::{OrBundle, OrBundleRepeater}, buildtree::Variable};
 
 #[derive(Parser)]
 #[grammar = "frontend/eard.pest"]
-struct EardParser;
+pub(crate) struct EardParser;
 
 #[derive(Clone)]
 struct ParseFixedState {
@@ -202,12 +203,18 @@ impl EardParser {
         ))
     }
 
-    fn include(input: Node) -> PestResult<String> {
-        Ok(match_nodes!(input.into_children(); [string(s)] => s ))
+    fn include(input: Node) -> PestResult<(String,Option<String>)> {
+        Ok(match_nodes!(input.into_children();
+            [string(s)] => (s,None),
+            [string(s),identifier(a)] => (s,Some(a))
+        ))
     }
 
-    fn fixed_include(input: Node) -> PestResult<String> {
-        Ok(match_nodes!(input.into_children(); [string(s)] => s ))
+    fn fixed_include(input: Node) -> PestResult<(String,Option<String>)> {
+        Ok(match_nodes!(input.into_children();
+            [string(s)] => (s,None),
+            [string(s),identifier(a)] => (s,Some(a))
+        ))
     }
 
     fn bundle(input: Node) -> PestResult<String> {
@@ -315,7 +322,9 @@ impl EardParser {
 
     fn func_or_proc_call(input: Node) -> PestResult<PTCall> {
         Ok(match_nodes!(input.into_children();
+            [prefix(p),identifier(name),argument(args)..] => PTCall { name: format!("{}.{}",p,name), args: args.collect(), is_macro: false },
             [identifier(name),argument(args)..] => PTCall { name, args: args.collect(), is_macro: false },
+            [prefix(p),identifier(name)] => PTCall { name: format!("{}.{}",p,name), args: vec![], is_macro: false },
             [identifier(name)] => PTCall { name, args: vec![], is_macro: false }
         ))
     }
@@ -433,6 +442,20 @@ impl EardParser {
         ))
     }
 
+    fn when_assignment(input: Node) -> PestResult<(Variable,PTExpression)> {
+        Ok(match_nodes!(input.into_children();
+            [variable(v),expression(x)] => (v,x)
+        ))
+    }
+
+    fn when_statement(input: Node) -> PestResult<PTStatementValue> {
+        Ok(match_nodes!(input.into_children();
+            [expression(m),when_assignment(a)..] => {
+                PTStatementValue::WhenStatement(m,a.collect())
+            }
+        ))
+    }
+
     fn code_modifiers(input: Node) -> PestResult<Vec<CodeModifier>> {
         let mut out = vec![];
         for child in input.into_children() {
@@ -627,10 +650,12 @@ impl EardParser {
     }
 
     fn inner_block(input: Node) -> PestResult<PTStatement> {
-        let line_no = input.as_span().start_pos().line_col().0;
-        let position = input.user_data().position.at_line(line_no as u32);
+        let (line_no,column) = input.as_span().start_pos().line_col();
+        let position = input.user_data().position.at_line(line_no as u32,column as u32);
         let context = input.user_data().context;
         let value = match_nodes!(input.into_children();
+            [when_statement(s)] => s,
+            [let_record_statement(s)] => s,
             [let_statement(s)] => s,
             [let_repeater_statement(s)] => s,
             [modify_statement(s)] => s,
@@ -656,18 +681,59 @@ impl EardParser {
         ))
     }
 
+    fn record_field(input: Node) -> PestResult<TypedArgument> {
+        Ok(match_nodes!(input.into_children();
+            [identifier(id),funcproc_arg_extras(typespec)] => TypedArgument { id, typespec }
+        ))
+    }
+
+    fn record_def(input: Node) -> PestResult<PTRecordDef> {
+        Ok(match_nodes!(input.into_children();
+            [export(_),identifier(name),record_field(f)..] => PTRecordDef { name, export: true, fields: f.collect() },
+            [identifier(name),record_field(f)..] => PTRecordDef { name, export: false, fields: f.collect() }
+        ))
+    }
+
+    fn record_name(input: Node) -> PestResult<String> {
+        Ok(match_nodes!(input.into_children();
+            [prefix(p),identifier(id)] => format!("{}.{}",p,id),
+            [identifier(id)] => id
+        ))
+    }
+
+    fn record_value(input: Node) -> PestResult<(String,PTExpression)> {
+        Ok(match_nodes!(input.into_children();
+            [identifier(id),expression(x)] => (id,x)
+        ))
+    }
+
+    fn let_record_statement(input: Node) -> PestResult<PTStatementValue> {
+        Ok(match_nodes!(input.into_children();
+            [prefix(p),record_name(r),record_value(v)..] => PTStatementValue::RecordLet(p,r,v.collect())
+        ))
+    }
+
+    fn const_decl(input: Node) -> PestResult<PTStatementValue> {
+        Ok(match_nodes!(input.into_children();
+            [export(_),identifier(id),expression(x)] => PTStatementValue::ConstDef(id,x,true),
+            [identifier(id),expression(x)] => PTStatementValue::ConstDef(id,x,false)
+        ))
+    }
+
     fn block(input: Node) -> PestResult<PTStatement> {
         let context = input.user_data().context;
-        let line_no = input.as_span().start_pos().line_col().0;
-        let position = input.user_data().position.at_line(line_no as u32);
+        let (line_no,column) = input.as_span().start_pos().line_col();
+        let position = input.user_data().position.at_line(line_no as u32,column as u32);
         let value = match_nodes!(input.into_children();
             [header(v)] => v,
             [lib_version(v)] => v,
             [code_block(block)] => PTStatementValue::Code(block),
-            [include(s)] => PTStatementValue::Include(s,false),
-            [fixed_include(s)] => PTStatementValue::Include(s,true),
+            [include((s,a))] => PTStatementValue::Include(s,false,a),
+            [fixed_include((s,a))] => PTStatementValue::Include(s,true,a),
             [function(f)] => PTStatementValue::FuncDef(f),
             [procedure(p)] => PTStatementValue::ProcDef(p),
+            [const_decl(c)] => c,
+            [record_def(r)] => PTStatementValue::RecordDef(r),
             [inner_block(b)] => { return Ok(b); },
         );
         Ok(PTStatement { value, position, context })
@@ -860,7 +926,15 @@ fn do_parse_eard(input: &str, position: &ParsePosition, optimise: bool, context:
     EardParser::file(input)
 }
 
-pub(crate) fn parse_eard(position: &ParsePosition, filename: &str, fixed: bool, optimise: bool, context: usize) -> Result<Vec<PTStatement>,String> {
-    let (input,new_pos) = position.push(filename,fixed)?;
-    do_parse_eard(&input,&new_pos,optimise,context).map_err(|e| e.to_string())
+pub(crate) fn parse_eard(position: &ParsePosition, filename: &str, fixed: bool, optimise: bool, context: usize) -> Result<Vec<PTStatement>,Diagnostic> {
+    let (input,new_pos) = position.push(filename,fixed).map_err(|e| Diagnostic::new(DiagnosticCode::Include,&e))?;
+    do_parse_eard(&input,&new_pos,optimise,context).map_err(|e| {
+        let (line_no,column) = match e.line_col {
+            LineColLocation::Pos(pos) => pos,
+            LineColLocation::Span(start,_) => start
+        };
+        new_pos.at_line(line_no as u32,column as u32)
+            .diagnostic(DiagnosticCode::Parse,&e.variant.message())
+            .with_source(e.line())
+    })
 }
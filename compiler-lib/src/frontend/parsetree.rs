@@ -1,4 +1,4 @@
-use crate::{model::{constants::Constant, checkstypes::{TypedArgument, ArgTypeSpec, Check}, codeblocks::CodeBlock}, controller::source::ParsePosition};
+use crate::{model::{constants::Constant, checkstypes::{TypedArgument, ArgTypeSpec, Check}, codeblocks::CodeBlock}, controller::{source::ParsePosition, diagnostic::{Diagnostic, Diagnostics, DiagnosticCode}}};
 use super::{buildtree::{BuildTree, Variable}, femodel::{OrBundle, OrBundleRepeater}};
 use super::buildtreebuilder::BuildContext;
 
@@ -26,16 +26,16 @@ pub enum FuncProcModifier {
 }
 
 pub trait PTTransformer {
-    fn include(&mut self, _pos: &ParsePosition, _fixed: bool, _path: &str) -> Result<Option<Vec<PTStatement>>,String> { Ok(None) }
-    fn bad_repeater(&mut self, _pos: (&[String],usize)) -> Result<(),String> { Ok(()) }
-    fn call_to_expr(&mut self, _call: &PTCall, _context: usize) -> Result<Option<PTExpression>,String> { Ok(None) }
-    fn call_to_block(&mut self, _call: &PTCall, _pos: &ParsePosition, _context: usize) -> Result<Option<Vec<PTStatement>>,String> { Ok(None) }
-    fn replace_infix(&mut self, _a: &PTExpression, _f: &str, _b: &PTExpression) -> Result<Option<PTExpression>,String> { Ok(None) }
-    fn replace_prefix(&mut self, _f: &str, _a: &PTExpression) -> Result<Option<PTExpression>,String> { Ok(None) }
+    fn include(&mut self, _pos: &ParsePosition, _fixed: bool, _path: &str) -> Result<Option<(Vec<PTStatement>,usize)>,Diagnostic> { Ok(None) }
+    fn bad_repeater(&mut self, _pos: (&[String],usize)) -> Result<(),Diagnostic> { Ok(()) }
+    fn call_to_expr(&mut self, _call: &PTCall, _context: usize) -> Result<Option<PTExpression>,Diagnostic> { Ok(None) }
+    fn call_to_block(&mut self, _call: &PTCall, _pos: &ParsePosition, _context: usize) -> Result<Option<Vec<PTStatement>>,Diagnostic> { Ok(None) }
+    fn replace_infix(&mut self, _a: &PTExpression, _f: &str, _b: &PTExpression) -> Result<Option<PTExpression>,Diagnostic> { Ok(None) }
+    fn replace_prefix(&mut self, _f: &str, _a: &PTExpression) -> Result<Option<PTExpression>,Diagnostic> { Ok(None) }
 }
 
 impl OrBundleRepeater<PTExpression> {
-    fn transform(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<OrBundleRepeater<PTExpression>,String> {
+    fn transform(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<OrBundleRepeater<PTExpression>,Diagnostic> {
         Ok(match self {
             OrBundleRepeater::Normal(x) => OrBundleRepeater::Normal(x.transform(transformer,pos,context)?),
             OrBundleRepeater::Bundle(s) => OrBundleRepeater::Bundle(s),
@@ -52,7 +52,7 @@ pub struct PTCall {
 }
 
 impl PTCall {
-    fn transform(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<PTCall,String> {
+    fn transform(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<PTCall,Diagnostic> {
         let mut args = vec![];
         for arg in self.args {
             args.push(arg.transform(transformer,pos,context)?);
@@ -64,7 +64,7 @@ impl PTCall {
         })
     }
 
-    fn transform_expression(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<PTExpression,String> {
+    fn transform_expression(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<PTExpression,Diagnostic> {
         if let Some(repl) = transformer.call_to_expr(&self,context)? {
             Ok(repl)
         } else {
@@ -72,7 +72,7 @@ impl PTCall {
         }
     }
 
-    fn transform_block(&self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<Option<Vec<PTStatement>>,String> {
+    fn transform_block(&self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<Option<Vec<PTStatement>>,Diagnostic> {
         if let Some(repl) = transformer.call_to_block(&self,pos,context)? {
             Ok(Some(repl))
         } else {
@@ -93,7 +93,7 @@ pub enum PTExpression {
 }
 
 impl PTExpression {
-    fn transform(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<PTExpression,String> {
+    fn transform(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<PTExpression,Diagnostic> {
         Ok(match self {
             PTExpression::FiniteSequence(mut exprs) => {
                 PTExpression::FiniteSequence(exprs.drain(..).map(|x| x.transform(transformer,pos,context)).collect::<Result<Vec<_>,_>>()?)
@@ -138,7 +138,7 @@ pub struct PTFuncDef {
 }
 
 impl PTFuncDef {
-    fn transform(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<PTFuncDef,String> {
+    fn transform(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<PTFuncDef,Diagnostic> {
         Ok(PTFuncDef {
             name: self.name,
             args: self.args,
@@ -161,7 +161,7 @@ impl PTFuncDef {
 }
 
 impl OrBundle<PTExpression> {
-    fn transform(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<OrBundle<PTExpression>,String> {
+    fn transform(self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<OrBundle<PTExpression>,Diagnostic> {
         Ok(match self {
             OrBundle::Normal(expr) => {
                 OrBundle::Normal(expr.transform(transformer,pos,context)?)
@@ -183,7 +183,7 @@ pub struct PTProcDef {
 }
 
 impl PTProcDef {
-    fn transform(mut self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<PTProcDef,String> {
+    fn transform(mut self, transformer: &mut dyn PTTransformer, pos: &ParsePosition, context: usize) -> Result<PTProcDef,Diagnostic> {
         Ok(PTProcDef {
             name: self.name,
             args: self.args,
@@ -205,6 +205,13 @@ impl PTProcDef {
     }
 }
 
+#[derive(Debug,Clone)]
+pub struct PTRecordDef {
+    pub name: String,
+    pub export: bool,
+    pub fields: Vec<TypedArgument>
+}
+
 #[derive(Clone,PartialEq,Eq)]
 pub enum CodeArgModifier {
     Large,
@@ -221,7 +228,8 @@ pub struct PTStatement {
 #[derive(Debug,Clone)]
 pub enum PTStatementValue {
     /* preprocessor */
-    Include(String,bool),
+    Include(String,bool,Option<String>),
+    Namespace(String,usize), // alias for a context, inserted when including with "as"
     Header(String,String,u32),
     Version(String,u32,u32),
 
@@ -229,23 +237,27 @@ pub enum PTStatementValue {
     Code(CodeBlock),
     FuncDef(PTFuncDef),
     ProcDef(PTProcDef),
+    ConstDef(String,PTExpression,bool), // substituted at each use when building
+    RecordDef(PTRecordDef), // expanded into prefixed variables when building
 
     /* instructions */
     LetStatement(Vec<OrBundleRepeater<(Variable,Vec<Check>)>>,Vec<OrBundle<PTExpression>>),
     LetRepeaterStatement(String,String),
+    RecordLet(String,String,Vec<(String,PTExpression)>),
     ModifyStatement(Vec<Variable>,Vec<PTExpression>),
+    WhenStatement(PTExpression,Vec<(Variable,PTExpression)>), // lowered to __operator_when when building
     Expression(PTExpression),
     MacroCall(PTCall),
 }
 
 impl PTStatement {
-    pub fn transform(self, transformer: &mut dyn PTTransformer) -> Result<Vec<PTStatement>,String> {
+    pub fn transform(self, transformer: &mut dyn PTTransformer) -> Result<Vec<PTStatement>,Diagnostic> {
         let pos = self.position.clone();
         let value = match self.value {
             PTStatementValue::LetStatement(lvalues,rvalues) => {
                 let count_repeats = lvalues.iter().filter(|x| x.is_repeater()).count();
                 if count_repeats > 1 {
-                    return Err(format!("only one repeat permitted per statement"));
+                    return Err(pos.diagnostic(DiagnosticCode::Build,"only one repeat permitted per statement"));
                 }
                 let mut exprs = vec![];
                 for rvalue in rvalues {
@@ -260,11 +272,26 @@ impl PTStatement {
                 ).collect::<Result<_,_>>()?;
                 PTStatementValue::ModifyStatement(lvalues,rvalues)
             },
+            PTStatementValue::WhenStatement(mask,mut assigns) => {
+                let context = self.context;
+                let mask = mask.transform(transformer,&pos,context)?;
+                let assigns = assigns.drain(..).map(|(v,x)|
+                    Ok((v,x.transform(transformer,&pos,context)?))
+                ).collect::<Result<_,Diagnostic>>()?;
+                PTStatementValue::WhenStatement(mask,assigns)
+            },
+            PTStatementValue::RecordLet(prefix,record,mut values) => {
+                let context = self.context;
+                let values = values.drain(..).map(|(f,x)|
+                    Ok((f,x.transform(transformer,&pos,context)?))
+                ).collect::<Result<_,Diagnostic>>()?;
+                PTStatementValue::RecordLet(prefix,record,values)
+            },
             PTStatementValue::MacroCall(call) => {
                 if let Some(repl) = call.transform_block(transformer,&pos,self.context)? {
                     return Ok(repl);
                 } else {
-                    return Err(format!("no such macro"));
+                    return Err(pos.diagnostic(DiagnosticCode::Macro,"no such macro"));
                 }
             },
             PTStatementValue::Expression(x) => {
@@ -277,6 +304,9 @@ impl PTStatement {
             PTStatementValue::ProcDef(call) => {
                 PTStatementValue::ProcDef(call.transform(transformer,&pos,self.context)?)
             },
+            PTStatementValue::ConstDef(name,x,export) => {
+                PTStatementValue::ConstDef(name,x.transform(transformer,&pos,self.context)?,export)
+            },
             x => x
         };
         Ok(vec![PTStatement {
@@ -286,39 +316,49 @@ impl PTStatement {
         }])
     }
 
-    pub fn transform_list(this: Vec<Self>, transformer: &mut dyn PTTransformer) -> Result<Vec<PTStatement>,String> {
+    pub fn transform_list(this: Vec<Self>, transformer: &mut dyn PTTransformer) -> Result<Vec<PTStatement>,Diagnostic> {
         let mut out = vec![];
         for block in this {
             let pos = block.position.clone();
             let mut more = match &block.value {
-                PTStatementValue::Include(path,fixed) => {
+                PTStatementValue::Include(path,fixed,alias) => {
                     if pos.contains(path) {
-                        return Err(pos.message(&format!("recursive include of {}",path)));
+                        return Err(pos.diagnostic(DiagnosticCode::Include,&format!("recursive include of {}",path)));
                     }
-                    if let Some(repl) = transformer.include(&pos,*fixed,path)? {
+                    if let Some((mut repl,context)) = transformer.include(&pos,*fixed,path)? {
+                        /* an included file's statements all share its context, so name that */
+                        if let Some(alias) = alias {
+                            repl.insert(0,PTStatement {
+                                value: PTStatementValue::Namespace(alias.to_string(),context),
+                                position: pos.clone(),
+                                context: block.context
+                            });
+                        }
                         repl
                     } else {
                         vec![block]
                     }
                 },
-                _ => block.transform(transformer)?
+                _ => block.transform(transformer).map_err(|e| pos.locate(e))?
             };
             out.append(&mut more);
         }
         Ok(out)    
     }
 
-    pub(crate) fn to_build_tree(this: Vec<Self>, target_version: Option<u32>) -> Result<BuildTree,String> {
+    pub(crate) fn to_build_tree(this: Vec<Self>, target_version: Option<u32>) -> Result<BuildTree,Diagnostics> {
         let mut bt = BuildTree::new();
         let mut bc = BuildContext::new(target_version);
         for stmt in this.iter() {
             bc.set_location(&stmt.position);
             bc.set_file_context(stmt.context);
-            bc.build_statement(&mut bt,&stmt).map_err(|e| {
-                bc.location().message(&e)
-            })?;
+            if let Err(e) = bc.build_statement(&mut bt,&stmt) {
+                bc.add_error(&e);
+                bc.abandon_funcproc();
+            }
+            if bc.too_many_errors() { break; }
         }
         bt.finish();
-        Ok(bt)
+        bc.take_errors().result(bt)
     }
 }
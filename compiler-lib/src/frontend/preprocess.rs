@@ -1,5 +1,5 @@
 use std::{collections::HashMap};
-use crate::controller::{compiler::EardCompiler, compilation::EardCompilation, source::ParsePosition};
+use crate::controller::{compiler::EardCompiler, compilation::EardCompilation, source::ParsePosition, diagnostic::{Diagnostic, DiagnosticCode}};
 
 use super::{parsetree::{PTTransformer, PTCall, PTExpression, PTStatement, at}, femodel::OrBundleRepeater};
 
@@ -30,26 +30,26 @@ struct RunMacrosOnce<'a> {
 }
 
 impl<'a> PTTransformer for RunMacrosOnce<'a> {
-    fn call_to_expr(&mut self, call: &PTCall, context: usize) -> Result<Option<PTExpression>,String> {
+    fn call_to_expr(&mut self, call: &PTCall, context: usize) -> Result<Option<PTExpression>,Diagnostic> {
         if call.is_macro {
             self.any = true;
-            Ok(Some(self.compiler.apply_expression_macro(&call.name,&call.args,context)?))
+            Ok(Some(self.compiler.apply_expression_macro(&call.name,&call.args,context).map_err(|e| Diagnostic::new(DiagnosticCode::Macro,&e))?))
         } else {
             Ok(None)
         }
     }
 
-    fn call_to_block(&mut self, call: &PTCall, pos: &ParsePosition, context: usize) -> Result<Option<Vec<PTStatement>>,String> {
+    fn call_to_block(&mut self, call: &PTCall, pos: &ParsePosition, context: usize) -> Result<Option<Vec<PTStatement>>,Diagnostic> {
         if call.is_macro {
             self.any = true;
-            Ok(Some(self.compiler.apply_block_macro(&call.name,&call.args,pos,context)?))
+            Ok(Some(self.compiler.apply_block_macro(&call.name,&call.args,pos,context).map_err(|e| pos.diagnostic(DiagnosticCode::Macro,&e))?))
         } else {
             Ok(None)
         }
     }
 }
 
-fn run_macros_once(compiler: &EardCompiler, block: Vec<PTStatement>) -> Result<(Vec<PTStatement>,bool),String> {
+fn run_macros_once(compiler: &EardCompiler, block: Vec<PTStatement>) -> Result<(Vec<PTStatement>,bool),Diagnostic> {
     let mut once = RunMacrosOnce { compiler, any: false };
     let out = PTStatement::transform_list(block,&mut once)?;
     Ok((out,once.any))
@@ -61,15 +61,15 @@ struct RunIncludeOnce<'a,'b> {
 }
 
 impl<'a,'b> PTTransformer for RunIncludeOnce<'a,'b> {
-    fn include(&mut self, pos: &ParsePosition, fixed: bool, path: &str) -> Result<Option<Vec<PTStatement>>,String> {
+    fn include(&mut self, pos: &ParsePosition, fixed: bool, path: &str) -> Result<Option<(Vec<PTStatement>,usize)>,Diagnostic> {
         self.any = true;
-        Ok(Some(self.compilation.parse_part(pos,path,fixed).map_err(|e| {
-            pos.message(&e)
+        Ok(Some(self.compilation.include_part(pos,path,fixed).map_err(|e| {
+            pos.locate(e)
         })?))
     }
 }
 
-fn run_include_once<'a,'b>(compilation: &'b mut EardCompilation<'a>, block: Vec<PTStatement>) -> Result<(Vec<PTStatement>,bool),String> {
+fn run_include_once<'a,'b>(compilation: &'b mut EardCompilation<'a>, block: Vec<PTStatement>) -> Result<(Vec<PTStatement>,bool),Diagnostic> {
     let mut once = RunIncludeOnce { compilation, any: false };
     let out = PTStatement::transform_list(block,&mut once)?;
     Ok((out,once.any))
@@ -90,11 +90,11 @@ impl Phase2Misc {
 }
 
 impl PTTransformer for Phase2Misc {
-    fn bad_repeater(&mut self, pos: (&[String],usize)) -> Result<(),String> {
-        return Err(at("invalid use of repeater (**)",Some(pos)))
+    fn bad_repeater(&mut self, pos: (&[String],usize)) -> Result<(),Diagnostic> {
+        return Err(at("invalid use of repeater (**)",Some(pos)).into())
     }
 
-    fn replace_infix(&mut self, a: &PTExpression, f: &str, b: &PTExpression) -> Result<Option<PTExpression>,String> {
+    fn replace_infix(&mut self, a: &PTExpression, f: &str, b: &PTExpression) -> Result<Option<PTExpression>,Diagnostic> {
         if let Some(name) = self.infix.get(f) {
             Ok(Some(PTExpression::Call(PTCall {
                 name: name.to_string(),
@@ -109,7 +109,7 @@ impl PTTransformer for Phase2Misc {
         }
     }
 
-    fn replace_prefix(&mut self, f: &str, a: &PTExpression) -> Result<Option<PTExpression>,String> {
+    fn replace_prefix(&mut self, f: &str, a: &PTExpression) -> Result<Option<PTExpression>,Diagnostic> {
         if let Some(name) = self.prefix.get(f) {
             Ok(Some(PTExpression::Call(PTCall {
                 name: name.to_string(),
@@ -124,12 +124,12 @@ impl PTTransformer for Phase2Misc {
     }
 }
 
-fn phase2_misc(block: Vec<PTStatement>) -> Result<Vec<PTStatement>,String> {
+fn phase2_misc(block: Vec<PTStatement>) -> Result<Vec<PTStatement>,Diagnostic> {
     let mut flags = Phase2Misc::new();
     PTStatement::transform_list(block,&mut flags)
 }
 
-pub(crate) fn preprocess(compilation: &mut EardCompilation, mut block: Vec<PTStatement>) -> Result<Vec<PTStatement>,String> {
+pub(crate) fn preprocess(compilation: &mut EardCompilation, mut block: Vec<PTStatement>) -> Result<Vec<PTStatement>,Diagnostic> {
     let mut any1 = true;
     let mut any2 = true;
     while any1 || any2 {
@@ -3,7 +3,9 @@ mod controller {
     pub(crate) mod compilation;
     pub(crate) mod source;
     pub(crate) mod compiled;
-    pub(crate) mod serialise;    
+    pub(crate) mod serialise;
+    pub(crate) mod diagnostic;
+    pub(crate) mod definitions;
 }
 
 mod frontend {
@@ -13,6 +15,7 @@ mod frontend {
     pub(crate) mod parser;
     pub(crate) mod parsetree;   
     pub(crate) mod femodel; 
+    pub(crate) mod format;
 }
 
 mod libcore {
@@ -22,6 +25,7 @@ mod libcore {
     mod foldseq;
     mod foldmaths;
     mod foldconvert;
+    mod macros;
 }
 
 mod middleend {
@@ -76,7 +80,9 @@ pub use crate::model::{
 pub use crate::controller::{
     compiler::EardCompiler, 
     compilation::EardCompilation, 
-    serialise::EardSerializeCode,
+    serialise::{EardSerializeCode, OutputFormat},
+    diagnostic::{ Diagnostic, Diagnostics, DiagnosticCode, Severity, SourceSpan },
+    definitions::{ Definition, DefinitionKind },
     source::FixedSourceSource
 };
 
@@ -86,4 +92,5 @@ pub use crate::frontend::femodel::{OrBundle, OrBundleRepeater };
 pub use crate::frontend::parsetree::{ PTExpression, PTStatement, PTStatementValue, PTCall };
 pub use crate::frontend::buildtree::{ Variable };
 pub use crate::frontend::parser::parse_string;
+pub use crate::frontend::format::{ format_source, format_file, format_files };
 pub use crate::model::constants::Constant;
\ No newline at end of file
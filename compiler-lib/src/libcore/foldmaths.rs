@@ -255,3 +255,63 @@ pub(crate) fn fold_min_seq(inputs: &[Option<FullConstant>]) -> Option<Vec<FullCo
     }
     None
 }
+
+pub(crate) fn fold_pow(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    fold_num_bin(inputs,|a,b| a.powf(b))
+}
+
+pub(crate) fn fold_floor(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let Some(Some(a)) = inputs.first() {
+        Some(vec![number_un!(|a: f64| a.floor(),a)])
+    } else {
+        None
+    }
+}
+
+pub(crate) fn fold_ceil(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let Some(Some(a)) = inputs.first() {
+        Some(vec![number_un!(|a: f64| a.ceil(),a)])
+    } else {
+        None
+    }
+}
+
+pub(crate) fn fold_round(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let Some(Some(a)) = inputs.first() {
+        Some(vec![number_un!(|a: f64| a.round(),a)])
+    } else {
+        None
+    }
+}
+
+pub(crate) fn fold_abs(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let Some(Some(a)) = inputs.first() {
+        Some(vec![number_un!(|a: f64| a.abs(),a)])
+    } else {
+        None
+    }
+}
+
+pub(crate) fn fold_log10(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let Some(Some(a)) = inputs.first() {
+        Some(vec![number_un!(|a: f64| a.log10(),a)])
+    } else {
+        None
+    }
+}
+
+pub(crate) fn fold_ln(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let Some(Some(a)) = inputs.first() {
+        Some(vec![number_un!(|a: f64| a.ln(),a)])
+    } else {
+        None
+    }
+}
+
+pub(crate) fn fold_exp(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let Some(Some(a)) = inputs.first() {
+        Some(vec![number_un!(|a: f64| a.exp(),a)])
+    } else {
+        None
+    }
+}
@@ -1,8 +1,10 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use ordered_float::OrderedFloat;
 
 use crate::model::constants::{FullConstant, Constant};
 
-use super::util::to_usize;
+use super::util::{to_usize, to_number, to_string, to_boolean};
 
 pub(super) fn fold_infseq(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
     if let Some(Some(FullConstant::Atomic(x))) = inputs.get(0) {
@@ -90,6 +92,10 @@ pub(super) fn fold_if(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstan
     None
 }
 
+pub(super) fn fold_const(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    inputs.get(1).cloned().flatten().map(|x| vec![x])
+}
+
 pub(super) fn fold_repeat(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
     if let (Some(Some(FullConstant::Atomic(f))),
             Some(Some(FullConstant::Atomic(Constant::Number(n))))) = 
@@ -148,3 +154,183 @@ pub(super) fn fold_enumerate(inputs: &[Option<FullConstant>]) -> Option<Vec<Full
     }
     None
 }
+
+pub(super) fn fold_sum(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let Some(Some(FullConstant::Finite(x))) = inputs.first() {
+        let sum = to_number(x)?.iter().sum();
+        return Some(vec![FullConstant::Atomic(Constant::Number(OrderedFloat(sum)))]);
+    }
+    None
+}
+
+pub(super) fn fold_mean(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let Some(Some(FullConstant::Finite(x))) = inputs.first() {
+        let x = to_number(x)?;
+        let mean = x.iter().sum::<f64>() / (x.len() as f64);
+        return Some(vec![FullConstant::Atomic(Constant::Number(OrderedFloat(mean)))]);
+    }
+    None
+}
+
+pub(super) fn fold_cumsum(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let Some(Some(FullConstant::Finite(x))) = inputs.first() {
+        let mut total = 0.;
+        let out = to_number(x)?.iter().map(|v| {
+            total += v;
+            Constant::Number(OrderedFloat(total))
+        }).collect();
+        return Some(vec![FullConstant::Finite(out)]);
+    }
+    None
+}
+
+pub(super) fn fold_group_sum(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let (Some(Some(FullConstant::Finite(values))),
+            Some(Some(FullConstant::Finite(mapping))),
+            Some(Some(FullConstant::Atomic(Constant::Number(groups))))) = 
+                (inputs.first(),inputs.get(1),inputs.get(2)) {
+        /* bad counts and mismatched lengths left to fail at runtime */
+        if groups.0 < 0. || groups.0.fract() != 0. || values.len() != mapping.len() { return None; }
+        let mut out = vec![0.;groups.0 as usize];
+        for (value,group) in to_number(values)?.iter().zip(to_number(mapping)?.iter()) {
+            /* bad and out of range groups left to fail at runtime */
+            if *group < 0. || group.fract() != 0. { return None; }
+            *out.get_mut(*group as usize)? += value;
+        }
+        return Some(vec![FullConstant::Finite(
+            out.drain(..).map(|v| Constant::Number(OrderedFloat(v))).collect()
+        )]);
+    }
+    None
+}
+
+fn argsort<T: Ord>(input: &[T], descending: bool) -> Vec<Constant> {
+    let mut order = (0..input.len()).collect::<Vec<_>>();
+    if descending {
+        order.sort_by(|a,b| input[*b].cmp(&input[*a]));
+    } else {
+        order.sort_by(|a,b| input[*a].cmp(&input[*b]));
+    }
+    order.drain(..).map(|x| Constant::Number(OrderedFloat(x as f64))).collect()
+}
+
+pub(super) fn fold_argsort(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let (Some(Some(FullConstant::Finite(x))),
+            Some(Some(FullConstant::Atomic(Constant::Boolean(descending))))) = (inputs.first(),inputs.get(1)) {
+        let order = if let Some(x) = to_number(x) {
+            argsort(&x.iter().map(|x| OrderedFloat(*x)).collect::<Vec<_>>(),*descending)
+        } else {
+            argsort(&to_string(x)?,*descending)
+        };
+        return Some(vec![FullConstant::Finite(order)]);
+    }
+    None
+}
+
+pub(super) fn fold_filter(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let (Some(Some(FullConstant::Finite(x))),
+            Some(Some(FullConstant::Finite(mask)))) = (inputs.first(),inputs.get(1)) {
+        let mask = to_boolean(mask)?;
+        if x.len() != mask.len() { return None; } /* fail at runtime */
+        let out = x.iter().zip(mask.iter()).filter(|(_,m)| **m).map(|(x,_)| x.clone()).collect();
+        return Some(vec![FullConstant::Finite(out)]);
+    }
+    None
+}
+
+pub(super) fn fold_scatter(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let (Some(Some(FullConstant::Finite(x))),
+            Some(Some(FullConstant::Finite(mask))),
+            Some(Some(values))) = (inputs.first(),inputs.get(1),inputs.get(2)) {
+        let mask = to_boolean(mask)?;
+        if x.len() != mask.len() { return None; }
+        let trues = mask.iter().filter(|m| **m).count();
+        let values = match values {
+            FullConstant::Finite(v) if v.len() == trues => v.clone(),
+            FullConstant::Infinite(v) => vec![v.clone();trues],
+            _ => { return None; }
+        };
+        let mut values = values.into_iter();
+        let out = x.iter().zip(mask.iter()).map(|(x,m)| {
+            if *m { values.next().unwrap() } else { x.clone() }
+        }).collect();
+        return Some(vec![FullConstant::Finite(out)]);
+    }
+    None
+}
+
+pub(super) fn fold_unique(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let Some(Some(FullConstant::Finite(x))) = inputs.first() {
+        let mut positions = BTreeMap::new();
+        let mut values = vec![];
+        let mapping = x.iter().map(|value| {
+            let pos = *positions.entry(value).or_insert_with(|| {
+                values.push(value.clone());
+                values.len()-1
+            });
+            Constant::Number(OrderedFloat(pos as f64))
+        }).collect();
+        return Some(vec![FullConstant::Finite(values),FullConstant::Finite(mapping)]);
+    }
+    None
+}
+
+pub(super) fn fold_in_set(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let (Some(Some(FullConstant::Finite(needles))),
+            Some(Some(FullConstant::Finite(haystack)))) = (inputs.first(),inputs.get(1)) {
+        let haystack = haystack.iter().collect::<BTreeSet<_>>();
+        let out = needles.iter().map(|x| Constant::Boolean(haystack.contains(x))).collect();
+        return Some(vec![FullConstant::Finite(out)]);
+    }
+    None
+}
+
+/* union when members is None, otherwise keep values of a whose membership of b matches */
+fn fold_set_combine(inputs: &[Option<FullConstant>], members: Option<bool>) -> Option<Vec<FullConstant>> {
+    if let (Some(Some(FullConstant::Finite(a))),
+            Some(Some(FullConstant::Finite(b)))) = (inputs.first(),inputs.get(1)) {
+        let mut seen = BTreeSet::new();
+        let out = if let Some(members) = members {
+            let b = b.iter().collect::<BTreeSet<_>>();
+            a.iter().filter(|x| b.contains(x) == members && seen.insert(*x)).cloned().collect()
+        } else {
+            a.iter().chain(b.iter()).filter(|x| seen.insert(*x)).cloned().collect()
+        };
+        return Some(vec![FullConstant::Finite(out)]);
+    }
+    None
+}
+
+pub(super) fn fold_union(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    fold_set_combine(inputs,None)
+}
+
+pub(super) fn fold_intersect(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    fold_set_combine(inputs,Some(true))
+}
+
+pub(super) fn fold_difference(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    fold_set_combine(inputs,Some(false))
+}
+
+/* a missing key without a default doesn't fold, constfold reports it */
+pub(super) fn fold_map(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    if let (Some(Some(x)),
+            Some(Some(FullConstant::Finite(keys))),
+            Some(Some(FullConstant::Finite(values)))) = (inputs.first(),inputs.get(1),inputs.get(2)) {
+        let default = match inputs.get(3) {
+            Some(Some(FullConstant::Atomic(d))) => Some(d),
+            Some(_) => { return None; },
+            None => None
+        };
+        let table = keys.iter().zip(values.iter()).collect::<BTreeMap<_,_>>();
+        let lookup = |k: &Constant| table.get(k).cloned().or(default).cloned();
+        let out = match x {
+            FullConstant::Atomic(k) => FullConstant::Atomic(lookup(k)?),
+            FullConstant::Finite(k) => FullConstant::Finite(k.iter().map(lookup).collect::<Option<_>>()?),
+            FullConstant::Infinite(k) => FullConstant::Infinite(lookup(k)?)
+        };
+        return Some(vec![out]);
+    }
+    None
+}
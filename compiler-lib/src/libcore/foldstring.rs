@@ -1,5 +1,6 @@
 use ordered_float::OrderedFloat;
 
+use eard_number_format::NumberFormat;
 use crate::model::constants::{FullConstant, Constant};
 use super::util::{to_string, arm };
 
@@ -166,3 +167,110 @@ pub(crate) fn fold_strlen(inputs: &[Option<FullConstant>]) -> Option<Vec<FullCon
         }
     } else { None }
 }
+
+/* Applies cb to a string constant or to each string in a sequence constant. */
+fn fold_strings<F>(input: Option<&Option<FullConstant>>, cb: F) -> Option<Vec<FullConstant>>
+        where F: Fn(&str) -> Constant {
+    let out = match input? {
+        Some(FullConstant::Atomic(Constant::String(s))) => FullConstant::Atomic(cb(s)),
+        Some(FullConstant::Finite(seq)) => {
+            FullConstant::Finite(to_string(seq)?.iter().map(|s| cb(s)).collect())
+        },
+        Some(FullConstant::Infinite(Constant::String(s))) => FullConstant::Infinite(cb(s)),
+        _ => { return None; }
+    };
+    Some(vec![out])
+}
+
+fn atomic_number(input: Option<&Option<FullConstant>>) -> Option<f64> {
+    match input? {
+        Some(FullConstant::Atomic(Constant::Number(n))) => Some(n.0),
+        _ => None
+    }
+}
+
+fn atomic_string(input: Option<&Option<FullConstant>>) -> Option<&str> {
+    match input? {
+        Some(FullConstant::Atomic(Constant::String(s))) => Some(s),
+        _ => None
+    }
+}
+
+/* Positions are in characters. A negative start counts from the end. */
+fn substr(input: &str, start: f64, len: f64) -> String {
+    let chars = input.chars().count() as f64;
+    let start = if start < 0. { (chars+start).max(0.) } else { start.min(chars) };
+    input.chars().skip(start as usize).take(len.max(0.) as usize).collect()
+}
+
+fn padding(input: &str, width: f64, pad: &str) -> String {
+    let missing = (width.max(0.) as usize).saturating_sub(input.chars().count());
+    pad.chars().cycle().take(if !pad.is_empty() { missing } else { 0 }).collect()
+}
+
+pub(crate) fn fold_substr(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    let start = atomic_number(inputs.get(1))?;
+    let len = atomic_number(inputs.get(2))?;
+    fold_strings(inputs.first(),|s| Constant::String(substr(s,start,len)))
+}
+
+pub(crate) fn fold_upper(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    fold_strings(inputs.first(),|s| Constant::String(s.to_uppercase()))
+}
+
+pub(crate) fn fold_lower(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    fold_strings(inputs.first(),|s| Constant::String(s.to_lowercase()))
+}
+
+pub(crate) fn fold_trim(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    fold_strings(inputs.first(),|s| Constant::String(s.trim().to_string()))
+}
+
+pub(crate) fn fold_pad_start(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    let width = atomic_number(inputs.get(1))?;
+    let pad = atomic_string(inputs.get(2))?;
+    fold_strings(inputs.first(),|s| Constant::String(format!("{}{}",padding(s,width,pad),s)))
+}
+
+pub(crate) fn fold_pad_end(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    let width = atomic_number(inputs.get(1))?;
+    let pad = atomic_string(inputs.get(2))?;
+    fold_strings(inputs.first(),|s| Constant::String(format!("{}{}",s,padding(s,width,pad))))
+}
+
+pub(crate) fn fold_starts_with(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    let prefix = atomic_string(inputs.get(1))?;
+    fold_strings(inputs.first(),|s| Constant::Boolean(s.starts_with(prefix)))
+}
+
+pub(crate) fn fold_ends_with(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    let suffix = atomic_string(inputs.get(1))?;
+    fold_strings(inputs.first(),|s| Constant::Boolean(s.ends_with(suffix)))
+}
+
+pub(crate) fn fold_contains(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    let needle = atomic_string(inputs.get(1))?;
+    fold_strings(inputs.first(),|s| Constant::Boolean(s.contains(needle)))
+}
+
+pub(crate) fn fold_number_format(inputs: &[Option<FullConstant>]) -> Option<Vec<FullConstant>> {
+    let format = NumberFormat::new(atomic_string(inputs.get(1))?).ok()?; /* fail at runtime */
+    let out = match inputs.first()? {
+        Some(FullConstant::Atomic(Constant::Number(n))) => {
+            FullConstant::Atomic(Constant::String(format.format(n.0)))
+        },
+        Some(FullConstant::Finite(seq)) => {
+            FullConstant::Finite(seq.iter().map(|c| {
+                match c {
+                    Constant::Number(n) => Some(Constant::String(format.format(n.0))),
+                    _ => None
+                }
+            }).collect::<Option<_>>()?)
+        },
+        Some(FullConstant::Infinite(Constant::Number(n))) => {
+            FullConstant::Infinite(Constant::String(format.format(n.0)))
+        },
+        _ => { return None; }
+    };
+    Some(vec![out])
+}
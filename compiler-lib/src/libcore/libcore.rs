@@ -1,5 +1,6 @@
 use crate::{controller::{compiler::EardCompiler, source::FixedSourceSource}};
-use super::{foldseq::{fold_bound, fold_total, fold_length, fold_push, fold_finseq, fold_infseq, fold_if, fold_repeat, fold_index, fold_count, fold_enumerate }, foldmaths::{fold_add, fold_sub, fold_mul, fold_div, fold_gt, fold_ge, fold_not, fold_eq, fold_minus, fold_and, fold_or, fold_any, fold_all, fold_position, fold_mod, fold_max, fold_min_seq, fold_max_seq, fold_min}, foldstring::{fold_push_str, fold_split, fold_template, fold_join, fold_format, fold_strlen}, foldconvert::{fold_to_boolean, fold_to_number, fold_to_string}};
+use super::macros::map_macro;
+use super::{foldseq::{fold_bound, fold_total, fold_length, fold_push, fold_finseq, fold_infseq, fold_if, fold_const, fold_repeat, fold_index, fold_count, fold_enumerate, fold_sum, fold_mean, fold_cumsum, fold_group_sum, fold_argsort, fold_filter, fold_scatter, fold_unique, fold_in_set, fold_union, fold_intersect, fold_difference, fold_map }, foldmaths::{fold_add, fold_sub, fold_mul, fold_div, fold_gt, fold_ge, fold_not, fold_eq, fold_minus, fold_and, fold_or, fold_any, fold_all, fold_position, fold_mod, fold_max, fold_min_seq, fold_max_seq, fold_min, fold_floor, fold_ceil, fold_round, fold_abs, fold_log10, fold_ln, fold_exp, fold_pow}, foldstring::{fold_push_str, fold_split, fold_template, fold_join, fold_format, fold_strlen, fold_substr, fold_upper, fold_lower, fold_trim, fold_pad_start, fold_pad_end, fold_starts_with, fold_ends_with, fold_contains, fold_number_format}, foldconvert::{fold_to_boolean, fold_to_number, fold_to_string}};
 
 pub(crate) fn libcore_add(compiler: &mut EardCompiler) -> Result<(),String> {
     compiler.add_constant_folder("libcore__infseq",fold_infseq)?;
@@ -21,10 +22,24 @@ pub(crate) fn libcore_add(compiler: &mut EardCompiler) -> Result<(),String> {
     compiler.add_constant_folder("libcore__and",fold_and)?;
     compiler.add_constant_folder("libcore__or",fold_or)?;
     compiler.add_constant_folder("libcore__if",fold_if)?;
+    compiler.add_constant_folder("libcore__const",fold_const)?;
     compiler.add_constant_folder("libcore__repeat",fold_repeat)?;
     compiler.add_constant_folder("libcore__index",fold_index)?;
     compiler.add_constant_folder("libcore__count",fold_count)?;
     compiler.add_constant_folder("libcore__enumerate",fold_enumerate)?;
+    compiler.add_constant_folder("libcore__sum",fold_sum)?;
+    compiler.add_constant_folder("libcore__mean",fold_mean)?;
+    compiler.add_constant_folder("libcore__cumsum",fold_cumsum)?;
+    compiler.add_constant_folder("libcore__group_sum",fold_group_sum)?;
+    compiler.add_constant_folder("libcore__argsort",fold_argsort)?;
+    compiler.add_constant_folder("libcore__filter",fold_filter)?;
+    compiler.add_constant_folder("libcore__scatter",fold_scatter)?;
+    compiler.add_constant_folder("libcore__unique",fold_unique)?;
+    compiler.add_constant_folder("libcore__in_set",fold_in_set)?;
+    compiler.add_constant_folder("libcore__union",fold_union)?;
+    compiler.add_constant_folder("libcore__intersect",fold_intersect)?;
+    compiler.add_constant_folder("libcore__difference",fold_difference)?;
+    compiler.add_constant_folder("libcore__map",fold_map)?;
     compiler.add_constant_folder("libcore__join",fold_join)?;
     compiler.add_constant_folder("libcore__push_str",fold_push_str)?;
     compiler.add_constant_folder("libcore__split",fold_split)?;
@@ -40,7 +55,26 @@ pub(crate) fn libcore_add(compiler: &mut EardCompiler) -> Result<(),String> {
     compiler.add_constant_folder("libcore__min",fold_min)?;
     compiler.add_constant_folder("libcore__max_seq",fold_max_seq)?;
     compiler.add_constant_folder("libcore__min_seq",fold_min_seq)?;
+    compiler.add_constant_folder("libcore__floor",fold_floor)?;
+    compiler.add_constant_folder("libcore__ceil",fold_ceil)?;
+    compiler.add_constant_folder("libcore__round",fold_round)?;
+    compiler.add_constant_folder("libcore__abs",fold_abs)?;
+    compiler.add_constant_folder("libcore__log10",fold_log10)?;
+    compiler.add_constant_folder("libcore__ln",fold_ln)?;
+    compiler.add_constant_folder("libcore__exp",fold_exp)?;
+    compiler.add_constant_folder("libcore__pow",fold_pow)?;
     compiler.add_constant_folder("libcore__strlen",fold_strlen)?;
+    compiler.add_constant_folder("libcore__substr",fold_substr)?;
+    compiler.add_constant_folder("libcore__upper",fold_upper)?;
+    compiler.add_constant_folder("libcore__lower",fold_lower)?;
+    compiler.add_constant_folder("libcore__trim",fold_trim)?;
+    compiler.add_constant_folder("libcore__pad_start",fold_pad_start)?;
+    compiler.add_constant_folder("libcore__pad_end",fold_pad_end)?;
+    compiler.add_constant_folder("libcore__starts_with",fold_starts_with)?;
+    compiler.add_constant_folder("libcore__ends_with",fold_ends_with)?;
+    compiler.add_constant_folder("libcore__contains",fold_contains)?;
+    compiler.add_constant_folder("libcore__number_format",fold_number_format)?;
+    compiler.add_expression_macro("map",map_macro)?;
     Ok(())
 }
 
@@ -57,5 +91,5 @@ pub(crate) fn libcore_sources() -> FixedSourceSource {
         ("string",include_str!("eard/string.eard")),
         ("convert",include_str!("eard/convert.eard")),
         ("bio",include_str!("eard/bio.eard")),
-    ])
+    ]).with_dir(concat!(env!("CARGO_MANIFEST_DIR"),"/src/libcore/eard"))
 }
@@ -0,0 +1,63 @@
+use crate::{frontend::{parsetree::{PTExpression, PTCall}, femodel::OrBundleRepeater}, model::constants::Constant};
+
+fn table_constant(expr: &PTExpression) -> Result<Constant,String> {
+    match expr {
+        PTExpression::Constant(c) => Ok(c.clone()),
+        _ => Err("map! table entries must be literal constants".to_string())
+    }
+}
+
+fn same_type(items: &[Constant], what: &str) -> Result<(),String> {
+    if let Some(first) = items.first() {
+        if items.iter().any(|x| x.to_atomic_type() != first.to_atomic_type()) {
+            return Err(format!("map! {} must all be the same type",what));
+        }
+    }
+    Ok(())
+}
+
+/* map!(x,[[key,value],...],default) looks x up in a literal table, becoming a single opcode with
+ * the keys and values in the constant pool. Without a default, a value of x missing from the
+ * table is an error, at compile time if x is constant and otherwise at runtime.
+ */
+pub(crate) fn map_macro(args: &[OrBundleRepeater<PTExpression>], _context: usize) -> Result<PTExpression,String> {
+    let args = args.iter().map(|arg| match arg {
+        OrBundleRepeater::Normal(x) => Ok(x),
+        _ => Err("map! does not take bundles".to_string())
+    }).collect::<Result<Vec<_>,_>>()?;
+    if args.len() < 2 || args.len() > 3 {
+        return Err("map! expects a value, a table and an optional default".to_string());
+    }
+    let rows = match args[1] {
+        PTExpression::FiniteSequence(rows) if !rows.is_empty() => rows,
+        _ => { return Err("map! expects a non-empty table of [key,value] pairs".to_string()); }
+    };
+    let (mut keys,mut values) = (vec![],vec![]);
+    for row in rows {
+        match row {
+            PTExpression::FiniteSequence(pair) if pair.len() == 2 => {
+                let key = table_constant(&pair[0])?;
+                if keys.contains(&key) {
+                    return Err(format!("map! has duplicate key {:?}",key));
+                }
+                keys.push(key);
+                values.push(table_constant(&pair[1])?);
+            },
+            _ => { return Err("map! expects a non-empty table of [key,value] pairs".to_string()); }
+        }
+    }
+    let default = args.get(2).map(|d| table_constant(d)).transpose()?;
+    same_type(&keys,"keys")?;
+    same_type(&values.iter().chain(default.iter()).cloned().collect::<Vec<_>>(),"values")?;
+    let seq = |x: Vec<Constant>| {
+        OrBundleRepeater::Normal(PTExpression::FiniteSequence(x.into_iter().map(PTExpression::Constant).collect()))
+    };
+    let mut call_args = vec![OrBundleRepeater::Normal(args[0].clone()),seq(keys),seq(values)];
+    let name = if let Some(default) = default {
+        call_args.push(OrBundleRepeater::Normal(PTExpression::Constant(default)));
+        "__operator_map"
+    } else {
+        "__operator_map_all"
+    };
+    Ok(PTExpression::Call(PTCall { name: name.to_string(), args: call_args, is_macro: false }))
+}
@@ -56,6 +56,15 @@ pub(crate) fn to_usize(input: &[Constant]) -> Option<Vec<usize>> {
     }).collect::<Option<Vec<_>>>()
 }
 
+pub(crate) fn to_number(input: &[Constant]) -> Option<Vec<f64>> {
+    input.iter().map(|idx| {
+        match idx {
+            Constant::Number(n) => Some(n.0),
+            _ => None
+        }
+    }).collect::<Option<Vec<_>>>()
+}
+
 pub(crate) fn to_string(input: &[Constant]) -> Option<Vec<String>> {
     input.iter().map(|idx| {
         match idx {
@@ -86,6 +95,11 @@ pub(crate) fn fold_num_bin<F>(inputs: &[Option<FullConstant>], cb: F) -> Option<
         if let (Some(a),Some(b)) = (arm!(a,Number),arm!(b,Number)) {
             return Some(vec![FullConstant::Infinite(Constant::Number(OrderedFloat(cb(a.0,b.0))))]);
         }
+    } else if let (Some(Some(FullConstant::Atomic(Constant::Number(a)))),
+                   Some(Some(FullConstant::Finite(b)))) = (inputs.get(0),inputs.get(1)) {
+        let out = b.iter().map(|c| arm!(c,Number)).collect::<Option<Vec<_>>>()?;
+        let z = out.iter().map(|v| Constant::Number(OrderedFloat(cb(a.0,v.0)))).collect::<Vec<_>>();
+        return Some(vec![FullConstant::Finite(z)]);
     } else if let (Some(Some(FullConstant::Finite(a))),
                    Some(Some(FullConstant::Atomic(Constant::Number(b))))) = (inputs.get(0),inputs.get(1)) {
         let out = a.iter().map(|c| arm!(c,Number)).collect::<Option<Vec<_>>>()?;
@@ -1,5 +1,6 @@
 use std::{collections::HashMap, fmt};
-use crate::{model::{linear::{LinearStatementValue, LinearStatement}}, frontend::buildtree::{BuildTree, BTTopDefn}, controller::source::ParsePosition};
+use crate::{model::{linear::{LinearStatementValue, LinearStatement, recover_each}}, frontend::buildtree::{BuildTree, BTTopDefn}, controller::source::ParsePosition};
+use crate::controller::diagnostic::{Diagnostics, DiagnosticCode};
 
 #[derive(Clone,PartialEq,Eq,Hash,PartialOrd,Ord)]
 pub(crate) enum BroadType {
@@ -57,7 +58,7 @@ impl<'a> BroadTyping<'a> {
                 }
             },
             LinearStatementValue::Check(_,_,_,_,_) => {},
-            LinearStatementValue::Signature(_) => {},
+            LinearStatementValue::Signature(_,_) => {},
             LinearStatementValue::Entry(_) => {},
         }
         Ok(())
@@ -66,10 +67,9 @@ impl<'a> BroadTyping<'a> {
     fn take(self) -> (HashMap<usize,BroadType>,HashMap<usize,usize>) { (self.types,self.blocks) }
 }
 
-pub(crate) fn broad_type(bt: &BuildTree, stmts: &[LinearStatement]) -> Result<(HashMap<usize,BroadType>,HashMap<usize,usize>),String> {
+pub(crate) fn broad_type(bt: &BuildTree, stmts: &[LinearStatement]) -> Result<(HashMap<usize,BroadType>,HashMap<usize,usize>),Diagnostics> {
     let mut typing = BroadTyping::new(bt);
-    for stmt in stmts {
-        typing.add(stmt).map_err(|e| typing.position.message(&e))?;
-    }
-    Ok(typing.take())
+    let mut errors = Diagnostics::new();
+    recover_each(stmts,DiagnosticCode::BroadType,&mut errors,|stmt| typing.add(stmt));
+    errors.result(typing.take())
 }
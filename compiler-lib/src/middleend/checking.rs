@@ -10,8 +10,9 @@
  */
 
 use std::{collections::{HashMap, HashSet}};
-use crate::{frontend::{buildtree::{BuildTree, BTTopDefn}}, unbundle::linearize::Allocator, util::equiv::EquivalenceClass, model::{checkstypes::{CheckType}, linear::{LinearStatement, LinearStatementValue}, constants::Constant, codeblocks::CodeBlock}, controller::source::ParsePosition};
+use crate::{frontend::{buildtree::{BuildTree, BTTopDefn}}, unbundle::linearize::Allocator, util::equiv::EquivalenceClass, model::{checkstypes::{CheckType}, linear::{LinearStatement, LinearStatementValue, recover_each}, constants::Constant, codeblocks::CodeBlock}, controller::source::ParsePosition};
 use super::broadtyping::BroadType;
+use crate::controller::diagnostic::{Diagnostics, DiagnosticCode};
 
 pub(crate) struct Checking<'a> {
     bt: &'a BuildTree,
@@ -221,18 +222,16 @@ impl<'a> Checking<'a> {
     }
 }
 
-pub(crate) fn run_checking(bt: &BuildTree, stmts: &[LinearStatement], block_indexes: &HashMap<usize,usize>, allocator: &mut Allocator,  broad: &mut HashMap<usize,BroadType>, verbose: bool) -> Result<Vec<LinearStatement>,String> {
+pub(crate) fn run_checking(bt: &BuildTree, stmts: &[LinearStatement], block_indexes: &HashMap<usize,usize>, allocator: &mut Allocator,  broad: &mut HashMap<usize,BroadType>, verbose: bool) -> Result<Vec<LinearStatement>,Diagnostics> {
     let mut typing = Checking::new(bt,block_indexes,allocator,broad);
-    for stmt in stmts {
-        typing.make_equivs(stmt).map_err(|e| typing.position.message(&e))?;
-    }
+    let mut errors = Diagnostics::new();
+    recover_each(stmts,DiagnosticCode::Check,&mut errors,|stmt| typing.make_equivs(stmt));
+    if !errors.is_empty() { return Err(errors); }
     typing.done_making_equivs();
-    for stmt in stmts {
-        typing.groupify(stmt).map_err(|e| typing.position.message(&e))?;
-    }
-    for stmt in stmts {
-        typing.check(stmt).map_err(|e| typing.position.message(&e))?;
-    }
+    recover_each(stmts,DiagnosticCode::Check,&mut errors,|stmt| typing.groupify(stmt));
+    if !errors.is_empty() { return Err(errors); }
+    recover_each(stmts,DiagnosticCode::Check,&mut errors,|stmt| typing.check(stmt));
+    if !errors.is_empty() { return Err(errors); }
     if verbose {
         eprintln!("adding checks left {} statements",typing.out.len());
         typing.allocator.verbose();
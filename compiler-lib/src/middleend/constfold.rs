@@ -1,5 +1,5 @@
 use std::{collections::{HashMap}};
-use crate::{frontend::buildtree::{BuildTree, BTTopDefn}, model::{constants::{FullConstant, OperationConstant}, operation::{Operation, OperationValue}, linear::{LinearStatementValue, LinearStatement}, codeblocks::{CodeBlock, CodeModifier}, checkstypes::AtomicTypeSpec}, controller::{compilation::EardCompilation, source::ParsePosition}};
+use crate::{frontend::buildtree::{BuildTree, BTTopDefn}, model::{constants::{FullConstant, OperationConstant, Constant}, operation::{Operation, OperationValue}, linear::{LinearStatementValue, LinearStatement}, codeblocks::{CodeBlock, CodeModifier}, checkstypes::AtomicTypeSpec}, controller::{compilation::EardCompilation, source::ParsePosition, diagnostic::{Diagnostics, DiagnosticCode}}};
 use super::narrowtyping::NarrowType;
 
 struct ConstFold<'a,'b> {
@@ -9,6 +9,7 @@ struct ConstFold<'a,'b> {
     block_indexes: &'b HashMap<usize,usize>,
     values: HashMap<usize,FullConstant>,
     position: ParsePosition,
+    errors: Diagnostics,
     out: Vec<Operation>
 }
 
@@ -18,6 +19,7 @@ impl<'a,'b> ConstFold<'a,'b> {
             comp: compilation, bt, block_indexes, narrow,
             values: HashMap::new(),
             position: ParsePosition::empty("called"),
+            errors: Diagnostics::new(),
             out: vec![]
         }
     }
@@ -80,6 +82,27 @@ impl<'a,'b> ConstFold<'a,'b> {
         for fold in &folds {
             if self.fold(fold,rets,args) { return; }
         }
+        if block.modifiers.contains(&CodeModifier::Special("libcore__const".to_string())) {
+            let declaration = match self.values.get(&args[0]) {
+                Some(FullConstant::Atomic(Constant::Number(n))) => self.bt.get_const(n.0 as usize),
+                _ => None
+            };
+            let (name,position) = declaration.map(|(n,p)| (n.as_str(),p)).unwrap_or(("?",&self.position));
+            let msg = format!("const {} does not evaluate to a constant",name);
+            self.errors.add(position.diagnostic(DiagnosticCode::Codegen,&msg));
+        }
+        if block.modifiers.contains(&CodeModifier::Special("libcore__map".to_string())) {
+            if let (Some(x),Some(FullConstant::Finite(keys))) = (self.values.get(&args[0]),self.values.get(&args[1])) {
+                let missing = match x {
+                    FullConstant::Atomic(k) | FullConstant::Infinite(k) => if keys.contains(k) { None } else { Some(k) },
+                    FullConstant::Finite(k) => k.iter().find(|k| !keys.contains(k))
+                };
+                if let Some(key) = missing {
+                    let msg = format!("{:?} is not in map table",key);
+                    self.errors.add(self.position.diagnostic(DiagnosticCode::Codegen,&msg));
+                }
+            }
+        }
         self.out(OperationValue::Code(call,name,rets.to_vec(),args.to_vec()));
     }
 
@@ -96,7 +119,7 @@ impl<'a,'b> ConstFold<'a,'b> {
                 let block = self.get_block(*call,*name).clone();
                 self.code(&block,*call,*name,rets,args);
             },
-            LinearStatementValue::Signature(_) => {},
+            LinearStatementValue::Signature(_,_) => {},
             LinearStatementValue::Entry(s) => {
                 self.values.clear();
                 self.out(OperationValue::Entry(s.to_string()));
@@ -104,10 +127,12 @@ impl<'a,'b> ConstFold<'a,'b> {
         }
     }
 
-    fn take(self) -> Vec<Operation> { self.out }
+    fn take(self) -> Result<Vec<Operation>,Diagnostics> {
+        if !self.errors.is_empty() { Err(self.errors) } else { Ok(self.out) }
+    }
 }
 
-pub(crate) fn const_fold<'a,'b>(compilation: &'b EardCompilation<'a>, bt: &'b BuildTree, block_indexes: &'b HashMap<usize,usize>, narrow: &HashMap<usize,NarrowType>, stmts: &[LinearStatement], verbose: bool) -> Vec<Operation> {
+pub(crate) fn const_fold<'a,'b>(compilation: &'b EardCompilation<'a>, bt: &'b BuildTree, block_indexes: &'b HashMap<usize,usize>, narrow: &HashMap<usize,NarrowType>, stmts: &[LinearStatement], verbose: bool) -> Result<Vec<Operation>,Diagnostics> {
     let mut fold = ConstFold::new(compilation,bt,block_indexes,narrow);
     for stmt in stmts {
         fold.add(stmt);
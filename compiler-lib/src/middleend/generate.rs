@@ -6,6 +6,7 @@
 
 use std::{collections::{HashMap, BTreeSet, BTreeMap, HashSet}};
 use crate::{frontend::{buildtree::{BTTopDefn, BuildTree}}, middleend::narrowtyping::NarrowType, model::{constants::{FullConstant, OperationConstant}, step::Step, operation::{OperationValue, Operation}, codeblocks::{ImplBlock, CodeBlock, CodeImplArgument, CodeReturn}}, controller::source::ParsePosition};
+use crate::controller::diagnostic::{Diagnostic, DiagnosticCode};
 
 #[derive(Copy,Clone,PartialEq,Eq,PartialOrd,Ord,Hash,Debug)]
 struct NewRegister(usize);
@@ -67,6 +68,7 @@ struct Generate<'a> {
     narrow: &'a HashMap<usize,NarrowType>,
     position: ParsePosition,
     out: Vec<Step>,
+    positions: Vec<ParsePosition>, // parallel to out
     /**/
     constants: HashMap<usize,OperationConstant>, // from oldreg
     unborn_constants: HashSet<usize>,
@@ -85,10 +87,16 @@ impl<'a> Generate<'a> {
             last_use: HashMap::new(),
             regmap: HashMap::new(),
             reg_alloc: RegAllocate::new(),
-            out: vec![]
+            out: vec![],
+            positions: vec![]
         }
     }
 
+    fn emit(&mut self, step: Step) {
+        self.out.push(step);
+        self.positions.push(self.position.clone());
+    }
+
     fn clear(&mut self) {
         self.constants.clear();
         self.unborn_constants.clear();
@@ -174,7 +182,7 @@ impl<'a> Generate<'a> {
                 },
                 CodeReturn::Constant(c) => {
                     let new_ret = if mapping.rets[i] == 0 { NewRegister(0) } else { self.reg_alloc.allocate() };
-                    self.out.push(Step::Constant(new_ret.0,OperationConstant::Constant(FullConstant::Atomic(c.clone()))));
+                    self.emit(Step::Constant(new_ret.0,OperationConstant::Constant(FullConstant::Atomic(c.clone()))));
                     self.regmap.insert(*reg,new_ret);
                 }
             }
@@ -243,7 +251,7 @@ impl<'a> Generate<'a> {
                 self.reg_alloc.allocate()
             };
             self.regmap.insert(reg,new_reg);
-            self.out.push(Step::Constant(new_reg.0,c.clone()));
+            self.emit(Step::Constant(new_reg.0,c.clone()));
         }
         reg
     }
@@ -262,27 +270,27 @@ impl<'a> Generate<'a> {
             OperationValue::Code(call,name,rets,args) =>  {
                 let block = self.get_block(*call,*name).clone();
                 if let Some(step) = self.code(&block,index,rets,args)? {
-                    self.out.push(step);
+                    self.emit(step);
                 }
             },
             OperationValue::Entry(s) => {
-                self.out.push(Step::Entry(s.to_string()));
+                self.emit(Step::Entry(s.to_string()));
                 self.clear();
             }
         }
         Ok(())
     }
 
-    fn take(self) -> Vec<Step> { self.out }
+    fn take(self) -> (Vec<Step>,Vec<ParsePosition>) { (self.out,self.positions) }
 }
 
-pub(crate) fn generate(bt: &BuildTree, block_index: &HashMap<usize,usize>, narrow: &HashMap<usize,NarrowType>, opers: &[Operation], verbose: bool) -> Result<Vec<Step>,String> {
+pub(crate) fn generate(bt: &BuildTree, block_index: &HashMap<usize,usize>, narrow: &HashMap<usize,NarrowType>, opers: &[Operation], verbose: bool) -> Result<(Vec<Step>,Vec<ParsePosition>),Diagnostic> {
     let mut generate = Generate::new(bt,block_index,narrow);
     for (i,oper) in opers.iter().enumerate() {
         generate.find_last(i,oper);
     }
     for (i,oper) in opers.iter().enumerate() {
-        generate.add(i,&oper).map_err(|e| generate.position.message(&e))?;
+        generate.add(i,&oper).map_err(|e| generate.position.diagnostic(DiagnosticCode::Codegen,&e))?;
     }
     if verbose {
         eprintln!("generated {} opcodes, using {} registers",generate.out.len(),generate.reg_alloc.next_reg);
@@ -1,5 +1,5 @@
 use std::collections::{HashSet, HashMap};
-use crate::{model::operation::{Operation, OperationValue}, unbundle::linearize::Allocator, frontend::buildtree::{BuildTree, BTTopDefn}};
+use crate::{model::operation::{Operation, OperationValue}, unbundle::linearize::Allocator, frontend::buildtree::{BuildTree, BTTopDefn}, controller::diagnostic::{Diagnostic, DiagnosticCode}};
 
 struct Large<'a> {
     allocator: &'a mut Allocator,
@@ -52,10 +52,10 @@ impl<'a> Large<'a> {
     }
 }
 
-pub(crate) fn large(bt: &BuildTree, block_index: &HashMap<usize,usize>,allocator: &mut Allocator, opers: &[Operation]) -> Result<Vec<Operation>,String> {
+pub(crate) fn large(bt: &BuildTree, block_index: &HashMap<usize,usize>,allocator: &mut Allocator, opers: &[Operation]) -> Result<Vec<Operation>,Diagnostic> {
     let mut large = Large::new(bt,block_index,allocator);
     for oper in opers {
-        large.add(oper)?;
+        large.add(oper).map_err(|e| oper.position.diagnostic(DiagnosticCode::Codegen,&e))?;
     }
     Ok(large.out)
 }
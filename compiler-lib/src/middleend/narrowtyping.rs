@@ -1,6 +1,7 @@
 use std::{collections::{HashMap, HashSet}, mem, fmt};
-use crate::{frontend::buildtree::{BuildTree, BTTopDefn}, model::{linear::{LinearStatement, LinearStatementValue}, checkstypes::{AtomicTypeSpec, TypeSpec}, codeblocks::CodeArgument}, controller::source::ParsePosition, util::equiv::EquivalenceClass};
+use crate::{frontend::buildtree::{BuildTree, BTTopDefn}, model::{linear::{LinearStatement, LinearStatementValue, recover_each_at}, checkstypes::{AtomicTypeSpec, TypeSpec}, codeblocks::{CodeArgument, CodeBlock, CodeModifier}}, controller::source::ParsePosition, util::equiv::EquivalenceClass};
 use super::{possible::NarrowPoss, broadtyping::BroadType};
+use crate::controller::diagnostic::{Diagnostics, DiagnosticCode};
 
 #[derive(PartialEq,Eq,Clone,PartialOrd,Ord,Hash)]
 pub(crate) enum NarrowType {
@@ -24,7 +25,7 @@ struct NarrowTyping<'a> {
     position: ParsePosition,
     possible: HashMap<usize,NarrowPoss>,
     equivs: EquivalenceClass<usize>,
-    seen: HashSet<usize>
+    seen: HashMap<usize,ParsePosition>
 }
 
 impl<'a> NarrowTyping<'a> {
@@ -34,12 +35,14 @@ impl<'a> NarrowTyping<'a> {
             position: ParsePosition::empty("called"),
             possible: HashMap::new(),
             equivs: EquivalenceClass::new(),
-            seen: HashSet::new(),
+            seen: HashMap::new(),
         }
     }
 
     fn poss_for_reg(&mut self, reg: usize) -> &mut NarrowPoss {
-        self.seen.insert(reg);
+        if !self.seen.contains_key(&reg) {
+            self.seen.insert(reg,self.position.clone());
+        }
         let reg = self.equivs.canon(reg);
         self.possible.entry(reg).or_insert_with(|| NarrowPoss::any())
     }
@@ -104,12 +107,26 @@ impl<'a> NarrowTyping<'a> {
         Ok(())
     }
 
-    fn code(&mut self, call: usize, name: usize, rets: &[usize], args: &[usize]) -> Result<(),String> {
+    fn block(&self, call: usize, name: usize) -> Result<&'a CodeBlock,String> {
         let block_index = *self.block_index.get(&call).unwrap_or(&0);
-        let block = match self.bt.get_by_index(name)? {
-            BTTopDefn::Code(c) => c.get_block(block_index),
+        match self.bt.get_by_index(name)? {
+            BTTopDefn::Code(c) => Ok(c.get_block(block_index)),
             _ => { panic!("didn't get code with code index"); }
-        };
+        }
+    }
+
+    /* map! checks its table when expanded, but the type of the value looked up is only known here */
+    fn map_keys(&mut self, args: &[usize]) -> Result<(),String> {
+        let keys = self.poss_for_reg(args[1]).clone();
+        let value = self.poss_for_reg(args[0]).clone();
+        if value.clone().unify(&keys).is_err() {
+            return Err(format!("map! keys are {:?} but the value looked up is {:?}",keys,value));
+        }
+        Ok(())
+    }
+
+    fn code(&mut self, call: usize, name: usize, rets: &[usize], args: &[usize]) -> Result<(),String> {
+        let block = self.block(call,name)?;
         /* arguments */
         let mut ties = HashMap::new();
         for (spec,reg) in block.arguments.iter().zip(args.iter()) {
@@ -152,8 +169,20 @@ impl<'a> NarrowTyping<'a> {
         Ok(())
     }
 
-    fn add(&mut self, stmt: &LinearStatement) -> Result<(),String> {
+    fn add(&mut self, stmt: &LinearStatement) -> Result<(),(String,ParsePosition)> {
         self.position = stmt.position.clone();
+        if let LinearStatementValue::Code(call,name,_,args) = &stmt.value {
+            let special = CodeModifier::Special("libcore__map_keys".to_string());
+            if self.block(*call,*name).map(|b| b.modifiers.contains(&special)).unwrap_or(false) {
+                /* blame the map! rather than the libcore function it expands to */
+                let position = stmt.position.caller().unwrap_or(stmt.position.clone());
+                self.map_keys(args).map_err(|e| (e,position))?;
+            }
+        }
+        self.add_value(stmt).map_err(|e| (e,stmt.position.clone()))
+    }
+
+    fn add_value(&mut self, stmt: &LinearStatement) -> Result<(),String> {
         match &stmt.value {
             LinearStatementValue::Code(call,name,rets,args) => { 
                 self.code(*call,*name,rets,args)?;
@@ -162,8 +191,8 @@ impl<'a> NarrowTyping<'a> {
                 let spec = c.to_atomic_type();
                 self.poss_for_reg(*reg).restrict_by_spec(&spec)?;
             },
-            LinearStatementValue::Signature(s) => {
-                self.signature(s)?;
+            LinearStatementValue::Signature(s,message) => {
+                self.signature(s).map_err(|e| message.clone().unwrap_or(e))?;
             },
             LinearStatementValue::Check(_, _, _, _, _) => {},
             LinearStatementValue::Copy(_, _) => {},
@@ -172,22 +201,26 @@ impl<'a> NarrowTyping<'a> {
         Ok(())
     }
 
-    fn finalise(&mut self) -> Result<HashMap<usize,NarrowType>,String> {
+    fn finalise(&mut self, poisoned: &HashSet<usize>, errors: &mut Diagnostics) -> HashMap<usize,NarrowType> {
         let mut out = HashMap::new();
-        let seen = mem::replace(&mut self.seen,HashSet::new());
-        for reg in seen {
+        let mut seen = mem::take(&mut self.seen).into_iter().collect::<Vec<_>>();
+        seen.sort_by_key(|(reg,_)| *reg);
+        for (reg,position) in seen {
+            if poisoned.contains(&reg) { continue; }
             let broad = self.broad.get(&reg).expect("missing broad type for register");
-            let narrow = self.poss_for_reg(reg).calc_type(broad)?;
-            out.insert(reg,narrow);
+            match self.poss_for_reg(reg).calc_type(broad) {
+                Ok(narrow) => { out.insert(reg,narrow); },
+                Err(e) => { errors.add(position.diagnostic(DiagnosticCode::NarrowType,&e)); }
+            }
         }
-        Ok(out)
+        out
     }
 }
 
-pub(crate) fn narrow_type(bt: &BuildTree, block_index: &HashMap<usize,usize>, broad: &HashMap<usize,BroadType>, stmts: &[LinearStatement]) -> Result<HashMap<usize,NarrowType>,String> {
+pub(crate) fn narrow_type(bt: &BuildTree, block_index: &HashMap<usize,usize>, broad: &HashMap<usize,BroadType>, stmts: &[LinearStatement]) -> Result<HashMap<usize,NarrowType>,Diagnostics> {
     let mut typing = NarrowTyping::new(bt,block_index,broad);
-    for stmt in stmts {
-        typing.add(stmt).map_err(|e| typing.position.message(&e))?;
-    }
-    typing.finalise()
+    let mut errors = Diagnostics::new();
+    let poisoned = recover_each_at(stmts,DiagnosticCode::NarrowType,&mut errors,|stmt| typing.add(stmt));
+    let narrow = typing.finalise(&poisoned,&mut errors);
+    errors.result(narrow)
 }
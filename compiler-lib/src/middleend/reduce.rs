@@ -1,6 +1,6 @@
 use std::collections::{HashMap};
 
-use crate::model::{linear::{LinearStatementValue, LinearStatement}};
+use crate::{model::{linear::{LinearStatementValue, LinearStatement}}, controller::diagnostic::{Diagnostic, DiagnosticCode}};
 
 /* NOTE! After linearizing we are not yet in signle-assignment form as multiple consecutive calls
  * to a function/procedure reuse registers (we can get away without rewriting or a stack because)
@@ -30,11 +30,11 @@ impl Reduce {
             LinearStatementValue::Check(name,reg,ct,ci,f) => {
                 Some(LinearStatementValue::Check(name.clone(),self.canon(*reg),ct.clone(),*ci,*f))
             },
-            LinearStatementValue::Signature(s) => {
+            LinearStatementValue::Signature(s,message) => {
                 let s = s.iter().map(|(reg,spec)| {
                     (self.canon(*reg),spec.to_vec())
                 }).collect::<Vec<_>>();
-                Some(LinearStatementValue::Signature(s))
+                Some(LinearStatementValue::Signature(s,message.clone()))
             },
             LinearStatementValue::Constant(reg,c) => {
                 Some(LinearStatementValue::Constant(self.canon(*reg),c.clone()))
@@ -61,11 +61,11 @@ impl Reduce {
     }
 }
 
-pub(crate) fn reduce(stmts: &[LinearStatement], verbose: bool) -> Result<Vec<LinearStatement>,String> {
+pub(crate) fn reduce(stmts: &[LinearStatement], verbose: bool) -> Result<Vec<LinearStatement>,Diagnostic> {
     let mut reduce = Reduce::new();
     let mut out = vec![];
     for stmt in stmts {
-        if let Some(new_stmt) = reduce.reduce(stmt)? {
+        if let Some(new_stmt) = reduce.reduce(stmt).map_err(|e| stmt.position.diagnostic(DiagnosticCode::Linearize,&e))? {
             out.push(new_stmt);
         }
     }
@@ -36,6 +36,7 @@
 
 use std::{collections::HashMap, mem};
 use crate::{frontend::{buildtree::{BTTopDefn, BuildTree}}, util::toposort::TopoSort, model::{operation::{Operation, OperationValue}, codeblocks::{CodeModifier, CodeBlock}, constants::OperationConstant}, controller::source::ParsePosition};
+use crate::controller::diagnostic::{Diagnostic, DiagnosticCode};
 
 #[derive(PartialEq,Eq,Hash,Clone,Debug)]
 enum ReorderNode {
@@ -187,7 +188,7 @@ impl<'a> Reorder<'a> {
     }
 }
 
-pub(crate) fn reorder(bt: &BuildTree, block_index: &HashMap<usize,usize>, opers: &[Operation]) -> Result<Vec<Operation>,String> {
+pub(crate) fn reorder(bt: &BuildTree, block_index: &HashMap<usize,usize>, opers: &[Operation]) -> Result<Vec<Operation>,Diagnostic> {
     /* populate and toposort initial graph */
     let mut reorder = Reorder::new(bt,block_index,10_000_000);
     for (i,oper) in opers.iter().enumerate() {
@@ -200,7 +201,7 @@ pub(crate) fn reorder(bt: &BuildTree, block_index: &HashMap<usize,usize>, opers:
     reorder.build();
     /* find instructions where we'd like to attempt a modifiable form */
     for (i,oper) in opers.iter().enumerate() {
-        reorder.make_useful_arcs(i,oper).map_err(|e| reorder.position.message(&e))?;
+        reorder.make_useful_arcs(i,oper).map_err(|e| reorder.position.diagnostic(DiagnosticCode::Codegen,&e))?;
     }
     reorder.add_useful_arcs();
     let order = reorder.topo.order().unwrap().iter().filter_map(|x| match x {
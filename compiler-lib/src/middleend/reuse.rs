@@ -1,5 +1,5 @@
 use std::{collections::{HashMap, BTreeMap}, fmt};
-use crate::{frontend::buildtree::{BTTopDefn, BuildTree}, model::{constants::{OperationConstant}, operation::{OperationValue, Operation}, codeblocks::{CodeBlock, CodeModifier}}, test::testutil::sepfmt};
+use crate::{frontend::buildtree::{BTTopDefn, BuildTree}, model::{constants::{OperationConstant}, operation::{OperationValue, Operation}, codeblocks::{CodeBlock, CodeModifier}}, test::testutil::sepfmt, controller::diagnostic::{Diagnostic, DiagnosticCode}};
 use super::{broadtyping::BroadType, narrowtyping::NarrowType};
 
 #[derive(PartialEq,Eq,Clone,PartialOrd,Ord)]
@@ -158,10 +158,10 @@ pub(crate) fn test_reuse(bt: &BuildTree, broad: &HashMap<usize,BroadType>, narro
     Ok(known.test_take())
 }
 
-pub(crate) fn reuse(bt: &BuildTree, broad: &HashMap<usize,BroadType>, narrow: &HashMap<usize,NarrowType>, block_indexes: &HashMap<usize,usize>, opers: &[Operation], verbose: bool) -> Result<Vec<Operation>,String> {
+pub(crate) fn reuse(bt: &BuildTree, broad: &HashMap<usize,BroadType>, narrow: &HashMap<usize,NarrowType>, block_indexes: &HashMap<usize,usize>, opers: &[Operation], verbose: bool) -> Result<Vec<Operation>,Diagnostic> {
     let mut known = KnownValues::new(bt,broad,narrow,block_indexes);
     for oper in opers {
-        known.add(oper)?;
+        known.add(oper).map_err(|e| oper.position.diagnostic(DiagnosticCode::Codegen,&e))?;
     }
     if verbose {
         eprintln!("memoizing side-effect free expressions left {} statements",known.out.len());
@@ -1,7 +1,7 @@
 use std::{convert::Infallible, fmt, collections::HashMap};
 use json::{JsonValue, object::Object};
 use minicbor::{Encoder, encode::Error};
-use crate::{test::testutil::sepfmt, controller::serialise::OpcodeVersion};
+use crate::{test::testutil::sepfmt, controller::{serialise::OpcodeVersion, diagnostic::SourceSpan}};
 
 use super::constants::OperationConstant;
 
@@ -47,11 +47,36 @@ impl Metadata {
 
 pub(crate) struct CompiledBlock {
     pub(crate) constants: Vec<OperationConstant>,
-    pub(crate) program: Vec<(usize,Vec<usize>)>
+    pub(crate) program: Vec<(usize,Vec<usize>)>,
+    pub(crate) debug: Vec<Vec<SourceSpan>> // parallel to program: position, then callers
 }
 
 impl CompiledBlock {
-    fn encode(&self, encoder: &mut Encoder<&mut Vec<u8>>) -> Result<(),Error<Infallible>> {
+    /* Only written when asked for. Older readers skip keys they don't know. */
+    fn encode_debug(&self, encoder: &mut Encoder<&mut Vec<u8>>) -> Result<(),Error<Infallible>> {
+        encoder.str("debug")?.begin_array()?;
+        for spans in &self.debug {
+            encoder.array(spans.len() as u64)?;
+            for span in spans {
+                encoder.array(2)?.str(&span.filename)?.u32(span.line)?;
+            }
+        }
+        encoder.end()?;
+        Ok(())
+    }
+
+    fn encode_debug_json(&self) -> JsonValue {
+        JsonValue::Array(self.debug.iter().map(|spans| {
+            JsonValue::Array(spans.iter().map(|span| {
+                JsonValue::Array(vec![
+                    JsonValue::String(span.filename.to_string()),
+                    JsonValue::Number(span.line.into())
+                ])
+            }).collect())
+        }).collect())
+    }
+
+    fn encode(&self, encoder: &mut Encoder<&mut Vec<u8>>, debug: bool) -> Result<(),Error<Infallible>> {
         encoder.begin_map()?.str("constants")?.begin_array()?;
         for c in &self.constants {
             c.encode(encoder)?;
@@ -63,11 +88,15 @@ impl CompiledBlock {
                 encoder.u32(*oparg as u32)?;
             }
         }
-        encoder.end()?.end()?; /* program entry; main map */
+        encoder.end()?; /* program entry */
+        if debug {
+            self.encode_debug(encoder)?;
+        }
+        encoder.end()?; /* main map */
         Ok(())
     }
 
-    fn encode_json(&self) -> JsonValue {
+    fn encode_json(&self, debug: bool) -> JsonValue {
         let mut out = Object::new();
         let constants = JsonValue::Array(self.constants.iter().map(|c| c.encode_json()).collect());
         out.insert("constants",constants);
@@ -79,6 +108,9 @@ impl CompiledBlock {
             program.push(JsonValue::Array(value));
         }
         out.insert("program",JsonValue::Array(program));
+        if debug {
+            out.insert("debug",self.encode_debug_json());
+        }
         JsonValue::Object(out)
     }
 }
@@ -98,24 +130,24 @@ pub struct CompiledCode {
 }
 
 impl CompiledCode {
-    pub(crate) fn encode(&self, encoder: &mut Encoder<&mut Vec<u8>>) -> Result<(),Error<Infallible>> {
+    pub(crate) fn encode(&self, encoder: &mut Encoder<&mut Vec<u8>>, debug: bool) -> Result<(),Error<Infallible>> {
         encoder.begin_map()?.str("metadata")?;
         self.metadata.encode(encoder)?;
         encoder.str("blocks")?.begin_map()?;
         for (name,block) in self.code.iter() {
             encoder.str(name)?;
-            block.encode(encoder)?;
+            block.encode(encoder,debug)?;
         }
         encoder.end()?.end()?;
         Ok(())
     }
 
-    pub(crate) fn encode_json(&self) -> JsonValue {
+    pub(crate) fn encode_json(&self, debug: bool) -> JsonValue {
         let mut out = Object::new();
         out.insert("metadata",self.metadata.name.encode_json());
         let mut blocks = Object::new();
         for (name,block) in self.code.iter() {
-            blocks.insert(&name,block.encode_json());
+            blocks.insert(&name,block.encode_json(debug));
         }
         out.insert("blocks",JsonValue::Object(blocks));
         JsonValue::Object(out)
@@ -41,7 +41,8 @@ impl Constant {
     fn encode(&self, encoder: &mut Encoder<&mut Vec<u8>>) -> Result<(),Error<Infallible>> {
         match self {
             Constant::Number(n) => { 
-                if n.0.fract() == 0. {
+                /* whole numbers too big for an i64 would saturate */
+                if (n.0 as i64) as f64 == n.0 {
                     encoder.i64(n.0 as i64)?;
                 } else {
                     encoder.f64(n.0)?; 
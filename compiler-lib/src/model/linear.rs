@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{fmt, collections::HashSet};
 use super::{checkstypes::{CheckType, TypeSpec}, constants::Constant};
 
 #[derive(Clone)]
@@ -7,15 +7,26 @@ pub(crate) enum LinearStatementValue {
     Constant(usize,Constant),
     Copy(usize,usize), // to,from
     Code(usize,usize,Vec<usize>,Vec<usize>), // call,index,rets,args
-    Signature(Vec<(usize,Vec<TypeSpec>)>),
+    Signature(Vec<(usize,Vec<TypeSpec>)>,Option<String>), // message to give on mismatch
     Entry(String)
 }
 
 #[cfg(test)]
 use crate::test::testutil::AllocDumper;
-use crate::{test::testutil::sepfmt, controller::source::ParsePosition};
+use crate::{test::testutil::sepfmt, controller::{source::ParsePosition, diagnostic::{Diagnostics, DiagnosticCode}}};
 
 impl LinearStatementValue {
+    fn registers(&self) -> Vec<usize> {
+        match self {
+            Self::Check(_,reg,_,_,_) => vec![*reg],
+            Self::Constant(reg,_) => vec![*reg],
+            Self::Copy(to,from) => vec![*to,*from],
+            Self::Code(_,_,rets,args) => rets.iter().chain(args.iter()).cloned().collect(),
+            Self::Signature(sig,_) => sig.iter().map(|(reg,_)| *reg).collect(),
+            Self::Entry(_) => vec![]
+        }
+    }
+
     #[cfg(test)]
     fn dump(&self, ad: &mut AllocDumper) -> String {
         match self {
@@ -23,7 +34,7 @@ impl LinearStatementValue {
                 let force = if *force { "f" } else { "" };
                 format!("r{:?} <check:{}>{} {:?} {:?}",v,name,force,ct,c)
             },
-            Self::Signature(r) => {
+            Self::Signature(r,_) => {
                 let sig = r.iter().map(|(reg,retrs)| {
                     format!("r{}: {}",reg,sepfmt(&mut retrs.iter(),", ",""))
                 }).collect::<Vec<_>>();
@@ -47,7 +58,7 @@ impl fmt::Debug for LinearStatementValue {
                 let force = if *force { "f" } else { "" };
                 write!(f,"r{:?} <check:{}>{} {:?} {:?}",v,name,force,ct,c)
             },
-            Self::Signature(r) => {
+            Self::Signature(r,_) => {
                 let sig = r.iter().map(|(reg,retrs)| {
                     format!("r{}: {}",reg,sepfmt(&mut retrs.iter(),", ",""))
                 }).collect::<Vec<_>>();
@@ -83,6 +94,34 @@ impl fmt::Debug for LinearStatement {
     }
 }
 
+/* Runs cb over each statement collecting errors rather than stopping at the first. Registers
+ * touched by a failing statement are poisoned and later statements using them are skipped to
+ * avoid a cascade of knock-on errors. Returns the poisoned registers.
+ */
+pub(crate) fn recover_each<F>(stmts: &[LinearStatement], code: DiagnosticCode, errors: &mut Diagnostics, mut cb: F) -> HashSet<usize>
+        where F: FnMut(&LinearStatement) -> Result<(),String> {
+    recover_each_at(stmts,code,errors,|stmt| cb(stmt).map_err(|e| (e,stmt.position.clone())))
+}
+
+/* As recover_each, but cb also says where the error is to be reported. */
+pub(crate) fn recover_each_at<F>(stmts: &[LinearStatement], code: DiagnosticCode, errors: &mut Diagnostics, mut cb: F) -> HashSet<usize>
+        where F: FnMut(&LinearStatement) -> Result<(),(String,ParsePosition)> {
+    let mut poisoned = HashSet::new();
+    for stmt in stmts {
+        let regs = stmt.value.registers();
+        if regs.iter().any(|r| poisoned.contains(r)) {
+            poisoned.extend(regs);
+            continue;
+        }
+        if let Err((e,position)) = cb(stmt) {
+            errors.add(position.diagnostic(code,&e));
+            poisoned.extend(regs);
+            if errors.full() { break; }
+        }
+    }
+    poisoned
+}
+
 #[cfg(test)]
 pub(crate) fn dump_linear(linear: &[LinearStatement]) -> String {
     let mut ad = AllocDumper::new();
@@ -1,5 +1,6 @@
+use std::fs;
 use ordered_float::OrderedFloat;
-use crate::{ controller::compiler::EardCompiler, controller::compilation::EardCompilation, controller::source::{CombinedSourceSourceBuilder, FixedSourceSource, CombinedSourceSource, ParsePosition, SourceSourceImpl}, libcore::libcore::libcore_sources, model::constants::Constant, test::testutil::sepfmt};
+use crate::{ controller::compiler::EardCompiler, controller::compilation::EardCompilation, controller::source::{CombinedSourceSourceBuilder, FixedSourceSource, CombinedSourceSource, ParsePosition, SourceSourceImpl}, libcore::libcore::libcore_sources, model::constants::Constant, test::testutil::sepfmt, controller::diagnostic::{Diagnostics, DiagnosticCode}, frontend::format::{format_source, format_file, format_files}, controller::serialise::EardSerializeCode, controller::compiled::make_program};
 use crate::frontend::parsetree::{PTExpression};
 use super::testharness::run_parse_tests;
 
@@ -190,7 +191,7 @@ fn test_multi_generate() {
         let stmts = compilation.parse(&position,"test",true).expect("cannot parse");
         let stmts = compilation.preprocess(stmts).expect("preprocess");
         let tree = compilation.build(stmts).expect("building");
-        let (step,_) = compilation.middleend(&tree).expect("middleend");
+        let (step,_,_) = compilation.middleend(&tree).expect("middleend");
         let text = sepfmt(&mut step.iter(),"\n","");
         for line in text.split("\n") {
             if line.contains("opcode") {
@@ -204,3 +205,355 @@ fn test_multi_generate() {
     }
     assert_eq!(vec![901, 901, 901, 901, 902, 902, 902, 902, 902, 903, 904, 904],chosen);
 }
+
+fn compile(files: Vec<(&str,&str)>) -> Result<(),Diagnostics> {
+    let compiler = EardCompiler::new().expect("bad compiler");
+    let mut compilation = EardCompilation::new(&compiler).expect("bad compilation");
+    let mut soso_builder = CombinedSourceSourceBuilder::new().expect("cannot create soso");
+    soso_builder.add_fixed(&libcore_sources());
+    soso_builder.add_fixed(&FixedSourceSource::new_vec(files));
+    let soso = CombinedSourceSource::new(&soso_builder);
+    let position = ParsePosition::root(SourceSourceImpl::new(soso),"included");
+    compilation.parse(&position,"test",true)
+        .and_then(|stmts| compilation.preprocess(stmts))
+        .map_err(Diagnostics::from)
+        .and_then(|stmts| compilation.build(stmts))
+        .and_then(|tree| compilation.middleend(&tree))
+        .map(|_| ())
+}
+
+fn diagnose(files: Vec<(&str,&str)>) -> Diagnostics {
+    compile(files).expect_err("unexpected success")
+}
+
+/* check and compile read from disk, so give them a real file */
+fn check_and_compile(name: &str, src: &str) -> (Result<(),Diagnostics>,Result<(),Diagnostics>) {
+    let path = std::env::temp_dir().join(format!("eard-check-{}-{}.eard",std::process::id(),name));
+    fs::write(&path,src).expect("cannot write source");
+    let path = path.to_string_lossy().to_string();
+    let compiler = EardCompiler::new().expect("bad compiler");
+    let checked = EardCompilation::new(&compiler).expect("bad compilation").check(&path);
+    let compiled = EardCompilation::new(&compiler).expect("bad compilation").compile(&path).map(|_| ());
+    fs::remove_file(&path).ok();
+    (checked,compiled)
+}
+
+fn check_fails_as_compile(name: &str, src: &str) -> String {
+    let (checked,compiled) = check_and_compile(name,src);
+    let checked = checked.expect_err("check unexpectedly succeeded").to_string();
+    let compiled = compiled.expect_err("compile unexpectedly succeeded").to_string();
+    assert_eq!(compiled,checked);
+    checked
+}
+
+#[test]
+fn test_check() {
+    let (checked,compiled) = check_and_compile("good","program \"test\" \"test\" 1;\nlet x = 1;\nprint(format(x+1));\n");
+    assert!(checked.is_ok() && compiled.is_ok());
+    let diags = check_fails_as_compile("bad","program \"test\" \"test\" 1;\nlet x = 1;\nprint(x+\"a\");\n");
+    assert!(diags.contains("-bad.eard:3"),"{}",diags);
+}
+
+#[test]
+fn test_check_include() {
+    let path = std::env::temp_dir().join(format!("eard-check-include-{}.eard",std::process::id()));
+    let compiler = EardCompiler::new().expect("bad compiler");
+    fs::write(&path,"export function f(x) { x+1 }\n").expect("cannot write source");
+    let path_s = path.to_string_lossy().to_string();
+    let header = EardCompilation::new(&compiler).expect("bad compilation").check(&path_s).expect_err("check unexpectedly succeeded");
+    let included = EardCompilation::new(&compiler).expect("bad compilation").check_include(&path_s);
+    fs::write(&path,"let x = 1;\nprint(x+\"a\");\n").expect("cannot write source");
+    let bad = EardCompilation::new(&compiler).expect("bad compilation").check_include(&path_s);
+    fs::remove_file(&path).ok();
+    assert!(header.to_string().contains("missing program header"));
+    assert!(included.is_ok());
+    let diags = bad.expect_err("check_include unexpectedly succeeded").to_string();
+    assert!(diags.contains(".eard:2"),"{}",diags);
+}
+
+#[test]
+fn test_buffers() {
+    let dir = std::env::temp_dir().join(format!("eard-buffers-{}",std::process::id()));
+    fs::create_dir_all(&dir).expect("cannot make directory");
+    fs::write(dir.join("inner.eard"),"export function f(x) { x+\"a\" }\n").expect("cannot write source");
+    let main = dir.join("main.eard");
+    let main_src = "program \"test\" \"test\" 1;\ninclude \"inner.eard\";\nprint(format(f(1)));\n";
+    let compiler = EardCompiler::new().expect("bad compiler");
+    /* main is only a buffer and its include is read from disk */
+    let mut compilation = EardCompilation::new(&compiler).expect("bad compilation");
+    compilation.add_buffer(&main,main_src);
+    let diags = compilation.check(&main.to_string_lossy()).expect_err("check unexpectedly succeeded").to_string();
+    assert!(diags.contains("inner.eard:1"),"{}",diags);
+    /* the include's buffer replaces what's on disk */
+    let mut compilation = EardCompilation::new(&compiler).expect("bad compilation");
+    compilation.add_buffer(&main,main_src);
+    compilation.add_buffer(&dir.join("inner.eard"),"export function f(x) { x+1 }\n");
+    let result = compilation.compile(&main.to_string_lossy());
+    fs::remove_dir_all(&dir).ok();
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_diagnostics() {
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\n  refer \"inner\";\n"),
+        ("inner","\nlet x = ;\n")
+    ]);
+    assert_eq!(1,diags.len());
+    let diag = diags.iter().next().unwrap();
+    assert_eq!(DiagnosticCode::Parse,diag.code);
+    let span = diag.span.clone().expect("missing span");
+    assert_eq!(("inner",2,9),(span.filename.as_str(),span.line,span.column));
+    assert_eq!(Some("let x = ;".to_string()),span.source);
+    assert_eq!(vec![("test".to_string(),2,3)],diag.chain.iter().map(|s| (s.filename.clone(),s.line,s.column)).collect::<Vec<_>>());
+    assert!(diag.render().contains("--> inner:2:9"));
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\n\n    print(y);\n")
+    ]);
+    let diag = diags.iter().next().unwrap();
+    assert_eq!(DiagnosticCode::Linearize,diag.code);
+    let span = diag.span.clone().expect("missing span");
+    assert_eq!(("test",3,5),(span.filename.as_str(),span.line,span.column));
+    assert_eq!("unknown variable 'y' at test:3",diag.to_string());
+}
+
+#[test]
+fn test_const_diagnostics() {
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\nworld code wc() -> number { impl() -> r1: number { opcode 999, r1; } }\nfunction w() { let x = wc(); x }\nconst X = w() + 1;\nhalt(X > 0);\n")
+    ]);
+    assert_eq!("const X does not evaluate to a constant at test:4",diags.to_string());
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\nrefer \"inner\";\nhalt(Y > 0);\nhalt(Z > 0);\n"),
+        ("inner","export const Y = 1;\nconst Z = 2;\n")
+    ]);
+    assert_eq!("unknown variable 'Z' at test:4",diags.to_string());
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\nconst X = 1;\nlet X = 2;\nconst Y = Y;\nhalt(Y > 0);\n")
+    ]);
+    assert_eq!("cannot assign to const X at test:3\nconst Y refers to itself at test:4",diags.to_string());
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\nconst A = B + 1;\nconst B = A;\nhalt(A > 0);\nhalt(A > 1);\n")
+    ]);
+    assert_eq!("const A refers to itself at test:2",diags.to_string());
+    let diags = check_fails_as_compile("const","program \"test\" \"test\" 1;\nworld code wc() -> number { impl() -> r1: number { opcode 999, r1; } }\nfunction w() { let x = wc(); x }\nconst X = w() + 1;\nhalt(X > 0);\n");
+    assert!(diags.starts_with("const X does not evaluate to a constant at ") && diags.ends_with("-const.eard:4"),"{}",diags);
+}
+
+#[test]
+fn test_namespace_diagnostics() {
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\nrefer \"a\";\nrefer \"b\";\n"),
+        ("a","export function label(x) { x }\n"),
+        ("b","\nexport function label(x) { x }\n")
+    ]);
+    assert_eq!("label is exported both here and at a:1, include one of them with \"as\" to keep them apart at b:2 (included from test:3)",diags.to_string());
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\nrefer \"a\" as a;\nlet x = a.helper(1);\nlet y = b.label(1);\nlet z = label(1);\n"),
+        ("a","function helper(x) { x }\nexport function label(x) { x }\n")
+    ]);
+    assert_eq!("a does not export helper at test:3\nno such namespace b at test:4\nNo such function/procedure label at test:5",diags.to_string());
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\nrefer \"e\" as e;\nrefer \"a\" as e;\n"),
+        ("e",""),
+        ("a","export function label(x) { x }\n")
+    ]);
+    assert_eq!("namespace e already used at test:3",diags.to_string());
+}
+
+#[test]
+fn test_record_diagnostics() {
+    let record = "record Transcript { start: seq(number) length(?X), end: seq(number) length(?X), name: string }\n";
+    let diags = diagnose(vec![
+        ("test",&format!("program \"test\" \"test\" 1;\n{}let tr = Transcript {{ start: [1,2], name: \"a\" }};\nlet tr = Transcript {{ start: [1], end: [2], name: \"a\", strand: 1 }};\nlet tr = Gene {{}};\n",record))
+    ]);
+    assert_eq!("record Transcript is missing field end at test:3\nrecord Transcript has no field strand at test:4\nno such record Gene at test:5",diags.to_string());
+    let diags = diagnose(vec![
+        ("test",&format!("program \"test\" \"test\" 1;\n{}let tr = Transcript {{ start: [1,2], end: [3,4], name: \"a\" }};\nlet s = tr.strand;\ntr.names = \"b\";\n",record))
+    ]);
+    assert_eq!("tr is a Transcript record, which has no field strand at test:4\ntr is a Transcript record, which has no field names at test:5",diags.to_string());
+    let diags = diagnose(vec![
+        ("test",&format!("program \"test\" \"test\" 1;\n{}let s : length(!n) = [1,2];\nlet tr = Transcript {{ start: s, end: s + 10, name: \"a\" }};\nlet tr2 = Transcript {{ start: s, end: [3], name: \"b\" }};\n",record))
+    ]);
+    assert_eq!("checking error: cannot guarantee Length at test:2 (called from test:5)",diags.to_string());
+    let diags = diagnose(vec![
+        ("test",&format!("program \"test\" \"test\" 1;\n{}let s : length(!n) = [1,2];\nlet tr = Transcript {{ start: s, end: s, name: 1 }};\n",record))
+    ]);
+    assert_eq!("field name of Transcript must be string at test:4",diags.to_string());
+    let diags = diagnose(vec![
+        ("test",&format!("program \"test\" \"test\" 1;\n{}let e : length(!n) = [\"a\",\"b\"];\nlet n = 1;\nlet tr = Transcript {{ start: e, end: e, name: n }};\n",record))
+    ]);
+    assert_eq!("field start of Transcript must be seq(number) at test:5\nfield name of Transcript must be string at test:5",diags.to_string());
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\nrefer \"a\" as a;\nlet x = a.Point { x: 1, y: 2 };\nlet z = a.Hidden { z: x.y };\n"),
+        ("a","export record Point { x: number, y: number }\nrecord Hidden { z: number }\n")
+    ]);
+    assert_eq!("a does not export Hidden at test:4",diags.to_string());
+}
+
+#[test]
+fn test_map_diagnostics() {
+    for (table,error) in [
+        ("[[\"a\",1],[\"a\",2]],0","map! has duplicate key \"a\""),
+        ("[[\"a\",1],[\"b\",\"x\"]],0","map! values must all be the same type"),
+        ("[[\"a\",1],[\"b\",2]],\"none\"","map! values must all be the same type"),
+        ("[[\"a\",x]],0","map! table entries must be literal constants"),
+        ("[\"a\",1]","map! expects a non-empty table of [key,value] pairs"),
+        ("[]","map! expects a non-empty table of [key,value] pairs"),
+    ] {
+        let diags = diagnose(vec![
+            ("test",&format!("program \"test\" \"test\" 1;\nlet x = 1;\nlet y = map!(x,{});\n",table))
+        ]);
+        assert_eq!(format!("{} at test:3",error),diags.to_string());
+    }
+    for (value,table) in [("[1,2]","[[\"a\",1]],0"),("1","[[\"a\",1]]")] {
+        let diags = diagnose(vec![
+            ("test",&format!("program \"test\" \"test\" 1;\nlet x = {};\nlet y = map!(x,{});\nprint(format(y));\n",value,table))
+        ]);
+        assert_eq!("map! keys are string but the value looked up is number at test:3",diags.to_string());
+    }
+    for (key,missing) in [("\"z\"","\"z\""),("[\"a\",\"y\"]","\"y\"")] {
+        let diags = diagnose(vec![
+            ("test",&format!("program \"test\" \"test\" 1;\nlet y = map!({},[[\"a\",1]]);\nprint(format(y));\n",key))
+        ]).to_string();
+        assert!(diags.starts_with(&format!("{} is not in map table at ",missing)) && diags.ends_with(" (called from test:2)"),"{}",diags);
+    }
+    let diags = check_fails_as_compile("map","program \"test\" \"test\" 1;\nlet y = map!(\"z\",[[\"a\",1]]);\nprint(format(y));\n");
+    assert!(diags.starts_with("\"z\" is not in map table at ") && diags.ends_with("-map.eard:2)"),"{}",diags);
+}
+
+#[test]
+fn test_multiple_diagnostics() {
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\nfunction f(x) { x }\nfunction f(x) { x }\nfunction g(x) { x }\nfunction g(x) { x }\n")
+    ]);
+    assert_eq!("duplicate definition for f at test:3\nduplicate definition for g at test:5",diags.to_string());
+    let mut source = "program \"test\" \"test\" 1;\nfunction f(x) { x }\n".to_string();
+    for _ in 0..20 {
+        source.push_str("function f(x) { x }\n");
+    }
+    let diags = diagnose(vec![("test",&source)]);
+    assert_eq!(20,diags.len());
+    assert!(!diags.render().contains("too many errors"));
+    for _ in 0..10 {
+        source.push_str("function f(x) { x }\n");
+    }
+    let diags = diagnose(vec![("test",&source)]);
+    assert_eq!(20,diags.len());
+    assert!(diags.render().contains("too many errors"));
+    let diags = diagnose(vec![
+        ("test","program \"test\" \"test\" 1;\nprocedure p(a: seq(number) length(?X), b: seq(number) length(?X)) { print(format(a+b)); }\np([1,2],[3]);\n")
+    ]);
+    assert_eq!("checking error: cannot guarantee Length at test:2 (called from test:3)",diags.to_string());
+}
+
+#[test]
+fn test_definitions() {
+    let mut compiler = EardCompiler::new().expect("bad compiler");
+    compiler.add_source(FixedSourceSource::new_vec(vec![
+        ("test","refer \"inner\";\nfunction f(x) { x }\nrefer \"other\" as o;\n"),
+        ("inner","\nexport procedure g(a: number) -> (number) { (a) }\ncode h(number) -> (string) {}\n"),
+        ("other","export record Point { x: number, y: number }\n")
+    ]));
+    let mut compilation = EardCompilation::new(&compiler).expect("bad compilation");
+    let defs = compilation.definitions("test",true).expect("definitions failed");
+    let found = defs.iter().map(|d| {
+        format!("{} {} {}",d.signature,d.exported,d.span.as_ref().map(|s| s.to_string()).unwrap_or_default())
+    }).collect::<Vec<_>>();
+    assert_eq!(vec![
+        "export procedure g(a: number) -> (number) true inner:2",
+        "code h(number) -> (string) false inner:3",
+        "function f(x) false test:2",
+        "export record Point { x: number, y: number } true other:1"
+    ],found);
+    let point = defs.iter().find(|d| d.name == "Point").expect("missing record");
+    assert_eq!((vec!["o".to_string()],vec!["x".to_string(),"y".to_string()]),(point.namespaces.clone(),point.fields.clone()));
+    assert!(defs.iter().all(|d| d.name == "Point" || d.namespaces.is_empty()));
+    /* library definitions point back at the source they were built from */
+    let compiler = EardCompiler::new().expect("bad compiler");
+    let defs = EardCompilation::new(&compiler).expect("bad compilation").definitions("libcore",true).expect("definitions failed");
+    let print = defs.iter().find(|d| d.name == "print").expect("missing print");
+    let span = print.span.as_ref().expect("missing span");
+    let source = fs::read_to_string(span.path.as_ref().expect("missing path")).expect("missing library source");
+    assert!(source.lines().nth(span.line as usize-1).expect("missing line").contains("procedure print"));
+}
+
+#[test]
+fn test_format() {
+    let formatted = format_source("input.eard",include_str!("testdata/format/input.eard")).expect("format failed");
+    assert_eq!(include_str!("testdata/format/output.eard"),formatted);
+    for src in &[
+        include_str!("testdata/format/output.eard"),
+        include_str!("../libcore/eard/sequences.eard"),
+        include_str!("../libcore/eard/string.eard"),
+        include_str!("../libcore/eard/arith.eard")
+    ] {
+        let once = format_source("test",src).expect("format failed");
+        assert_eq!(once,format_source("test",&once).expect("reformat failed"));
+    }
+    let src = "code __code_y(seq(?X),number) -> (seq(?X),number) { impl (r1: seq(number),r2: number) -> (r3: seq(number),r4: number) { opcode 9,r3,r4,r1,r2; } }\nfunction f(a: number,b) { a }\nfunction g(a,b) { a }\n";
+    let once = format_source("test",src).expect("format failed");
+    assert_eq!("code __code_y(seq(?X), number) -> (seq(?X), number) {\n    impl (r1: seq(number), r2: number) -> (r3: seq(number), r4: number) {\n        opcode 9, r3, r4, r1, r2;\n    }\n}\nfunction f(a: number, b) { a }\nfunction g(a, b) { a }\n",once);
+    assert_eq!(once,format_source("test",&once).expect("reformat failed"));
+    let e = format_source("bad.eard","let x = ;\n").expect_err("unexpected success");
+    assert_eq!("expected expr_or_bundle or record_name at bad.eard:1",e.to_string());
+}
+
+#[test]
+fn test_format_file() {
+    let path = std::env::temp_dir().join(format!("eard-format-{}.eard",std::process::id()));
+    let filename = path.to_string_lossy().to_string();
+    fs::write(&path,"print(  \"x\" );\n").expect("cannot write");
+    assert!(!format_file(&filename,true).expect("format failed"));
+    assert_eq!("print(  \"x\" );\n",fs::read_to_string(&path).expect("cannot read"));
+    assert!(!format_file(&filename,false).expect("format failed"));
+    assert_eq!("print(\"x\");\n",fs::read_to_string(&path).expect("cannot read"));
+    assert!(format_file(&filename,true).expect("format failed"));
+    assert!(format_files(&[filename.clone()],true).expect("format failed"));
+    fs::write(&path,"print(  \"x\" );\n").expect("cannot write");
+    assert!(!format_files(&[filename.clone()],true).expect("format failed"));
+    assert!(format_files(&[filename.clone()],false).expect("format failed"));
+    assert_eq!("print(\"x\");\n",fs::read_to_string(&path).expect("cannot read"));
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_debug_section() {
+    let compiler = EardCompiler::new().expect("bad compiler");
+    let mut compilation = EardCompilation::new(&compiler).expect("bad compilation");
+    let mut soso_builder = CombinedSourceSourceBuilder::new().expect("cannot create soso");
+    soso_builder.add_fixed(&libcore_sources());
+    soso_builder.add_fixed(&FixedSourceSource::new_vec(vec![
+        ("test","program \"group\" \"test\" 1;\nworld code wc(?X) -> ?X { impl(r1: ?X) -> r2: ?X { opcode 21, r2, r1; } }\nfunction f(x) {\n    let y = wc(x);\n    y\n}\nprint(format(f(1)));\n")
+    ]));
+    let soso = CombinedSourceSource::new(&soso_builder);
+    let position = ParsePosition::root(SourceSourceImpl::new(soso),"included");
+    let stmts = compilation.parse(&position,"test",true).expect("cannot parse");
+    let stmts = compilation.preprocess(stmts).expect("preprocess");
+    let tree = compilation.build(stmts).expect("building");
+    let (steps,positions,metadata) = compilation.middleend(&tree).expect("middleend");
+    let mut output = EardSerializeCode::new();
+    output.add(make_program(&steps,&positions,&metadata));
+    assert!(!output.serialize_json().contains("\"debug\""));
+    let plain = output.serialize().expect("serialize failed");
+    output.set_debug(true);
+    let json = output.serialize_json();
+    assert!(json.contains("[ [ \"print\", 26 ], [ \"test\", 7 ] ]"));
+    assert!(json.contains("[ [ \"test\", 4 ], [ \"test\", 7 ] ]"));
+    assert!(output.serialize().expect("serialize failed").len() > plain.len());
+}
+
+#[test]
+fn test_length_ties() {
+    let source = "program \"test\" \"test\" 1;\nlet v : length(!a) = [10,20,30];\nlet m = [0,1,1];\n";
+    let diags = diagnose(vec![("test",&format!("{}let w : length(a) = m;\n",source))]);
+    assert_eq!("checking error: cannot guarantee Length at test:4",diags.to_string());
+    compile(vec![("test",&format!("{}let s = group_sum(v,m,2);\nlet w : length(a) = m;\nprint(format(s));\n",source))]).expect("group_sum lengths not tied");
+    compile(vec![("test",&format!("{}let (s,e,i) = merge_intervals(v,m);\nlet w : length(a) = i;\nlet x : length(!b) = s;\nlet y : length(b) = e;\nprint(format(w));\nprint(format(x));\nprint(format(y));\n",source))]).expect("merge_intervals lengths not tied");
+    compile(vec![("test",&format!("{}let (x,y) = overlaps(v,m,[1,2],[3,4]);\nlet w : length(a) = m;\nlet p : length(!b) = x;\nlet q : length(b) = y;\nprint(format(w));\nprint(format(p));\nprint(format(q));\n",source))]).expect("overlaps lengths not tied");
+    compile(vec![("test",&format!("{}let k = [true,false,true];\nlet f = filter(v,k);\nlet w : length(a) = k;\nprint(format(f));\nprint(format(w));\n",source))]).expect("filter lengths not tied");
+    compile(vec![("test",&format!("{}let k = [true,false,true];\nlet z = scatter(v,k,[5]);\nlet w : length(a) = k;\nlet x : length(a) = z;\nprint(format(w));\nprint(format(x));\n",source))]).expect("scatter lengths not tied");
+    compile(vec![("test",&format!("{}let (u,i) = unique(v);\nlet w : length(a) = i;\nprint(format(u));\nprint(format(w));\n",source))]).expect("unique lengths not tied");
+    compile(vec![("test",&format!("{}let p : length(a) = pow(2,v);\nprint(format(p));\n",source))]).expect("pow lengths not tied");
+    compile(vec![("test",&format!("{}let p : length(a) = clamp(v,0,1);\nlet q : length(a) = max(v,m);\nprint(format(p));\nprint(format(q));\n",source))]).expect("clamp lengths not tied");
+}
@@ -52,7 +52,7 @@ pub(crate) fn make_compiler() -> Result<EardCompiler,String> {
     })?;
     compiler.add_block_macro("z", |_expr,pos,context| {
         Ok(vec![PTStatement {
-            value: PTStatementValue::Include("test2".to_string(),true),
+            value: PTStatementValue::Include("test2".to_string(),true,None),
             position: pos.clone(),
             context
         }])
@@ -153,7 +153,7 @@ fn process_ws(input: &str, options: &HashSet<String>) -> String {
 fn frontend(compilation: &mut EardCompilation, processed: &[PTStatement]) -> (BuildTree,Vec<LinearStatement>,Allocator,Metadata) {
     let tree = compilation.build(processed.to_vec()).expect("build failed");
     let bundles = build_unbundle(&tree).expect("unbundle failed");
-    let (linear,next_register,metadata) = linearize(&tree,&bundles,true).expect("linearize failed");
+    let (linear,next_register,metadata) = linearize(&tree,&bundles,true,true).expect("linearize failed");
     (tree,reduce(&linear,true).expect("reduce failed"),next_register,metadata)
 }
 
@@ -211,7 +211,7 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
                     assert!(false);
                 },
                 Err(e) => {
-                    if !(process_ws(&e,parse_options).contains(&process_ws(&parse,parse_options))) {
+                    if !(process_ws(&e.to_string(),parse_options).contains(&process_ws(parse,parse_options))) {
                         eprintln!("{:?} does not contain {:?}",e,parse);
                         assert!(false);
                     }
@@ -258,7 +258,7 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
                     assert!(false);
                 },
                 Err(e) => {
-                    if !(process_ws(&e,parse_options).contains(&process_ws(&parse,parse_options))) {
+                    if !(process_ws(&e.to_string(),parse_options).contains(&process_ws(parse,parse_options))) {
                         eprintln!("{:?} does not contain {:?}",e,parse);
                         assert!(false);
                     }
@@ -291,7 +291,7 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
         if let Some((built_options,built_fail)) = sections.get("built-fail") {
             let processed = compilation.build(processed.clone().expect("processing failed")).err().expect("build unexpectedly succeeded");
             println!("{}\n",processed);
-            assert_eq!(process_ws(built_fail,built_options),process_ws(&processed,built_options));
+            assert_eq!(process_ws(built_fail,built_options),process_ws(&processed.to_string(),built_options));
             continue;
         }
         if let Some((unbundle_options,unbundle_correct)) = sections.get("unbundle") {
@@ -319,13 +319,13 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
         if let Some((unbundle_options,unbundle_err)) = sections.get("unbundle-fail") {
             let processed = compilation.build(processed.expect("processing failed")).expect("build failed");
             let error = trace_build_unbundle(&processed).err().expect("unbundle unexpectedly succeeded");
-            assert_eq!(process_ws(&error,unbundle_options),process_ws(&unbundle_err,unbundle_options));
+            assert_eq!(process_ws(&error.to_string(),unbundle_options),process_ws(unbundle_err,unbundle_options));
             continue;
         }
         if let Some((linearized_options,linearized_correct)) = sections.get("linearize") {
             let tree = compilation.build(processed.clone().expect("processing failed")).expect("build failed");
             let bundles = build_unbundle(&tree).expect("unbundle failed");
-            let (mut linear,_,_) = linearize(&tree,&bundles,true).expect("linearize failed");
+            let (mut linear,_,_) = linearize(&tree,&bundles,true,true).expect("linearize failed");
             if linearized_options.contains("reduce") {
                 linear = reduce(&linear,true).expect("reduce failed");
             }
@@ -335,9 +335,9 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
         if let Some((linearized_options,linearized_correct)) = sections.get("linearize-fail") {
             let tree = compilation.build(processed.expect("processing failed")).expect("build failed");
             let bundles = build_unbundle(&tree).expect("unbundle failed");
-            let error = linearize(&tree,&bundles,true).err().expect("linearize unexpectedly succeeded");
+            let error = linearize(&tree,&bundles,true,true).err().expect("linearize unexpectedly succeeded");
             println!("{}",error);
-            assert_eq!(process_ws(&error,linearized_options),process_ws(linearized_correct,linearized_options));
+            assert_eq!(process_ws(&error.to_string(),linearized_options),process_ws(linearized_correct,linearized_options));
             continue;
         }
         if let Some((broad_options,broad_correct)) = sections.get("broad") {
@@ -359,7 +359,7 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
             println!("{}",dump_linear(&linear));
             let got = broad_type(&tree,&linear).err().expect("typing unexpectedly succeeded");
             println!("{}",got);
-            assert_eq!(process_ws(&got,broad_options),process_ws(broad_correct,broad_options));
+            assert_eq!(process_ws(&got.to_string(),broad_options),process_ws(broad_correct,broad_options));
         }
         if let Some((checking_options,checking_correct)) = sections.get("checking") {
             let processed = processed.clone().expect("processing failed");
@@ -374,7 +374,7 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
             let (tree,linear,mut next_register,_) = frontend(&mut compilation,&processed);
             let (mut broad,block_indexes) = broad_type(&tree,&linear).expect("typing failed");
             let error = run_checking(&tree,&linear,&block_indexes,&mut next_register,&mut broad,true).err().expect("checking unexpectedly succeeded");
-            assert_eq!(process_ws(&error,checking_options),process_ws(checking_expected,checking_options));
+            assert_eq!(process_ws(&error.to_string(),checking_options),process_ws(checking_expected,checking_options));
         }
         if let Some((narrow_options,narrow_correct)) = sections.get("narrow") {
             let processed = processed.clone().expect("processing failed");
@@ -398,7 +398,7 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
             let linear = run_checking(&tree,&linear,&block_indexes,&mut next_register,&mut broad,true).expect("checking unexpectedly failed");
             let error = narrow_type(&tree,&block_indexes,&broad,&linear).err().expect("narrow unespectedly succeeded");
             println!("{}",error);
-            assert_eq!(process_ws(&error,narrow_options),process_ws(narrow_correct,narrow_options));
+            assert_eq!(process_ws(&error.to_string(),narrow_options),process_ws(narrow_correct,narrow_options));
         }
         if let Some((constfold_options,constfold_correct)) = sections.get("constfold") {
             let processed = processed.clone().expect("processing failed");
@@ -406,7 +406,7 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
             let (mut broad,block_indexes) = broad_type(&tree,&linear).expect("broad typing failed");
             let linear = run_checking(&tree,&linear,&block_indexes,&mut next_register,&mut broad,true).expect("checking unexpectedly failed");
             let narrow = narrow_type(&tree,&block_indexes,&broad,&linear).expect("narrow typing failed");
-            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true);
+            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true).expect("constant folding failed");
             if constfold_options.contains("culdesac") {
                 opers = culdesac(&tree,&block_indexes,&opers,true);
             }
@@ -419,7 +419,7 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
             let (mut broad,block_indexes) = broad_type(&tree,&linear).expect("broad typing failed");
             let linear = run_checking(&tree,&linear,&block_indexes,&mut next_register,&mut broad,true).expect("checking unexpectedly failed");
             let narrow = narrow_type(&tree,&block_indexes,&broad,&linear).expect("narrow typing failed");
-            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true);
+            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true).expect("constant folding failed");
             opers = culdesac(&tree,&block_indexes,&opers,true);
             let (new_opers, knowns) = test_reuse(&tree,&broad,&narrow,&block_indexes,&opers).expect("reuse failed");
             println!("FROM:\n {}\n\n",dump_opers(&opers));
@@ -439,7 +439,7 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
             let (mut broad,block_indexes) = broad_type(&tree,&linear).expect("broad typing failed");
             let linear = run_checking(&tree,&linear,&block_indexes,&mut next_register, &mut broad,true).expect("checking unexpectedly failed");
             let mut narrow = narrow_type(&tree,&block_indexes,&broad,&linear).expect("narrow typing failed");
-            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true);
+            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true).expect("constant folding failed");
             opers = culdesac(&tree,&block_indexes,&opers,true);
             opers = reuse(&tree,&broad,&narrow,&block_indexes,&opers,true).expect("reuse failed");
             opers = spill(&mut next_register,&opers, &mut narrow);
@@ -452,7 +452,7 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
             let (mut broad,block_indexes) = broad_type(&tree,&linear).expect("broad typing failed");
             let linear = run_checking(&tree,&linear,&block_indexes,&mut next_register,&mut broad,true).expect("checking unexpectedly failed");
             let mut narrow = narrow_type(&tree,&block_indexes,&broad,&linear).expect("narrow typing failed");
-            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true);
+            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true).expect("constant folding failed");
             opers = culdesac(&tree,&block_indexes,&opers,true);
             opers = reuse(&tree,&broad,&narrow,&block_indexes,&opers,true).expect("reuse failed");
             opers = spill(&mut next_register,&opers,&mut narrow);
@@ -466,13 +466,13 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
             let (mut broad,block_indexes) = broad_type(&tree,&linear).expect("broad typing failed");
             let linear = run_checking(&tree,&linear,&block_indexes,&mut allocator,&mut broad,true).expect("checking unexpectedly failed");
             let mut narrow = narrow_type(&tree,&block_indexes,&broad,&linear).expect("narrow typing failed");
-            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true);
+            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true).expect("constant folding failed");
             opers = culdesac(&tree,&block_indexes,&opers,true);
             opers = reuse(&tree,&broad,&narrow,&block_indexes,&opers,true).expect("reuse failed");
             opers = spill(&mut allocator,&opers,&mut narrow);
             opers = reorder(&tree,&block_indexes,&opers).expect("reorder failed");
             opers = large(&tree,&block_indexes,&mut allocator,&opers).expect("large failed");
-            let steps = generate(&tree,&block_indexes,&narrow,&opers,true).expect("generate failed");
+            let (steps,_) = generate(&tree,&block_indexes,&narrow,&opers,true).expect("generate failed");
             println!("steps:\n{}",sepfmt(&mut steps.iter(),"\n",""));
             assert_eq!(process_ws(&sepfmt(&mut steps.iter(),"\n",""),generate_options),process_ws(generate_correct,generate_options));
         }
@@ -482,14 +482,14 @@ pub(super) fn run_parse_tests(data: &str, libcore: bool, optimise: bool) {
             let (mut broad,block_indexes) = broad_type(&tree,&linear).expect("broad typing failed");
             let linear = run_checking(&tree,&linear,&block_indexes,&mut allocator,&mut broad,true).expect("checking unexpectedly failed");
             let mut narrow = narrow_type(&tree,&block_indexes,&broad,&linear).expect("narrow typing failed");
-            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true);
+            let mut opers = const_fold(&compilation,&tree,&block_indexes,&narrow,&linear,true).expect("constant folding failed");
             opers = culdesac(&tree,&block_indexes,&opers,true);
             opers = reuse(&tree,&broad,&narrow,&block_indexes,&opers,true).expect("reuse failed");
             opers = spill(&mut allocator,&opers,&mut narrow);
             opers = reorder(&tree,&block_indexes,&opers).expect("reorder failed");
             opers = large(&tree,&block_indexes,&mut allocator,&opers).expect("large failed");
-            let steps = generate(&tree,&block_indexes,&narrow,&opers,true).expect("generate failed");
-            let program = make_program(&steps,&metadata);
+            let (steps,positions) = generate(&tree,&block_indexes,&narrow,&opers,true).expect("generate failed");
+            let program = make_program(&steps,&positions,&metadata);
             println!("{:?}",program);
             assert_eq!(process_ws(&format!("{:?}",program),compiled_options),process_ws(compiled_correct,compiled_options));
         }
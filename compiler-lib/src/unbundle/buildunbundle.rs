@@ -55,6 +55,7 @@
 use std::{collections::{HashSet, HashMap}};
 use crate::{frontend::{buildtree::{BTStatement, BTStatementValue, BTLValue, BTProcCall, BTExpression, BTRegisterType, BuildTree, BTFuncCall, BTTopDefn, Variable }, femodel::{OrBundle, OrBundleRepeater}}, model::checkstypes::TypedArgument, controller::source::ParsePosition};
 use super::{repeater::find_repeater_arguments, unbundleaux::{BundleNamespace, Transits, Position, TransitsBuilder}};
+use crate::controller::diagnostic::{Diagnostic, DiagnosticCode};
 
 fn sorted<T: Clone+Ord>(set: Option<&HashSet<T>>) -> Option<Vec<T>> {
     set.as_ref().map(|set| {
@@ -376,21 +377,21 @@ impl<'a> BuildUnbundle<'a> {
     }
 }
 
-fn do_build_unbundle(tree: &BuildTree) -> Result<BuildUnbundle,String> {
+fn do_build_unbundle(tree: &BuildTree) -> Result<BuildUnbundle,Diagnostic> {
     let mut unbundle = BuildUnbundle::new(tree);
     for stmt in tree.statements.iter().rev() {
-        unbundle.statement(stmt).map_err(|e| unbundle.positions.message(&e))?;
+        unbundle.statement(stmt).map_err(|e| unbundle.positions.diagnostic(DiagnosticCode::Unbundle,&e))?;
     }
     Ok(unbundle)
 }
 
-pub(crate) fn build_unbundle(tree: &BuildTree) -> Result<Transits,String> {
+pub(crate) fn build_unbundle(tree: &BuildTree) -> Result<Transits,Diagnostic> {
     let unbundle = do_build_unbundle(tree)?;
     Ok(unbundle.transits.build())
 }
 
 #[cfg(test)]
-pub(crate) fn trace_build_unbundle(tree: &BuildTree) -> Result<(Transits,Vec<String>),String> {
+pub(crate) fn trace_build_unbundle(tree: &BuildTree) -> Result<(Transits,Vec<String>),Diagnostic> {
     let unbundle = do_build_unbundle(tree)?;
     Ok((unbundle.transits.build(),unbundle.trace.clone()))
 }
@@ -1,7 +1,8 @@
 use std::{collections::{HashMap}};
-use crate::{frontend::{femodel::{OrBundle, OrBundleRepeater}, buildtree::Variable}, model::{linear::{LinearStatementValue, LinearStatement}, checkstypes::{TypedArgument}, compiled::{Metadata, ProgramName}}, controller::{source::ParsePosition, serialise::OpcodeVersion}};
+use crate::{frontend::{femodel::{OrBundle, OrBundleRepeater}, buildtree::Variable}, model::{linear::{LinearStatementValue, LinearStatement}, checkstypes::{TypedArgument, TypeSpec}, compiled::{Metadata, ProgramName}}, controller::{source::ParsePosition, serialise::OpcodeVersion}};
 use crate::frontend::{buildtree::{BuildTree, BTStatement, BTStatementValue, BTLValue, BTProcCall, BTExpression, BTRegisterType, BTFuncProcDefinition, BTTopDefn}};
 use super::{unbundleaux::{Position, VarRegisters, Transits, Checks}, repeater::{find_repeater_arguments, rewrite_repeater}};
+use crate::controller::diagnostic::{Diagnostic, DiagnosticCode};
 
 /* NOTE! After linearizing we are not yet in signle-assignment form as multiple consecutive calls
  * to a function/procedure reuse registers (we can get away without rewriting or a stack because)
@@ -90,7 +91,7 @@ impl<'a> Linearize<'a> {
                     self.var_registers.add(&Variable { name: arg.id.clone(), prefix: None },var_reg);
                     self.add(LinearStatementValue::Copy(var_reg,*regs.next().unwrap()));
                     if arg.typespec.arg_types.len() > 0 {
-                        self.add(LinearStatementValue::Signature(vec![(var_reg,arg.typespec.arg_types.clone())]));
+                        self.add(LinearStatementValue::Signature(vec![(var_reg,arg.typespec.arg_types.clone())],None));
                     }
                     for check in &arg.typespec.checks {
                         let check_index = self.checks.get(&check.check_type,&check.name);
@@ -129,7 +130,7 @@ impl<'a> Linearize<'a> {
     fn ret_checks(&mut self, index: usize, reg: usize, defn: &BTFuncProcDefinition) -> Result<(),String> {
         if let Some(type_spec) = defn.ret_type.as_ref().and_then(|v| v.get(index)) {
             if type_spec.arg_types.len() > 0 {
-                self.add(LinearStatementValue::Signature(vec![(reg,type_spec.arg_types.clone())]));
+                self.add(LinearStatementValue::Signature(vec![(reg,type_spec.arg_types.clone())],None));
             }
             for check in &type_spec.checks {
                 let check_index = self.checks.get(&check.check_type,&check.name);
@@ -222,12 +223,30 @@ impl<'a> Linearize<'a> {
         }
         let regs = regs.iter().filter(|(_,t)| t.len()>0).cloned().collect::<Vec<_>>();
         if regs.len() > 0 {
-            self.add(LinearStatementValue::Signature(regs));
+            self.add(LinearStatementValue::Signature(regs,None));
         }
         Ok(())
     }
 
+    /* Fields of the wrong type are reported where the record is built, rather than deep in typing. */
+    fn record_fields(&mut self, record: &str, defn: &BTFuncProcDefinition, arg_regs: &[usize]) {
+        for (arg,reg) in defn.args.iter().zip(arg_regs.iter()) {
+            if let OrBundle::Normal(arg) = arg {
+                let types = &arg.typespec.arg_types;
+                if types.is_empty() || !types.iter().all(|t| matches!(t,TypeSpec::Atomic(_) | TypeSpec::Sequence(_))) {
+                    continue;
+                }
+                let names = types.iter().map(|t| format!("{:?}",t)).collect::<Vec<_>>();
+                let message = format!("field {} of {} must be {}",arg.id,record,names.join(" or "));
+                self.add(LinearStatementValue::Signature(vec![(*reg,types.clone())],Some(message)));
+            }
+        }
+    }
+
     fn callee(&mut self, index: usize, defn: &BTFuncProcDefinition, arg_regs: &[usize]) -> Result<Vec<usize>,String> {
+        if let Some(record) = &defn.record {
+            self.record_fields(record,defn,arg_regs);
+        }
         self.var_registers.push();
         self.checks.push();
         let old_pos = self.positions.clone();
@@ -237,6 +256,7 @@ impl<'a> Linearize<'a> {
         for stmt in &defn.block {
             self.statement(stmt)?;
         }
+        self.positions.update(defn.position.last());
         let callee_rets = self.callee_rets(&defn)?;
         self.callee_matches(defn,&arg_regs,&callee_rets)?;
         self.positions = old_pos;
@@ -468,13 +488,17 @@ impl<'a> Linearize<'a> {
     }
 }
 
-pub(crate) fn linearize(tree: &BuildTree, bundles: &Transits,verbose: bool) -> Result<(Vec<LinearStatement>,Allocator,Metadata),String> {
+/* need_header is false when checking a file which may only be included, such as a library */
+pub(crate) fn linearize(tree: &BuildTree, bundles: &Transits, need_header: bool, verbose: bool) -> Result<(Vec<LinearStatement>,Allocator,Metadata),Diagnostic> {
     let mut linearize = Linearize::new(tree,bundles);
     for stmt in &tree.statements {
-        linearize.statement(stmt).map_err(|e| linearize.positions.message(&e))?;
+        linearize.statement(stmt).map_err(|e| linearize.positions.diagnostic(DiagnosticCode::Linearize,&e))?;
     }
     if linearize.name.is_none() {
-        return Err("missing program header".to_string());
+        if need_header {
+            return Err(Diagnostic::new(DiagnosticCode::Linearize,"missing program header"));
+        }
+        linearize.name = Some(ProgramName { group: String::new(), name: String::new(), version: 0 });
     }
     if verbose {
         eprintln!("linearised to {} statements",linearize.output.len());
@@ -9,6 +9,6 @@ pub fn libeoe_add(compiler: &mut EardCompiler) -> Result<(),String> {
     compiler.add_block_macro("struct",struct_macro)?;
     compiler.add_source(FixedSourceSource::new_vec(vec![
         ("libeoe",include_str!("eard/libeoe.eard")),
-    ]));
+    ]).with_dir(concat!(env!("CARGO_MANIFEST_DIR"),"/src/eard")));
     Ok(())
 }
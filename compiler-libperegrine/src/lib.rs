@@ -10,6 +10,6 @@ pub fn libperegrine_add(compiler: &mut EardCompiler) -> Result<(),String> {
     compiler.add_block_macro("style",style_macro)?;
     compiler.add_source(FixedSourceSource::new_vec(vec![
         ("libperegrine",include_str!("eard/libperegrine.eard")),
-    ]));
+    ]).with_dir(concat!(env!("CARGO_MANIFEST_DIR"),"/src/eard")));
     Ok(())
 }
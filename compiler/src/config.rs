@@ -1,16 +1,18 @@
-use clap::{Parser, ValueEnum};
-
-#[derive(Debug,Copy,Clone,PartialEq,Eq,PartialOrd,Ord,ValueEnum)]
-pub(crate) enum Format {
-    /// Standard format (binary)
-    #[value(alias("s"))]
-    Standard,
-    /// Expanded (for debugging)
-    #[value(alias("x"))]
-    Expanded,
-    /// Dump (for debugging)
-    #[value(alias("d"))]
-    Dump    
+use clap::{Parser, Subcommand};
+use eard_compiler_lib::OutputFormat;
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum Command {
+    /// Reformat source files in place
+    Fmt {
+        /// Report files which aren't formatted, rather than changing them
+        #[arg(long, default_value_t = false)]
+        check: bool,
+
+        /// Source files to format
+        #[arg(required = true)]
+        files: Vec<String>
+    }
 }
 
 #[derive(Parser, Debug)]
@@ -19,6 +21,9 @@ pub(crate) enum Format {
 #[command(version = "0.0")]
 #[command(about = "Compiles eard source into eard binaries", long_about = None)]
 pub(crate) struct Config {
+   #[command(subcommand)]
+   pub(crate) command: Option<Command>,
+
    /// Source files to compile
    #[arg(short = 'c', long)]
    pub(crate) source: Vec<String>,
@@ -36,8 +41,12 @@ pub(crate) struct Config {
    pub(crate) optimise: bool,
 
    /// Format
-   #[arg(short, long, value_enum, default_value_t = Format::Standard)]
-   pub(crate) format: Format,
+   #[arg(short, long, value_enum, default_value_t = OutputFormat::Standard)]
+   pub(crate) format: OutputFormat,
+
+   /// Include source positions in output, so runtime errors can cite them
+   #[arg(short = 'g', long = "debug-info", default_value_t = false)]
+   pub(crate) debug: bool,
 
    /// Verbose
    #[arg(short = 'v', long, default_value_t = false)]
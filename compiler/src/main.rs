@@ -1,7 +1,7 @@
 mod config;
 use std::{process::exit, fs::File, io::{Write, self}};
-use eard_compiler_lib::{EardCompiler, EardCompilation, EardSerializeCode };
-use config::{Config, Format};
+use eard_compiler_lib::{EardCompiler, EardCompilation, EardSerializeCode, format_files };
+use config::{Command, Config};
 use clap::Parser;
 use eard_compiler_libeoe::libeoe_add;
 use eard_compiler_libperegrine::libperegrine_add;
@@ -20,22 +20,13 @@ fn do_it(config: &Config) -> Result<(),String> {
         compiler.set_verbose(true);
     }
     let mut output = EardSerializeCode::new();
+    output.set_debug(config.debug);
     for src in &config.source {
         let mut compilation = EardCompilation::new(&compiler)?;
-        let code = compilation.compile(src)?;
+        let code = compilation.compile(src).map_err(|e| e.render())?;
         output.add(code);
     }
-    let binary = match &config.format {
-        Format::Standard => {
-            output.serialize()?
-        },
-        Format::Expanded => {
-            output.serialize_json().as_bytes().to_vec()
-        },
-        Format::Dump => {
-            format!("{:?}",output).as_bytes().to_vec()
-        }
-    };
+    let binary = output.output(config.format)?;
     if config.outfile == "-" {
         let mut f = io::stdout().lock();
         f.write_all(&binary).map_err(|e| format!("cannot write file: {}",e))?;
@@ -46,8 +37,20 @@ fn do_it(config: &Config) -> Result<(),String> {
     Ok(())
 }
 
+fn run(config: &Config) -> Result<(),String> {
+    match &config.command {
+        Some(Command::Fmt { check, files }) => {
+            if !format_files(files,*check)? {
+                exit(1);
+            }
+            Ok(())
+        },
+        None => do_it(config)
+    }
+}
+
 fn main() {
-    match do_it(&Config::parse()) {
+    match run(&Config::parse()) {
         Ok(()) => { exit(0); }
         Err(e) => {
             eprintln!("{}",e);
@@ -0,0 +1,21 @@
+use std::{env, process::{exit, Command}};
+
+/* The binary used to be called eard-interp-cli: this keeps that name working by running eard,
+ * installed alongside, with the same arguments.
+ */
+fn main() {
+    let eard = match env::current_exe() {
+        Ok(exe) => exe.with_file_name(format!("eard{}",env::consts::EXE_SUFFIX)),
+        Err(e) => {
+            eprintln!("cannot find eard: {}",e);
+            exit(1);
+        }
+    };
+    match Command::new(&eard).args(env::args_os().skip(1)).status() {
+        Ok(status) => { exit(status.code().unwrap_or(1)); }
+        Err(e) => {
+            eprintln!("cannot run {}: {}",eard.display(),e);
+            exit(1);
+        }
+    }
+}
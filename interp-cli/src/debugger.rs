@@ -0,0 +1,250 @@
+use std::{collections::{BTreeSet, BTreeMap}, fmt, io::{BufRead, Write}};
+use eard_interp::{DebugHook, StepView};
+
+const HELP : &str = "\
+commands:
+  s, step [N]            run N steps (default 1)
+  c, continue            run to the next breakpoint
+  b, break N             break before step N
+  b, break FILE:LINE     break on entering a source line (needs debug info)
+  b, break :LINE         break on entering a line in any file
+  d, delete [BP]         remove a breakpoint, or all breakpoints
+  p, print rN ...        show registers (default: those of the current step)
+  w, watch rN            show register whenever it changes
+  u, unwatch [rN]        stop watching a register, or all registers
+  i, info                list breakpoints and watches
+  where                  show source position and callers
+  q, quit                stop the program
+  h, help                show this message";
+
+#[derive(Clone,PartialEq,Eq,PartialOrd,Ord)]
+enum Breakpoint {
+    Step(usize),
+    Line(Option<String>,u32)
+}
+
+impl Breakpoint {
+    fn parse(spec: &str) -> Result<Breakpoint,String> {
+        if let Some((file,line)) = spec.rsplit_once(':') {
+            let line = line.parse::<u32>().map_err(|_| format!("bad line number {}",line))?;
+            let file = if file.len() > 0 { Some(file.to_string()) } else { None };
+            Ok(Breakpoint::Line(file,line))
+        } else {
+            Ok(Breakpoint::Step(spec.parse::<usize>().map_err(|_| format!("bad step {}",spec))?))
+        }
+    }
+
+    fn hit(&self, view: &StepView) -> bool {
+        match self {
+            Breakpoint::Step(index) => view.index == *index,
+            Breakpoint::Line(file,line) => {
+                view.locations.iter().any(|loc| {
+                    loc.line == *line && file.as_ref().map(|f| loc.filename.ends_with(f.as_str())).unwrap_or(true)
+                })
+            }
+        }
+    }
+
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Step(index) => write!(f,"step {}",index),
+            Breakpoint::Line(Some(file),line) => write!(f,"{}:{}",file,line),
+            Breakpoint::Line(None,line) => write!(f,":{}",line)
+        }
+    }
+}
+
+fn parse_register(spec: &str) -> Result<usize,String> {
+    spec.strip_prefix('r').unwrap_or(spec).parse::<usize>().map_err(|_| format!("bad register {}",spec))
+}
+
+fn describe(view: &StepView) -> String {
+    let regs = view.registers.iter().map(|r| format!("r{}",r)).collect::<Vec<_>>();
+    let mut out = format!("step {}: opcode {} {} {}",view.index,view.opcode,view.name.unwrap_or("*unknown*"),regs.join(" "));
+    if let Some(location) = view.locations.first() {
+        out.push_str(&format!(" ; {}",location));
+    }
+    out
+}
+
+fn show_register(view: &StepView, reg: usize) -> String {
+    match view.value(reg) {
+        Ok(value) => format!("{:?}",value),
+        Err(e) => format!("<{}>",e)
+    }
+}
+
+/* Stops before steps and reads what to do from input, reporting to output (stdin and stderr
+ * when run from the command line, a script in tests).
+ */
+pub(crate) struct Debugger<R,W> {
+    input: R,
+    output: W,
+    breakpoints: BTreeSet<Breakpoint>,
+    active: BTreeSet<Breakpoint>, // line breakpoints we're inside, so that they fire once on entry
+    watches: BTreeMap<usize,String>,
+    steps_left: Option<usize>
+}
+
+impl<R: BufRead, W: Write> Debugger<R,W> {
+    pub(crate) fn new(input: R, mut output: W) -> Debugger<R,W> {
+        writeln!(output,"type h for help").ok();
+        Debugger {
+            input, output,
+            breakpoints: BTreeSet::new(),
+            active: BTreeSet::new(),
+            watches: BTreeMap::new(),
+            steps_left: Some(0)
+        }
+    }
+
+    fn read_line(&mut self) -> Option<String> {
+        write!(self.output,"(eard) ").ok();
+        self.output.flush().ok();
+        let mut line = String::new();
+        match self.input.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim().to_string())
+        }
+    }
+
+    fn should_stop(&mut self, view: &StepView) -> bool {
+        let mut stop = false;
+        if let Some(steps) = self.steps_left.as_mut() {
+            if *steps == 0 { stop = true; } else { *steps -= 1; }
+        }
+        for bp in &self.breakpoints {
+            if bp.hit(view) {
+                if self.active.insert(bp.clone()) {
+                    writeln!(self.output,"breakpoint {}",bp).ok();
+                    stop = true;
+                }
+            } else {
+                self.active.remove(bp);
+            }
+        }
+        stop
+    }
+
+    fn print(&mut self, view: &StepView, args: &[&str]) -> Result<(),String> {
+        let regs = if args.len() > 0 {
+            args.iter().map(|a| parse_register(a)).collect::<Result<Vec<_>,_>>()?
+        } else {
+            view.registers.to_vec()
+        };
+        for reg in regs {
+            writeln!(self.output,"  r{} = {}",reg,show_register(view,reg)).ok();
+        }
+        Ok(())
+    }
+
+    fn info(&mut self) {
+        for bp in &self.breakpoints {
+            writeln!(self.output,"  break {}",bp).ok();
+        }
+        for reg in self.watches.keys() {
+            writeln!(self.output,"  watch r{}",reg).ok();
+        }
+    }
+
+    /* Returns Some(keep running?) when the command resumes (or ends) the program. */
+    fn command(&mut self, view: &StepView, line: &str) -> Result<Option<bool>,String> {
+        let parts = line.split_whitespace().collect::<Vec<_>>();
+        let (cmd,args) = match parts.split_first() {
+            Some((cmd,args)) => (*cmd,args),
+            None => ("step",&[] as &[&str])
+        };
+        match cmd {
+            "s" | "step" => {
+                let count = args.first().map(|n| n.parse::<usize>().map_err(|_| format!("bad count {}",n))).transpose()?;
+                self.steps_left = Some(count.unwrap_or(1).max(1)-1);
+                return Ok(Some(true));
+            },
+            "c" | "continue" => {
+                self.steps_left = None;
+                return Ok(Some(true));
+            },
+            "b" | "break" => {
+                for arg in args {
+                    let bp = Breakpoint::parse(arg)?;
+                    if bp.hit(view) {
+                        /* already there: fire next time it's entered */
+                        self.active.insert(bp.clone());
+                    }
+                    self.breakpoints.insert(bp);
+                }
+                self.info();
+            },
+            "d" | "delete" => {
+                if args.len() == 0 {
+                    self.breakpoints.clear();
+                }
+                for arg in args {
+                    let bp = Breakpoint::parse(arg)?;
+                    if !self.breakpoints.remove(&bp) {
+                        return Err(format!("no such breakpoint {}",arg));
+                    }
+                }
+            },
+            "p" | "print" => { self.print(view,args)?; },
+            "w" | "watch" => {
+                for arg in args {
+                    let reg = parse_register(arg)?;
+                    self.watches.insert(reg,show_register(view,reg));
+                    writeln!(self.output,"  r{} = {}",reg,show_register(view,reg)).ok();
+                }
+            },
+            "u" | "unwatch" => {
+                if args.len() == 0 {
+                    self.watches.clear();
+                }
+                for arg in args {
+                    self.watches.remove(&parse_register(arg)?);
+                }
+            },
+            "i" | "info" => { self.info(); },
+            "where" => {
+                writeln!(self.output,"  {}",view.location().unwrap_or_else(|| "no debug information: compile with -g".to_string())).ok();
+            },
+            "q" | "quit" => { return Ok(Some(false)); },
+            "h" | "help" => { writeln!(self.output,"{}",HELP).ok(); },
+            _ => { return Err(format!("unknown command {}: type h for help",cmd)); }
+        }
+        Ok(None)
+    }
+}
+
+impl<R: BufRead, W: Write> DebugHook for Debugger<R,W> {
+    fn before(&mut self, view: &StepView) -> bool {
+        if !self.should_stop(view) { return true; }
+        writeln!(self.output,"{}",describe(view)).ok();
+        loop {
+            let line = match self.read_line() {
+                Some(line) => line,
+                None => { return false; }
+            };
+            match self.command(view,&line) {
+                Ok(Some(run)) => { return run; },
+                Ok(None) => {},
+                Err(e) => { writeln!(self.output,"{}",e).ok(); }
+            }
+        }
+    }
+
+    fn after(&mut self, view: &StepView) {
+        let mut changed = vec![];
+        for (reg,old) in self.watches.iter() {
+            let new = show_register(view,*reg);
+            if &new != old {
+                changed.push((*reg,new));
+            }
+        }
+        for (reg,new) in changed {
+            writeln!(self.output,"{}\n  r{} = {}",describe(view),reg,new).ok();
+            self.watches.insert(reg,new);
+        }
+    }
+}
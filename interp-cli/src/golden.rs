@@ -0,0 +1,208 @@
+use std::{collections::BTreeMap, fs, future::Future, path::{Path, PathBuf}, pin::Pin, sync::{Arc, Mutex}};
+use async_std::task::block_on;
+use eard_interp::{RunContext, LibcoreTemplate};
+use eard_libperegrine_stub::StubResponses;
+use crate::{guess_block, unindent, libs::{CompileOptions, load_binary, make_interpreter}};
+
+/* For each foo.eard in the directory, foo.responses.json (if present) holds the stub responses
+ * and foo.expected the golden output. Golden files are split into sections like the compiler's
+ * .etf files: ">> printed" is what the program printed, ">> actions" the stub dump and
+ * ">> error" the error, if the run failed.
+ */
+
+const RESPONSES_SUFFIX : &str = "responses.json";
+const EXPECTED_SUFFIX : &str = "expected";
+
+async fn call_up_async() -> Result<(),String> {
+    Ok(())
+}
+
+#[derive(Clone)]
+pub(crate) struct LibcoreCapture(pub(crate) Arc<Mutex<Vec<String>>>);
+
+impl LibcoreTemplate for LibcoreCapture {
+    fn print(&self, s: &str) {
+        self.0.lock().unwrap().push(s.to_string());
+    }
+
+    fn call_up(&self) -> Pin<Box<dyn Future<Output=Result<(),String>>>> {
+        Box::pin(call_up_async())
+    }
+}
+
+fn parse_sections(input: &str) -> BTreeMap<String,String> {
+    let mut out = BTreeMap::new();
+    let mut section = None;
+    for line in input.lines() {
+        if let Some(name) = line.strip_prefix(">> ") {
+            section = Some(name.trim().to_string());
+            out.insert(name.trim().to_string(),String::new());
+        } else if let Some(text) = section.as_ref().and_then(|s| out.get_mut(s)) {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    out.values_mut().for_each(|v| { *v = v.trim_matches('\n').to_string(); });
+    out
+}
+
+fn render_sections(sections: &BTreeMap<String,String>) -> String {
+    let mut out = String::new();
+    for name in &["printed","actions","error"] {
+        if let Some(text) = sections.get(*name) {
+            out.push_str(&format!(">> {}\n\n{}\n\n",name,text));
+        }
+    }
+    out.trim_end().to_string() + "\n"
+}
+
+fn same_section(name: &str, a: &str, b: &str) -> bool {
+    if name == "actions" {
+        /* key order and layout are irrelevant */
+        if let (Ok(a),Ok(b)) = (serde_json::from_str::<serde_json::Value>(a),serde_json::from_str::<serde_json::Value>(b)) {
+            return a == b;
+        }
+    }
+    a == b
+}
+
+/* Lines only in expected are marked -, lines only in actual +. */
+fn diff(expected: &str, actual: &str) -> String {
+    let a = expected.lines().collect::<Vec<_>>();
+    let b = actual.lines().collect::<Vec<_>>();
+    let mut lcs = vec![vec![0;b.len()+1];a.len()+1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] { lcs[i+1][j+1]+1 } else { lcs[i+1][j].max(lcs[i][j+1]) };
+        }
+    }
+    let mut out = vec![];
+    let (mut i, mut j) = (0,0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            out.push(format!("  {}",a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i+1][j] >= lcs[i][j+1]) {
+            out.push(format!("- {}",a[i]));
+            i += 1;
+        } else {
+            out.push(format!("+ {}",b[j]));
+            j += 1;
+        }
+    }
+    out.join("\n")
+}
+
+fn sibling(source: &Path, suffix: &str) -> PathBuf {
+    source.with_extension(suffix)
+}
+
+fn get_responses(source: &Path) -> Result<StubResponses,String> {
+    let path = sibling(source,RESPONSES_SUFFIX);
+    if !path.exists() { return Ok(StubResponses::empty()); }
+    let input = fs::read(&path).map_err(|e| format!("cannot read {}: {}",path.display(),e))?;
+    serde_json::from_slice(&input).map_err(|e| format!("cannot read responses {}: {}",path.display(),e))
+}
+
+struct GoldenRunner {
+    options: CompileOptions
+}
+
+impl GoldenRunner {
+    fn new(options: &CompileOptions) -> GoldenRunner {
+        GoldenRunner { options: options.clone() }
+    }
+
+    /* Compile failures are errors in the test itself, but run failures are part of the output. */
+    fn run(&self, source: &Path) -> Result<BTreeMap<String,String>,String> {
+        let contents = load_binary(&source.to_string_lossy(),&self.options)?;
+        let (mut interp,libraries) = make_interpreter(false)?;
+        interp.load(&contents)?;
+        let program = interp.list_programs().first().cloned().ok_or_else(|| format!("File contained no programs!"))?;
+        let block = guess_block(&interp,&program)?;
+        let printed = LibcoreCapture(Arc::new(Mutex::new(vec![])));
+        let mut context = RunContext::new();
+        let stubdump = libraries.prepare(&mut context,printed.clone(),get_responses(source)?)?;
+        let result = block_on(interp.get(&program,&block)?.run(context));
+        let mut out = BTreeMap::new();
+        let printed = printed.0.lock().unwrap().join("\n");
+        if printed.len() > 0 {
+            out.insert("printed".to_string(),printed);
+        }
+        if stubdump.used() {
+            let actions = serde_json::to_string_pretty(&stubdump).map_err(|e| format!("cannot serialise actions: {}",e))?;
+            out.insert("actions".to_string(),unindent(&actions,10).trim().to_string());
+        }
+        if let Err(e) = result {
+            /* locations shouldn't depend on where we were run from */
+            let e = match source.parent().filter(|x| !x.as_os_str().is_empty()) {
+                Some(dir) => e.replace(&format!("{}/",dir.display()),""),
+                None => e
+            };
+            out.insert("error".to_string(),e);
+        }
+        Ok(out)
+    }
+
+    /* true if passed (or blessed) */
+    fn test(&self, source: &Path, bless: bool) -> Result<bool,String> {
+        let name = source.file_stem().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+        let actual = self.run(source)?;
+        let golden = sibling(source,EXPECTED_SUFFIX);
+        let expected = if golden.exists() {
+            Some(parse_sections(&fs::read_to_string(&golden).map_err(|e| format!("cannot read {}: {}",golden.display(),e))?))
+        } else {
+            None
+        };
+        let mut failures = vec![];
+        if let Some(expected) = &expected {
+            let names = expected.keys().chain(actual.keys()).collect::<std::collections::BTreeSet<_>>();
+            for section in names {
+                let want = expected.get(section).map(|x| x.as_str()).unwrap_or("");
+                let got = actual.get(section).map(|x| x.as_str()).unwrap_or("");
+                if !same_section(section,want,got) {
+                    failures.push(format!(">> {}\n{}",section,diff(want,got)));
+                }
+            }
+        }
+        if expected.is_some() && failures.len() == 0 {
+            println!("ok      {}",name);
+            return Ok(true);
+        }
+        if bless {
+            fs::write(&golden,render_sections(&actual)).map_err(|e| format!("cannot write {}: {}",golden.display(),e))?;
+            println!("blessed {}",name);
+            return Ok(true);
+        }
+        if expected.is_none() {
+            println!("FAILED  {}: no {} file, run with --bless to create it",name,golden.display());
+        } else {
+            println!("FAILED  {}\n{}",name,failures.join("\n"));
+        }
+        Ok(false)
+    }
+}
+
+/* Returns whether all tests passed. */
+pub(crate) fn run_golden_tests(dir: &str, bless: bool, options: &CompileOptions) -> Result<bool,String> {
+    let runner = GoldenRunner::new(options);
+    let mut sources = fs::read_dir(dir).map_err(|e| format!("cannot read {}: {}",dir,e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map(|x| x == "eard").unwrap_or(false))
+        .collect::<Vec<_>>();
+    sources.sort();
+    let (mut passed, mut failed) = (0,0);
+    for source in &sources {
+        let ok = match runner.test(source,bless) {
+            Ok(ok) => ok,
+            Err(e) => {
+                println!("FAILED  {}\n{}",source.display(),e);
+                false
+            }
+        };
+        if ok { passed += 1; } else { failed += 1; }
+    }
+    println!("{} passed, {} failed",passed,failed);
+    Ok(failed == 0)
+}
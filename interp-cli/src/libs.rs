@@ -0,0 +1,105 @@
+use std::fs;
+use clap::Args;
+use eard_compiler_lib::{EardCompiler, EardCompilation, EardSerializeCode};
+use eard_compiler_libeoe::libeoe_add;
+use eard_compiler_libperegrine::libperegrine_add;
+use eard_interp::{RunContext, LibcoreTemplate, build_libcore, InterpreterBuilder, Interpreter, prepare_libcore, LibcoreBuilder};
+use eard_libeoe::{build_libeoe, prepare_libeoe, LibEoEBuilder};
+use eard_libperegrine_stub::{build_libperegrine, prepare_libperegrine, LibPeregrineBuilder, StubResponses, StubDump};
+
+/* Registration of the libraries, kept in one place so that the compiler and interpreter agree. */
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct CompileOptions {
+    /// Target bytecode version
+    #[arg(long)]
+    pub(crate) bytecode: Option<u32>,
+
+    /// Optimise
+    #[arg(short = 'O', long, default_value_t = false)]
+    pub(crate) optimise: bool,
+
+    /// Include source positions in output, so runtime errors can cite them
+    #[arg(short = 'g', long = "debug-info", default_value_t = false)]
+    pub(crate) debug: bool,
+
+    /// Verbose
+    #[arg(short = 'v', long, default_value_t = false)]
+    pub(crate) verbose: bool
+}
+
+impl CompileOptions {
+    pub(crate) fn make_compiler(&self) -> Result<EardCompiler,String> {
+        let mut compiler = EardCompiler::new()?;
+        libperegrine_add(&mut compiler)?;
+        libeoe_add(&mut compiler)?;
+        if self.optimise {
+            compiler.set_optimise(true);
+        }
+        if let Some(v) = self.bytecode {
+            compiler.set_target_version(v);
+        }
+        if self.verbose {
+            compiler.set_verbose(true);
+        }
+        Ok(compiler)
+    }
+
+    pub(crate) fn compile(&self, sources: &[String]) -> Result<EardSerializeCode,String> {
+        let compiler = self.make_compiler()?;
+        let mut output = EardSerializeCode::new();
+        output.set_debug(self.debug);
+        for src in sources {
+            let mut compilation = EardCompilation::new(&compiler)?;
+            let code = compilation.compile(src).map_err(|e| e.render())?;
+            output.add(code);
+        }
+        Ok(output)
+    }
+}
+
+/* Source files are compiled in memory (always with debug info, so that errors cite the source),
+ * anything else is assumed to be compiled already.
+ */
+pub(crate) fn load_binary(filename: &str, options: &CompileOptions) -> Result<Vec<u8>,String> {
+    if filename.ends_with(".eard") {
+        let mut code = options.compile(&[filename.to_string()])?;
+        code.set_debug(true);
+        code.serialize()
+    } else {
+        fs::read(filename).map_err(|e| format!("cannot read {}: {}",filename,e))
+    }
+}
+
+pub(crate) struct Libraries {
+    libcore: LibcoreBuilder,
+    libperegrine: LibPeregrineBuilder,
+    libeoe: LibEoEBuilder
+}
+
+impl Libraries {
+    pub(crate) fn build(builder: &mut InterpreterBuilder) -> Result<Libraries,String> {
+        Ok(Libraries {
+            libcore: build_libcore(builder)?,
+            libperegrine: build_libperegrine(builder)?,
+            libeoe: build_libeoe(builder)?
+        })
+    }
+
+    pub(crate) fn prepare<F>(&self, context: &mut RunContext, libcore_template: F, responses: StubResponses) -> Result<StubDump,String>
+            where F: LibcoreTemplate + 'static {
+        prepare_libcore(context,&self.libcore,libcore_template);
+        let stubdump = prepare_libperegrine(context,&self.libperegrine,responses)?;
+        prepare_libeoe(context,&self.libeoe)?;
+        Ok(stubdump)
+    }
+}
+
+pub(crate) fn make_interpreter(step_by_step: bool) -> Result<(Interpreter,Libraries),String> {
+    let mut builder = InterpreterBuilder::new();
+    if step_by_step {
+        builder.set_step_by_step(true);
+    }
+    let libraries = Libraries::build(&mut builder)?;
+    Ok((Interpreter::new(builder),libraries))
+}
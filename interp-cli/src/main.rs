@@ -1,26 +1,29 @@
-use std::{process::exit, pin::Pin, future::Future, fs::{self}};
+use std::{process::exit, pin::Pin, future::Future, fs::{self, File}, io::{self, Write}};
 use async_std::task::block_on;
-use clap::{Parser};
-use eard_interp::{RunContext, LibcoreTemplate, build_libcore, InterpreterBuilder, Interpreter, prepare_libcore, ProgramName};
-use eard_libeoe::{build_libeoe, prepare_libeoe};
-use eard_libperegrine_stub::{build_libperegrine, prepare_libperegrine, StubResponses};
+use clap::{Args, Parser, Subcommand};
+use eard_compiler_lib::{EardCompilation, OutputFormat, format_files};
+use eard_interp::{RunContext, LibcoreTemplate, Interpreter, ProgramName, Profile};
+use eard_libperegrine_stub::StubResponses;
+use crate::debugger::Debugger;
+use crate::golden::run_golden_tests;
+use crate::libs::{CompileOptions, load_binary, make_interpreter};
 
-#[derive(Parser, Debug)]
-#[command(name = "eard cli interpreter")]
-#[command(author = "Ensembl Webteam <ensembl-webteam@ebi.ac.uk>")]
-#[command(version = "0.0")]
-#[command(about = "Command line tool to run cli-only eard binaries", long_about = None)]
-pub(crate) struct Config {
+mod debugger;
+mod golden;
+mod libs;
+
+#[derive(Args, Debug)]
+pub(crate) struct RunOptions {
     /// Block to run
     #[arg(short = 'b', long)]
     pub(crate) block: Option<String>,
 
     /// Program to run
     #[arg(short = 'p', long)]
-    pub(crate) program: Option<String>,    
+    pub(crate) program: Option<String>,
 
-    /// Source files to run
-    pub(crate) source: String,
+    /// Source (.eard) or compiled file to run
+    pub(crate) source: Option<String>,
 
     /// Response input file (if any)
     #[arg(short = 'r', long)]
@@ -28,10 +31,103 @@ pub(crate) struct Config {
 
     /// Step-by-step run with debugging info at each stage (for deep debugging)
     #[arg(short = 's', long = "step")]
-    pub(crate) step_by_step: bool
+    pub(crate) step_by_step: bool,
+
+    /// Run under an interactive debugger, with commands read from stdin
+    #[arg(short = 'd', long = "debug")]
+    pub(crate) debugger: bool,
+
+    /// Report time taken and sizes of output, by opcode, source line and step
+    #[arg(long)]
+    pub(crate) profile: bool,
 
+    /// Also write the profile as JSON to this file
+    #[arg(long)]
+    pub(crate) profile_json: Option<String>,
+
+    #[command(flatten)]
+    pub(crate) compile: CompileOptions
 }
 
+#[derive(Subcommand, Debug)]
+pub(crate) enum Command {
+    /// Compile source files into an eard binary
+    Compile {
+        /// Source files to compile
+        #[arg(required = true)]
+        source: Vec<String>,
+
+        /// Output filename
+        #[arg(short, long, default_value = "out.eardo")]
+        outfile: String,
+
+        /// Format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Standard)]
+        format: OutputFormat,
+
+        #[command(flatten)]
+        options: CompileOptions
+    },
+    /// Run a program, compiling it first if given a source file
+    Run(RunOptions),
+    /// Check that source files compile, including type-checking, without writing anything
+    Check {
+        /// Source files to check
+        #[arg(required = true)]
+        source: Vec<String>,
+
+        #[command(flatten)]
+        options: CompileOptions
+    },
+    /// List the contents of a compiled (or source) file
+    Disasm {
+        /// File to list
+        source: String,
+
+        #[command(flatten)]
+        options: CompileOptions
+    },
+    /// Reformat source files in place
+    Fmt {
+        /// Report files which aren't formatted, rather than changing them
+        #[arg(long, default_value_t = false)]
+        check: bool,
+
+        /// Source files to format
+        #[arg(required = true)]
+        files: Vec<String>
+    },
+    /// Compile and run each .eard file in a directory, comparing output against golden files
+    Test {
+        /// Directory containing sources, with optional NAME.responses.json and golden NAME.expected
+        dir: String,
+
+        /// Write the actual output to the golden files rather than comparing
+        #[arg(long)]
+        bless: bool,
+
+        #[command(flatten)]
+        options: CompileOptions
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "eard")]
+#[command(author = "Ensembl Webteam <ensembl-webteam@ebi.ac.uk>")]
+#[command(version = "0.0")]
+#[command(about = "Compile, check and run eard programs", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct Config {
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+
+    /* with no subcommand, as "run" */
+    #[command(flatten)]
+    pub(crate) run: RunOptions
+}
+
+const PROFILE_ROWS : usize = 20;
+
 async fn call_up_async() -> Result<(),String> {
     Ok(())
 }
@@ -50,7 +146,7 @@ impl LibcoreTemplate for LibcoreCli {
 
 fn guess_block(interp: &Interpreter, program: &ProgramName) -> Result<String,String> {
     let blocks = interp.list_blocks(&program);
-    if blocks.contains(&"main".to_string()) { 
+    if blocks.contains(&"main".to_string()) {
         Ok("main".to_string())
     } else if let Some(b) = blocks.first() {
         Ok(b.to_string())
@@ -83,7 +179,7 @@ fn unindent(data: &str, offset: usize) -> String {
     out
 }
 
-fn get_responses(config: &Config) -> Result<StubResponses,String> {
+fn get_responses(config: &RunOptions) -> Result<StubResponses,String> {
     if let Some(responses) = &config.responses {
         let input = fs::read(responses).map_err(|e| format!("cannot read response: {}",e))?;
         Ok(serde_json::from_slice(&input).map_err(|e| format!("cannot read responses: {}",e))?)
@@ -92,25 +188,51 @@ fn get_responses(config: &Config) -> Result<StubResponses,String> {
     }
 }
 
-fn do_it(config: &Config) -> Result<(),String> {
+fn do_compile(sources: &[String], outfile: &str, format: &OutputFormat, options: &CompileOptions) -> Result<(),String> {
+    let binary = options.compile(sources)?.output(*format)?;
+    if outfile == "-" {
+        let mut f = io::stdout().lock();
+        f.write_all(&binary).map_err(|e| format!("cannot write file: {}",e))?;
+    } else {
+        let mut f = File::create(outfile).map_err(|e| format!("cannot write file: {}",e))?;
+        f.write_all(&binary).map_err(|e| format!("cannot write file: {}",e))?;
+    }
+    Ok(())
+}
+
+/* returns whether all files passed */
+fn do_check(sources: &[String], options: &CompileOptions) -> Result<bool,String> {
+    let compiler = options.make_compiler()?;
+    let mut clean = true;
+    for src in sources {
+        let mut compilation = EardCompilation::new(&compiler)?;
+        if let Err(e) = compilation.check(src) {
+            eprintln!("{}",e.render());
+            clean = false;
+        }
+    }
+    Ok(clean)
+}
+
+fn do_disasm(source: &str, options: &CompileOptions) -> Result<(),String> {
+    let (interp,_) = make_interpreter(false)?;
+    let contents = load_binary(source,options)?;
+    print!("{}",interp.disassemble(&contents)?);
+    Ok(())
+}
+
+fn do_run(config: &RunOptions) -> Result<(),String> {
+    let source = config.source.as_ref().ok_or_else(|| format!("no source file given"))?;
     eprintln!("running {} ; program {} ; block {}",
-        config.source,
+        source,
         config.program.as_ref().map(|x| x.as_str()).unwrap_or("*any*"),
         config.block.as_ref().map(|x| x.as_str()).unwrap_or("*any*")
     );
     /* prepare an interpreter */
-    let libcore_context = LibcoreCli;
-    let mut builder = InterpreterBuilder::new();
-    if config.step_by_step {
-        builder.set_step_by_step(true);
-    }
-    let libcore_builder = build_libcore(&mut builder)?;
-    let libperegrine_builder = build_libperegrine(&mut builder)?;
-    let libeoe_builder = build_libeoe(&mut builder)?;
-    let mut interp = Interpreter::new(builder);
-    /* read the source */
-    let contents = fs::read(&config.source).map_err(|e| format!("cannot read {}: {}",config.source,e))?;
-    /* add the source */
+    let profile = config.profile || config.profile_json.is_some();
+    let (mut interp,libraries) = make_interpreter(config.step_by_step || config.debugger || profile)?;
+    /* read the source, compiling if necessary, and add it */
+    let contents = load_binary(source,&config.compile)?;
     interp.load(&contents)?;
     /* find the program */
     let programs = interp.list_programs();
@@ -132,19 +254,56 @@ fn do_it(config: &Config) -> Result<(),String> {
         .unwrap_or_else(|| { guess_block(&interp,&program) })?;
     /* prepare a run */
     let mut context = RunContext::new();
-    prepare_libcore(&mut context,&libcore_builder,libcore_context);
-    let responses = get_responses(config)?;
-    let stubdump = prepare_libperegrine(&mut context,&libperegrine_builder,responses)?;
-    prepare_libeoe(&mut context,&libeoe_builder)?;
+    let stubdump = libraries.prepare(&mut context,LibcoreCli,get_responses(config)?)?;
     /* run */
     let program = interp.get(&program,&block)?;
-    block_on(program.run(context))?;
+    if config.debugger {
+        block_on(program.run_debug(context,&mut Debugger::new(io::stdin().lock(),io::stderr())))?;
+    } else if profile {
+        let mut profile = Profile::new();
+        let result = block_on(program.run_profile(context,&mut profile));
+        eprint!("{}",profile.report(PROFILE_ROWS));
+        if let Some(path) = &config.profile_json {
+            let json = serde_json::to_string_pretty(&profile.to_json()).map_err(|e| format!("cannot serialise profile: {}",e))?;
+            fs::write(path,json).map_err(|e| format!("cannot write {}: {}",path,e))?;
+        }
+        result?;
+    } else {
+        block_on(program.run(context))?;
+    }
     if stubdump.used() {
         println!("{}",unindent(&serde_json::to_string_pretty(&stubdump).ok().unwrap(),10));
     }
     Ok(())
 }
 
+fn do_it(config: &Config) -> Result<(),String> {
+    match &config.command {
+        Some(Command::Compile { source, outfile, format, options }) => do_compile(source,outfile,format,options),
+        Some(Command::Run(run)) => do_run(run),
+        Some(Command::Check { source, options }) => {
+            if !do_check(source,options)? {
+                exit(1);
+            }
+            Ok(())
+        },
+        Some(Command::Disasm { source, options }) => do_disasm(source,options),
+        Some(Command::Fmt { check, files }) => {
+            if !format_files(files,*check)? {
+                exit(1);
+            }
+            Ok(())
+        },
+        Some(Command::Test { dir, bless, options }) => {
+            if !run_golden_tests(dir,*bless,options)? {
+                exit(1);
+            }
+            Ok(())
+        },
+        None => do_run(&config.run)
+    }
+}
+
 pub fn main() {
     match do_it(&Config::parse()) {
         Ok(()) => { exit(0); }
@@ -152,5 +311,10 @@ pub fn main() {
             eprintln!("{}",e);
             exit(1);
         }
-    } 
+    }
+}
+
+#[cfg(test)]
+mod test {
+    mod test;
 }
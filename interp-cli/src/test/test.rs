@@ -0,0 +1,138 @@
+use std::{fs, path::PathBuf, sync::{Arc, Mutex}};
+use async_std::task::block_on;
+use eard_compiler_lib::OutputFormat;
+use eard_interp::RunContext;
+use eard_libperegrine_stub::StubResponses;
+use crate::{do_check, do_compile, guess_block, debugger::Debugger, golden::{run_golden_tests, LibcoreCapture}, libs::{CompileOptions, load_binary, make_interpreter}};
+
+const FIXTURES : &str = concat!(env!("CARGO_MANIFEST_DIR"),"/src/test/golden");
+
+fn options() -> CompileOptions {
+    CompileOptions { bytecode: None, optimise: false, debug: false, verbose: false }
+}
+
+/* Copy of the given fixtures somewhere we can write to. */
+fn scratch(name: &str, stems: &[&str]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("eard-golden-{}-{}",std::process::id(),name));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("cannot create scratch dir");
+    for stem in stems {
+        for suffix in &["eard","expected","responses.json"] {
+            let file = format!("{}.{}",stem,suffix);
+            let source = PathBuf::from(FIXTURES).join(&file);
+            if source.exists() {
+                fs::copy(source,dir.join(&file)).expect("cannot copy fixture");
+            }
+        }
+    }
+    dir
+}
+
+#[test]
+fn test_golden_compare() {
+    let dir = scratch("pass",&["pass"]);
+    assert!(run_golden_tests(&dir.to_string_lossy(),false,&options()).expect("golden run failed"));
+    assert!(!run_golden_tests(FIXTURES,false,&options()).expect("golden run failed"));
+    /* without --bless nothing is written */
+    assert_eq!(">> printed\n\nhello\n",fs::read_to_string(PathBuf::from(FIXTURES).join("fail.expected")).expect("cannot read"));
+}
+
+#[test]
+fn test_golden_bless() {
+    let dir = scratch("bless",&["pass","fail"]);
+    let dir_name = dir.to_string_lossy().to_string();
+    assert!(!run_golden_tests(&dir_name,false,&options()).expect("golden run failed"));
+    assert!(run_golden_tests(&dir_name,true,&options()).expect("golden run failed"));
+    assert_eq!(">> printed\n\ngoodbye\n",fs::read_to_string(dir.join("fail.expected")).expect("cannot read"));
+    assert_eq!(">> printed\n\nhello\n[1,2,3]\n",fs::read_to_string(dir.join("pass.expected")).expect("cannot read"));
+    assert!(run_golden_tests(&dir_name,false,&options()).expect("golden run failed"));
+}
+
+/* responses feed the program and what it asked for ends up in the actions section */
+#[test]
+fn test_golden_stub() {
+    let dir = scratch("stub",&["stub"]);
+    let dir_name = dir.to_string_lossy().to_string();
+    assert!(run_golden_tests(&dir_name,false,&options()).expect("golden run failed"));
+    /* actions are compared as json, so layout doesn't matter but content does */
+    let expected = fs::read_to_string(dir.join("stub.expected")).expect("cannot read");
+    fs::write(dir.join("stub.expected"),expected.replace("\"ENSG1\"","\"ENSG2\"")).expect("cannot write");
+    assert!(!run_golden_tests(&dir_name,false,&options()).expect("golden run failed"));
+    /* without responses the request fails */
+    fs::remove_file(dir.join("stub.responses.json")).expect("cannot remove");
+    assert!(run_golden_tests(&dir_name,true,&options()).expect("golden run failed"));
+    let blessed = fs::read_to_string(dir.join("stub.expected")).expect("cannot read");
+    assert!(blessed.contains(">> error\n\nNo stubbed response data for self() gene"));
+}
+
+/* check must fail exactly when compile would */
+#[test]
+fn test_check_matches_compile() {
+    let dir = std::env::temp_dir().join(format!("eard-check-{}",std::process::id()));
+    fs::create_dir_all(&dir).expect("cannot create scratch dir");
+    let outfile = dir.join("out.eardo").to_string_lossy().to_string();
+    for (name,src,ok) in [
+        ("good","program \"test\" \"test\" 1;\nprint(\"hello\");\n",true),
+        ("bad","program \"test\" \"test\" 1;\nlet x = 1;\nprint(x+\"a\");\n",false)
+    ] {
+        let path = dir.join(format!("{}.eard",name));
+        fs::write(&path,src).expect("cannot write source");
+        let sources = vec![path.to_string_lossy().to_string()];
+        assert_eq!(ok,do_check(&sources,&options()).expect("check did not run"));
+        assert_eq!(ok,do_compile(&sources,&outfile,&OutputFormat::Standard,&options()).is_ok());
+    }
+}
+
+const DEBUG_SOURCE : &str = "program \"test\" \"debug\" 1;\n\nprint(\"a\");\nprint(\"b\");\nprint(\"c\");\n";
+
+/* Runs DEBUG_SOURCE under the debugger with the given commands, returning what the debugger
+ * said and what the program printed. Each print is a constant load into r1 then a print.
+ */
+fn debug_script(name: &str, script: &str) -> (String,Vec<String>) {
+    let dir = std::env::temp_dir().join(format!("eard-debug-{}-{}",std::process::id(),name));
+    fs::create_dir_all(&dir).expect("cannot create scratch dir");
+    let path = dir.join("debug.eard");
+    fs::write(&path,DEBUG_SOURCE).expect("cannot write source");
+    let contents = load_binary(&path.to_string_lossy(),&options()).expect("cannot compile");
+    let (mut interp,libraries) = make_interpreter(true).expect("cannot build interpreter");
+    interp.load(&contents).expect("cannot load");
+    let program = interp.list_programs().first().cloned().expect("no program");
+    let block = guess_block(&interp,&program).expect("no block");
+    let printed = LibcoreCapture(Arc::new(Mutex::new(vec![])));
+    let mut context = RunContext::new();
+    libraries.prepare(&mut context,printed.clone(),StubResponses::empty()).expect("cannot prepare");
+    let mut output = vec![];
+    let mut debugger = Debugger::new(script.as_bytes(),&mut output);
+    block_on(interp.get(&program,&block).expect("cannot get program").run_debug(context,&mut debugger)).expect("run failed");
+    let printed = printed.0.lock().unwrap().clone();
+    (String::from_utf8(output).expect("bad output"),printed)
+}
+
+#[test]
+fn test_debugger_breakpoint() {
+    let (output,printed) = debug_script("break","b 3\nc\nq\n");
+    assert!(output.contains("step 0: opcode 0 "));
+    assert!(output.contains("  break step 3\n"));
+    assert!(output.contains("breakpoint step 3\nstep 3: opcode 137 "));
+    assert!(!output.contains("step 1:"));
+    /* quit before the second print */
+    assert_eq!(vec!["a".to_string()],printed);
+}
+
+#[test]
+fn test_debugger_continue() {
+    let (output,printed) = debug_script("continue","c\n");
+    assert!(!output.contains("step 1:"));
+    assert_eq!(vec!["a".to_string(),"b".to_string(),"c".to_string()],printed);
+    /* out of commands stops the program */
+    let (_,printed) = debug_script("eof","s 2\n");
+    assert_eq!(vec!["a".to_string()],printed);
+}
+
+#[test]
+fn test_debugger_print() {
+    let (output,_) = debug_script("print","s 3\np r1\np\np x\nq\n");
+    assert!(output.contains("step 3: opcode 137 "));
+    assert_eq!(2,output.matches("  r1 = String(\"b\")\n").count());
+    assert!(output.contains("bad register x\n"));
+}
@@ -0,0 +1,38 @@
+use super::{globalcontext::GlobalContext, objectcode::SourceLocation, value::Value};
+
+/* "a:3 (called from b:10)" */
+pub(crate) fn describe_locations(locations: &[SourceLocation]) -> Option<String> {
+    let mut locations = locations.iter();
+    let mut out = locations.next()?.to_string();
+    for caller in locations {
+        out.push_str(&format!(" (called from {})",caller));
+    }
+    Some(out)
+}
+
+/* What a debugger gets to see of the program around each step. */
+pub struct StepView<'a> {
+    pub index: usize,
+    pub opcode: usize,
+    pub name: Option<&'a str>,
+    pub registers: &'a [usize],
+    pub locations: &'a [SourceLocation],
+    pub(crate) gctx: &'a GlobalContext
+}
+
+impl<'a> StepView<'a> {
+    pub fn value(&self, reg: usize) -> Result<&Value,String> {
+        self.gctx.get(reg)
+    }
+
+    pub fn location(&self) -> Option<String> {
+        describe_locations(self.locations)
+    }
+}
+
+pub trait DebugHook {
+    /* called before each step is run: return false to stop the program */
+    fn before(&mut self, view: &StepView) -> bool;
+
+    fn after(&mut self, _view: &StepView) {}
+}
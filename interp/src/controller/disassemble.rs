@@ -0,0 +1,49 @@
+use super::{objectcode::{ObjectFile, CompiledCode, CompiledBlock}, program::ProgramStore};
+
+fn disassemble_block(out: &mut Vec<String>, name: &str, block: &CompiledBlock, store: &ProgramStore) {
+    out.push(format!("  block {}",name));
+    if !block.constants.is_empty() {
+        out.push("    constants".to_string());
+        for (i,value) in block.constants.iter().enumerate() {
+            out.push(format!("      c{:<4} {:?}",i,value));
+        }
+    }
+    out.push("    code".to_string());
+    for (i,(opcode,regs)) in block.program.iter().enumerate() {
+        let name = store.opcode_name(*opcode).unwrap_or("*unknown*");
+        let mut args = regs.iter().map(|r| format!("r{}",r)).collect::<Vec<_>>();
+        if store.loads_constant(*opcode) && args.len() > 1 {
+            /* const takes a constant index rather than a register as its second argument */
+            let value = block.constants.get(regs[1]).map(|v| format!(" ({:?})",v)).unwrap_or_default();
+            args[1] = format!("c{}{}",regs[1],value);
+        }
+        let mut line = format!("      {:<5} {:>4} {:<16} {}",i,opcode,name,args.join(" "));
+        if let Some(location) = block.debug.get(i).and_then(|x| x.first()) {
+            line = format!("{:<56} ; {}",line,location);
+        }
+        out.push(line);
+    }
+}
+
+fn disassemble_code(out: &mut Vec<String>, code: &CompiledCode, store: &ProgramStore) {
+    let name = &code.metadata.name;
+    out.push(format!("program {}:{}:{}",name.group,name.name,name.version));
+    let versions = code.metadata.version.versions().iter().map(|(lib,(major,minor))| {
+        format!("{} {}.{}",lib,major,minor)
+    }).collect::<Vec<_>>();
+    out.push(format!("  libraries {}",versions.join(", ")));
+    let mut blocks = code.code.iter().collect::<Vec<_>>();
+    blocks.sort_by_key(|(name,_)| name.to_string());
+    for (name,block) in blocks {
+        disassemble_block(out,name,block,store);
+    }
+}
+
+pub(crate) fn disassemble(file: &ObjectFile, store: &ProgramStore) -> String {
+    let mut out = vec![];
+    for code in &file.code {
+        disassemble_code(&mut out,code,store);
+    }
+    out.push(String::new());
+    out.join("\n")
+}
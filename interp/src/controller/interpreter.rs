@@ -1,5 +1,5 @@
 use crate::ProgramName;
-use super::{operation::{OperationStore, Operation}, context::{ContextTemplateBuilder, ContextItem}, globalcontext::GlobalBuildContext, program::{ProgramStore, Program}, objectcode::{ObjectFile, CompiledBlock, Metadata }, version::OpcodeVersion};
+use super::{disassemble::disassemble, operation::{OperationStore, Operation}, context::{ContextTemplateBuilder, ContextItem}, globalcontext::GlobalBuildContext, program::{ProgramStore, Program}, objectcode::{ObjectFile, CompiledBlock, Metadata }, version::OpcodeVersion};
 
 pub struct InterpreterBuilder {
     step_by_step: bool,
@@ -32,6 +32,11 @@ impl InterpreterBuilder {
         self.store.add(opcode,oper);
     }
 
+    /* As add_operation, but also names the operation for the disassembler. */
+    pub fn add_operation_named(&mut self, opcode: usize, name: &str, oper: Operation) {
+        self.store.add(opcode,oper.named(name));
+    }
+
     pub fn set_step_by_step(&mut self, yn: bool) {
         self.step_by_step = yn;
     }
@@ -68,6 +73,12 @@ impl Interpreter {
         Ok(())
     }
 
+    /* Listing of an object file, with opcodes named from the operations registered here. */
+    pub fn disassemble(&self, bytes: &[u8]) -> Result<String,String> {
+        let file = ObjectFile::decode(bytes.to_vec()).map_err(|e| format!("loading error: {}",e))?;
+        Ok(disassemble(&file,&self.store))
+    }
+
     pub(crate) fn add(&mut self, file: &ObjectFile) -> Result<(),String> {
         for code in &file.code {
             for (name,block) in &code.code {
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 use minicbor::{Decoder, Decode, decode::Error, data::Type};
 use super::{globalcontext::GlobalBuildContext, value::Value, program::{ProgramBuilder, Program}, version::OpcodeVersion};
 
@@ -7,11 +7,11 @@ pub(crate) fn cbor_map<'b,F,T>(d: &mut Decoder<'b>, obj: &mut T, mut cb: F) -> R
     let entries = d.map()?;
     let mut index = 0;
     loop {
+        if let Some(len) = entries { if len == index { break; } }
+        else if let Type::Break = d.datatype()? { d.skip()?; break; }
         let key = d.str()?;
         (cb)(key,obj,d)?;
         index += 1;
-        if let Some(len) = entries { if len == index { break; } }
-        if let Type::Break = d.datatype()? { d.skip()?; break; }
     }
     Ok(())
 }
@@ -21,10 +21,10 @@ pub(crate) fn cbor_array<'b,F,T>(d: &mut Decoder<'b>, obj: &mut T, mut cb: F) ->
     let entries = d.array()?;
     let mut index = 0;
     loop {
+        if let Some(len) = entries { if len == index { break; } }
+        else if let Type::Break = d.datatype()? { d.skip()?; break; }
         (cb)(index,obj,d)?;
         index += 1;
-        if let Some(len) = entries { if len == index { break; } }
-        if let Type::Break = d.datatype()? { d.skip()?; break; }
     }
     Ok(())           
 }
@@ -82,10 +82,38 @@ impl Metadata {
     }
 }
 
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct SourceLocation {
+    pub filename: String,
+    pub line: u32
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"{}:{}",self.filename,self.line)
+    }
+}
+
+impl<'b> Decode<'b,()> for SourceLocation {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, Error> {
+        let mut out = SourceLocation { filename: "".to_string(), line: 0 };
+        cbor_array(d, &mut out, |idx,out,d| {
+            match idx {
+                0 => { out.filename = d.str()?.to_string(); },
+                1 => { out.line = d.u32()?; },
+                _ => { d.skip()?; }
+            }
+            Ok(())
+        })?;
+        Ok(out)
+    }
+}
+
 #[derive(Debug,Clone)]
 pub(crate) struct CompiledBlock {
     pub(crate) constants: Vec<Value>,
-    pub(crate) program: Vec<(usize,Vec<usize>)>
+    pub(crate) program: Vec<(usize,Vec<usize>)>,
+    pub(crate) debug: Vec<Vec<SourceLocation>> // optional, position then callers for each step
 }
 
 impl CompiledBlock {
@@ -96,13 +124,14 @@ impl CompiledBlock {
         for (opcode,regs) in &self.program {
             program.add_opcode(gbctx,*opcode,regs.to_vec())?;
         }
+        program.set_locations(&self.debug);
         Ok(program.to_program())
     }
 }
 
 impl<'b> Decode<'b,()> for CompiledBlock {
     fn decode(d: &mut Decoder<'b>, ctx: &mut ()) -> Result<Self, Error> {
-        let mut out = CompiledBlock { constants: vec![], program: vec![] };
+        let mut out = CompiledBlock { constants: vec![], program: vec![], debug: vec![] };
         cbor_map(d,&mut out,|key,out,d| {
             if key == "constants" {
                 cbor_array(d,&mut out.constants,|_,out,d| {
@@ -122,6 +151,16 @@ impl<'b> Decode<'b,()> for CompiledBlock {
                     }
                     Ok(())
                 })?;
+            } else if key == "debug" {
+                cbor_array(d,&mut out.debug, |_,out,d| {
+                    let mut locations = vec![];
+                    cbor_array(d,&mut locations, |_,out,d| {
+                        out.push(SourceLocation::decode(d,ctx)?);
+                        Ok(())
+                    })?;
+                    out.push(locations);
+                    Ok(())
+                })?;
             } else {
                 d.skip()?;
             }
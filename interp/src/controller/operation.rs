@@ -1,6 +1,6 @@
 use std::{future::Future, pin::Pin, sync::{Arc, Mutex}};
 
-use super::globalcontext::{GlobalBuildContext, GlobalContext};
+use super::{globalcontext::{GlobalBuildContext, GlobalContext}, value::Value};
 
 pub struct AsyncReturnImpl<T> {
     async_part: Arc<Mutex<Pin<Box<dyn Future<Output = Result<T,String>>>>>>,
@@ -67,24 +67,57 @@ impl OperationStore {
     pub(crate) fn get(&self, opcode: usize) -> Result<&Operation,String> {
         self.opers.get(opcode).ok_or_else(|| format!("no such opcode {}",opcode))
     }
+
+    pub(crate) fn name(&self, opcode: usize) -> Option<&str> {
+        self.opers.get(opcode).and_then(|oper| oper.name.as_ref()).map(|x| x.as_str())
+    }
+
+    pub(crate) fn loads_constant(&self, opcode: usize) -> bool {
+        self.opers.get(opcode).map(|oper| oper.loads_constant).unwrap_or(false)
+    }
 }
 
 pub struct Operation {
-    callback: Box<dyn Fn(&GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext, &[usize]) -> Result<Return,String>>,String>>
+    callback: Box<dyn Fn(&GlobalBuildContext,&[Option<&Value>]) -> Result<Box<dyn Fn(&mut GlobalContext, &[usize]) -> Result<Return,String>>,String>>,
+    name: Option<String>,
+    loads_constant: bool
 }
 
 impl Operation {
     pub fn new<F>(callback: F) -> Operation
             where F: Fn(&GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext, &[usize]) -> Result<Return,String>>,String> + 'static {
-        Operation { callback: Box::new(callback) }
+        Operation { callback: Box::new(move |gbctx,_| callback(gbctx)), name: None, loads_constant: false }
+    }
+
+    /* For operations which can do some work up front when arguments are constant. The callback
+     * gets, for each register of the step, its value if known to be a constant when loading.
+     */
+    pub fn new_with_constants<F>(callback: F) -> Operation
+            where F: Fn(&GlobalBuildContext,&[Option<&Value>]) -> Result<Box<dyn Fn(&mut GlobalContext, &[usize]) -> Result<Return,String>>,String> + 'static {
+        Operation { callback: Box::new(callback), name: None, loads_constant: false }
+    }
+
+    /* Marks the operation which copies constant r2 into register r1. Other operations are then
+     * told when an argument is such a constant, and the disassembler shows its value.
+     */
+    pub fn loads_constant(mut self) -> Operation {
+        self.loads_constant = true;
+        self
+    }
+
+    pub(crate) fn named(mut self, name: &str) -> Operation {
+        self.name = Some(name.to_string());
+        self
     }
 
     pub fn nop() -> Operation {
         Operation::new(|_| Ok(Box::new(|_,_| Ok(Return::Sync))))
     }
 
-    fn make(&self, gbctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext, &[usize]) -> Result<Return,String>>,String> {
-        (self.callback)(gbctx)
+    pub fn name(&self) -> Option<&str> { self.name.as_deref() }
+
+    fn make(&self, gbctx: &GlobalBuildContext, constants: &[Option<&Value>]) -> Result<Box<dyn Fn(&mut GlobalContext, &[usize]) -> Result<Return,String>>,String> {
+        (self.callback)(gbctx,constants)
     }
 }
 
@@ -94,8 +127,8 @@ pub(crate) struct Step {
 }
 
 impl Step {
-    pub(crate) fn new(gbctx: &GlobalBuildContext, operation: &Operation, registers: Vec<usize>) -> Result<Step,String> {
-        Ok(Step { callback: operation.make(gbctx)?, registers })
+    pub(crate) fn new(gbctx: &GlobalBuildContext, operation: &Operation, registers: Vec<usize>, constants: &[Option<&Value>]) -> Result<Step,String> {
+        Ok(Step { callback: operation.make(gbctx,constants)?, registers })
     }
 
     pub(crate) async fn run(&self, gctx: &mut GlobalContext) -> Result<(),Option<String>> {
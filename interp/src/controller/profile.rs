@@ -0,0 +1,180 @@
+use std::{cmp::Reverse, collections::BTreeMap, time::{Duration, Instant}};
+use serde_json::{json, Value as JsonValue};
+use super::debugger::{DebugHook, StepView};
+
+#[derive(Clone,Debug,Default)]
+pub struct ProfileEntry {
+    pub calls: u64,
+    pub time: Duration,
+    pub output_size: u64, // total elements written to the first register
+    pub max_output_size: usize
+}
+
+impl ProfileEntry {
+    fn add(&mut self, time: Duration, size: usize) {
+        self.calls += 1;
+        self.time += time;
+        self.output_size += size as u64;
+        self.max_output_size = self.max_output_size.max(size);
+    }
+
+    fn merge(&mut self, other: &ProfileEntry) {
+        self.calls += other.calls;
+        self.time += other.time;
+        self.output_size += other.output_size;
+        self.max_output_size = self.max_output_size.max(other.max_output_size);
+    }
+
+    fn to_json(&self) -> JsonValue {
+        json!({
+            "calls": self.calls,
+            "time_us": self.time.as_micros() as u64,
+            "output_size": self.output_size,
+            "max_output_size": self.max_output_size
+        })
+    }
+
+    fn report_line(&self, what: &str) -> String {
+        format!("  {:>10.3} {:>8} {:>10} {:>8}  {}",
+            self.time.as_secs_f64()*1000.,self.calls,self.output_size,self.max_output_size,what)
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct StepProfile {
+    pub index: usize,
+    pub opcode: usize,
+    pub name: Option<String>,
+    pub locations: Vec<String>, // position then callers, if there's debug info
+    pub entry: ProfileEntry
+}
+
+impl StepProfile {
+    fn describe(&self) -> String {
+        let mut out = format!("step {} opcode {} {}",self.index,self.opcode,self.name.as_deref().unwrap_or("*unknown*"));
+        if let Some(location) = self.locations.first() {
+            out.push_str(&format!(" ; {}",location));
+        }
+        out
+    }
+}
+
+/* Accumulates over one or more runs of a single program. */
+#[derive(Default)]
+pub struct Profile {
+    steps: Vec<Option<StepProfile>>,
+    started: Option<Instant>
+}
+
+fn sorted<K>(map: BTreeMap<K,ProfileEntry>) -> Vec<(K,ProfileEntry)> {
+    let mut out = map.into_iter().collect::<Vec<_>>();
+    out.sort_by_key(|a| Reverse(a.1.time));
+    out
+}
+
+impl Profile {
+    pub fn new() -> Profile {
+        Profile { steps: vec![], started: None }
+    }
+
+    pub fn total(&self) -> ProfileEntry {
+        let mut out = ProfileEntry::default();
+        for step in self.steps.iter().flatten() {
+            out.merge(&step.entry);
+        }
+        out
+    }
+
+    /* slowest first */
+    pub fn steps(&self) -> Vec<&StepProfile> {
+        let mut out = self.steps.iter().flatten().collect::<Vec<_>>();
+        out.sort_by_key(|a| Reverse(a.entry.time));
+        out
+    }
+
+    pub fn opcodes(&self) -> Vec<((usize,Option<String>),ProfileEntry)> {
+        let mut out = BTreeMap::new();
+        for step in self.steps.iter().flatten() {
+            let entry = out.entry((step.opcode,step.name.clone())).or_insert_with(ProfileEntry::default);
+            entry.merge(&step.entry);
+        }
+        sorted(out)
+    }
+
+    /* A step counts towards its own line and the lines of all its callers. */
+    pub fn lines(&self) -> Vec<(String,ProfileEntry)> {
+        let mut out = BTreeMap::new();
+        for step in self.steps.iter().flatten() {
+            let mut seen = vec![];
+            for location in &step.locations {
+                if seen.contains(&location) { continue; }
+                seen.push(location);
+                out.entry(location.to_string()).or_insert_with(ProfileEntry::default).merge(&step.entry);
+            }
+        }
+        sorted(out)
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let opcodes = self.opcodes().iter().map(|((opcode,name),entry)| {
+            json!({ "opcode": opcode, "name": name, "profile": entry.to_json() })
+        }).collect::<Vec<_>>();
+        let lines = self.lines().iter().map(|(location,entry)| {
+            json!({ "location": location, "profile": entry.to_json() })
+        }).collect::<Vec<_>>();
+        let steps = self.steps.iter().flatten().map(|step| {
+            json!({
+                "step": step.index, "opcode": step.opcode, "name": step.name,
+                "locations": step.locations, "profile": step.entry.to_json()
+            })
+        }).collect::<Vec<_>>();
+        json!({ "total": self.total().to_json(), "opcodes": opcodes, "lines": lines, "steps": steps })
+    }
+
+    /* Human-readable summary, showing at most limit rows in each table. */
+    pub fn report(&self, limit: usize) -> String {
+        let total = self.total();
+        let header = format!("  {:>10} {:>8} {:>10} {:>8}","time (ms)","calls","output","largest");
+        let mut out = vec![format!("{} steps run, {:.3}ms",total.calls,total.time.as_secs_f64()*1000.)];
+        out.push(format!("by opcode\n{}",header));
+        for ((opcode,name),entry) in self.opcodes().iter().take(limit) {
+            out.push(entry.report_line(&format!("{} {}",opcode,name.as_deref().unwrap_or("*unknown*"))));
+        }
+        let lines = self.lines();
+        if !lines.is_empty() {
+            out.push(format!("by source line, including callees\n{}",header));
+            for (location,entry) in lines.iter().take(limit) {
+                out.push(entry.report_line(location));
+            }
+        }
+        out.push(format!("by step\n{}",header));
+        for step in self.steps().iter().take(limit) {
+            out.push(step.entry.report_line(&step.describe()));
+        }
+        out.push(String::new());
+        out.join("\n")
+    }
+}
+
+impl DebugHook for Profile {
+    fn before(&mut self, _view: &StepView) -> bool {
+        self.started = Some(Instant::now());
+        true
+    }
+
+    fn after(&mut self, view: &StepView) {
+        let time = self.started.take().map(|s| s.elapsed()).unwrap_or_default();
+        let size = view.registers.first().and_then(|r| view.value(*r).ok()).map(|v| v.size()).unwrap_or(0);
+        if self.steps.len() <= view.index {
+            self.steps.resize(view.index+1,None);
+        }
+        let step = self.steps[view.index].get_or_insert_with(|| StepProfile {
+            index: view.index,
+            opcode: view.opcode,
+            name: view.name.map(|x| x.to_string()),
+            locations: view.locations.iter().map(|x| x.to_string()).collect(),
+            entry: ProfileEntry::default()
+        });
+        step.entry.add(time,size);
+    }
+}
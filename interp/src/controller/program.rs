@@ -1,5 +1,5 @@
 use std::{sync::Arc, mem, collections::HashMap};
-use super::{objectcode::ProgramName, operation::{OperationStore, Step}, context::RunContext, globalcontext::{GlobalContext, GlobalBuildContext}, value::Value};
+use super::{debugger::{describe_locations, DebugHook, StepView}, profile::Profile, objectcode::{ProgramName, SourceLocation}, operation::{OperationStore, Step}, context::RunContext, globalcontext::{GlobalContext, GlobalBuildContext}, value::Value};
 
 pub struct ProgramStore {
     store: OperationStore,
@@ -15,6 +15,14 @@ impl ProgramStore {
         ProgramBuilder::new(&self.store,step_by_step)
     }
 
+    pub(crate) fn opcode_name(&self, opcode: usize) -> Option<&str> {
+        self.store.name(opcode)
+    }
+
+    pub(crate) fn loads_constant(&self, opcode: usize) -> bool {
+        self.store.loads_constant(opcode)
+    }
+
     pub(crate) fn add_program(&mut self, metadata: &ProgramName, block: &str, program: Program) {
         self.program.insert((metadata.clone(),block.to_string()),program);
     }
@@ -41,10 +49,35 @@ pub struct Program {
     max_reg: usize,
     steps: Arc<Vec<Step>>,
     constants: Arc<Vec<Value>>,
-    step_details: Arc<Vec<(usize,Vec<usize>)>>
+    step_details: Arc<Vec<(usize,Vec<usize>)>>,
+    step_names: Arc<Vec<Option<String>>>,
+    locations: Arc<Vec<Vec<SourceLocation>>>
 }
 
 impl Program {
+    /* Where in the source a step came from, if the object file has a debug section. */
+    pub fn location(&self, step: usize) -> Option<String> {
+        describe_locations(self.locations.get(step)?)
+    }
+
+    pub fn step_count(&self) -> usize { self.steps.len() }
+
+    fn view<'a>(&'a self, index: usize, gctx: &'a GlobalContext) -> StepView<'a> {
+        let (opcode,registers) = &self.step_details[index];
+        StepView {
+            index, opcode: *opcode, registers, gctx,
+            name: self.step_names[index].as_deref(),
+            locations: self.locations.get(index).map(|x| x.as_slice()).unwrap_or(&[])
+        }
+    }
+
+    fn locate(&self, step: usize, error: Option<String>) -> Option<String> {
+        match (error,self.location(step)) {
+            (Some(error),Some(location)) => Some(format!("{} at {}",error,location)),
+            (error,_) => error
+        }
+    }
+
     async fn run_step_by_step(&self, context: RunContext) -> Result<(),Option<String>> {
         let mut gctx = GlobalContext::new(self.max_reg,&self.constants,context);
         for (i,step) in self.steps.as_ref().iter().enumerate() {
@@ -52,7 +85,7 @@ impl Program {
             for reg in &self.step_details[i].1 {
                 eprintln!("  before r{} = {:?}",*reg,gctx.get(*reg));
             }
-            step.run(&mut gctx).await?;
+            step.run(&mut gctx).await.map_err(|e| self.locate(i,e))?;
             eprintln!("");
             for reg in &self.step_details[i].1 {
                 eprintln!("  after  r{} = {:?}",*reg,gctx.get(*reg));
@@ -63,23 +96,52 @@ impl Program {
 
     async fn run_fast(&self, context: RunContext) -> Result<(),Option<String>> {
         let mut gctx = GlobalContext::new(self.max_reg,&self.constants,context);
-        for step in self.steps.as_ref().iter() {
-            step.run(&mut gctx).await?;
+        for (i,step) in self.steps.as_ref().iter().enumerate() {
+            step.run(&mut gctx).await.map_err(|e| self.locate(i,e))?;
+        }
+        Ok(())
+    }
+
+    async fn run_hooked(&self, context: RunContext, hook: &mut dyn DebugHook) -> Result<(),Option<String>> {
+        let mut gctx = GlobalContext::new(self.max_reg,&self.constants,context);
+        for (i,step) in self.steps.as_ref().iter().enumerate() {
+            if !hook.before(&self.view(i,&gctx)) {
+                return Err(None);
+            }
+            step.run(&mut gctx).await.map_err(|e| self.locate(i,e))?;
+            hook.after(&self.view(i,&gctx));
         }
         Ok(())
     }
 
+    fn finish(out: Result<(),Option<String>>) -> Result<(),String> {
+        match out {
+            Ok(()) => Ok(()),
+            Err(None) => Ok(()),
+            Err(Some(x)) => Err(x)
+        }
+    }
+
     pub async fn run(&self, context: RunContext) -> Result<(),String> {
         let out = if self.step_details.len() > 0 {
             self.run_step_by_step(context).await
         } else {
             self.run_fast(context).await
         };
-        match out {
-            Ok(()) => Ok(()),
-            Err(None) => Ok(()),
-            Err(Some(x)) => Err(x)
+        Self::finish(out)
+    }
+
+    /* Needs a program built step-by-step, so that opcodes and registers are kept. */
+    pub async fn run_debug(&self, context: RunContext, hook: &mut dyn DebugHook) -> Result<(),String> {
+        if self.step_details.len() != self.steps.len() {
+            return Err("program was not built step-by-step so cannot be debugged".to_string());
         }
+        Self::finish(self.run_hooked(context,hook).await)
+    }
+
+    /* Adds timings and output sizes from this run to profile. Also needs a step-by-step build. */
+    pub async fn run_profile(&self, context: RunContext, profile: &mut Profile) -> Result<(),String> {
+        self.run_debug(context,profile).await
     }
 }
 
@@ -89,14 +151,17 @@ pub struct ProgramBuilder<'a> {
     store: &'a OperationStore,
     constants: Vec<Value>,
     steps: Vec<Step>,
-    step_details: Vec<(usize,Vec<usize>)>  
+    step_details: Vec<(usize,Vec<usize>)>,
+    step_names: Vec<Option<String>>,
+    locations: Vec<Vec<SourceLocation>>,
+    known: HashMap<usize,usize> // register -> constant it certainly holds
 }
 
 impl<'a> ProgramBuilder<'a> {
     pub(crate) fn new(store: &'a OperationStore, symbols: bool) -> ProgramBuilder<'a> {
         ProgramBuilder { 
             store, constants: vec![], steps: vec![], max_reg: 0, step_details: vec![],
-            symbols
+            step_names: vec![], locations: vec![], known: HashMap::new(), symbols
         }
     }
 
@@ -112,17 +177,35 @@ impl<'a> ProgramBuilder<'a> {
         let oper = self.store.get(opcode)?;
         if self.symbols {
             self.step_details.push((opcode,registers.clone()));
+            self.step_names.push(oper.name().map(|x| x.to_string()));
+        }
+        let constants = registers.iter().map(|r| {
+            self.known.get(r).and_then(|c| self.constants.get(*c))
+        }).collect::<Vec<_>>();
+        self.steps.push(Step::new(gbctx,oper,registers.clone(),&constants)?);
+        /* Programs are straight-line so a register keeps a constant until another step mentions it. */
+        for reg in &registers {
+            self.known.remove(reg);
+        }
+        if self.store.loads_constant(opcode) && registers.len() == 2 {
+            self.known.insert(registers[0],registers[1]);
         }
-        self.steps.push(Step::new(gbctx,oper,registers)?);
         Ok(())
     }
 
+    pub(crate) fn set_locations(&mut self, locations: &[Vec<SourceLocation>]) {
+        self.locations = locations.to_vec();
+    }
+
     pub(crate) fn to_program(&mut self) -> Program {
+        self.known.clear();
         Program {
             max_reg: self.max_reg,
-            steps: Arc::new(mem::replace(&mut self.steps,vec![])),
-            constants: Arc::new(mem::replace(&mut self.constants,vec![])),
-            step_details: Arc::new(mem::replace(&mut self.step_details,vec![]))
+            steps: Arc::new(mem::take(&mut self.steps)),
+            constants: Arc::new(mem::take(&mut self.constants)),
+            step_details: Arc::new(mem::take(&mut self.step_details)),
+            step_names: Arc::new(mem::take(&mut self.step_names)),
+            locations: Arc::new(mem::take(&mut self.locations))
         }
     }
 }
@@ -134,6 +134,16 @@ impl Value {
         }
     }
 
+    /* number of elements in a finite sequence, otherwise one */
+    pub fn size(&self) -> usize {
+        match self {
+            Value::FiniteBoolean(v) => v.len(),
+            Value::FiniteNumber(v) => v.len(),
+            Value::FiniteString(v) => v.len(),
+            _ => 1
+        }
+    }
+
     pub fn is_atomic(&self) -> bool {
         match self {
             Value::Boolean(_) => true,
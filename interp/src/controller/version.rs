@@ -18,6 +18,12 @@ impl OpcodeVersion {
         self.version.insert(name.to_string(),version);
     }
 
+    pub(crate) fn versions(&self) -> Vec<(String,(u32,u32))> {
+        let mut out = self.version.iter().map(|(k,v)| (k.clone(),*v)).collect::<Vec<_>>();
+        out.sort();
+        out
+    }
+
     pub(crate) fn meets_minimums(&self, minimums: &OpcodeVersion) -> Result<(),String> {
         for (name,encoding) in &self.version {
             if let Some(minimum) = minimums.version.get(name) {
@@ -1,9 +1,12 @@
 mod controller {
     pub(crate) mod context;
+    pub(crate) mod debugger;
+    pub(crate) mod disassemble;
     pub(crate) mod globalcontext;
     pub(crate) mod handles;
     pub(crate) mod interpreter;
     pub(crate) mod operation;
+    pub(crate) mod profile;
     pub(crate) mod program;
     pub(crate) mod value;
     pub(crate) mod objectcode;    
@@ -21,6 +24,7 @@ mod libcore {
     mod arith;
     mod checks;
     mod print;
+    mod regexp;
     mod seq;
     mod string;
     mod seqctors;
@@ -32,11 +36,13 @@ mod test {
 }
 
 pub use controller::context::{ RunContext, ContextItem };
+pub use controller::debugger::{ DebugHook, StepView };
 pub use controller::globalcontext::{ GlobalContext, GlobalBuildContext };
 pub use controller::handles::HandleStore;
 pub use controller::interpreter::{ Interpreter, InterpreterBuilder };
-pub use controller::objectcode::{ProgramName, ObjectFile };
+pub use controller::objectcode::{ ProgramName, ObjectFile, SourceLocation };
 pub use controller::operation::{ Operation, Return, AsyncReturn };
+pub use controller::profile::{ Profile, ProfileEntry, StepProfile };
 pub use controller::value::Value;
 pub use libcore::libcore::LibcoreTemplate;
 pub use libcore::libcore::{ prepare_libcore, build_libcore, LibcoreBuilder };
@@ -311,6 +311,16 @@ op_binnum!(op_sub2,op_sub3,op_sub2s,op_sub3s,op_sub2ss,op_sub3ss,|a,b| *a -= b);
 op_binnum!(op_mul2,op_mul3,op_mul2s,op_mul3s,op_mul2ss,op_mul3ss,|a,b| *a *= b);
 op_binnum!(op_div2,op_div3,op_div2s,op_div3s,op_div2ss,op_div3ss,|a,b| *a /= b);
 op_binnum!(op_mod2,op_mod3,op_mod2s,op_mod3s,op_mod2ss,op_mod3ss,|a,b| *a %= b);
+op_binnum!(op_pow2,op_pow3,op_pow2s,op_pow3s,op_pow2ss,op_pow3ss,|a,b| *a = a.powf(b));
+
+/* pow(number,seq): the sequence is the exponent, so the arguments arrive the other way round */
+pub(super) fn op_rpow2s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    op_num2s(|a,b| *a = b.powf(*a))
+}
+
+pub(super) fn op_rpow3s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    op_num3s(|a,b| *a = b.powf(*a))
+}
 
 op_binnumbool!(op_gt,op_gt_s,op_gt_ss,|a,b| a>b);
 op_binnumbool!(op_ge,op_ge_s,op_ge_ss,|a,b| a>=b);
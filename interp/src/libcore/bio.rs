@@ -13,12 +13,10 @@ fn flip_one_base(input: char) -> char {
 pub(crate) fn op_base_flip(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
     Ok(Box::new(move |ctx,regs| {
         let input = ctx.force_string(regs[1])?;
-        eprintln!("input {:?}",input);
         let mut out = String::new();
         for data in input.chars() {
             out.push(flip_one_base(data));
         }
-        eprintln!("output {:?}",out);
         ctx.set(regs[0],Value::String(out))?;
         Ok(Return::Sync)
     }))
@@ -49,6 +47,138 @@ pub(crate) fn op_base_flip_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&m
     }))
 }
 
+fn reverse_complement(input: &str) -> String {
+    input.chars().rev().map(flip_one_base).collect()
+}
+
+pub(crate) fn op_reverse_complement(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let out = reverse_complement(ctx.force_string(regs[1])?);
+        ctx.set(regs[0],Value::String(out))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_reverse_complement_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        if ctx.is_finite(regs[1])? {
+            let input = ctx.force_finite_string(regs[1])?;
+            let out = input.iter().map(|x| reverse_complement(x)).collect();
+            ctx.set(regs[0],Value::FiniteString(out))?;
+        } else {
+            let out = reverse_complement(ctx.force_infinite_string(regs[1])?);
+            ctx.set(regs[0],Value::InfiniteString(out))?;
+        }
+        Ok(Return::Sync)
+    }))
+}
+
+fn base_index(base: u8) -> Option<usize> {
+    match base {
+        b't' | b'T' | b'u' | b'U' => Some(0),
+        b'c' | b'C' => Some(1),
+        b'a' | b'A' => Some(2),
+        b'g' | b'G' => Some(3),
+        _ => None
+    }
+}
+
+/* Standard genetic code, indexed by 16*first+4*second+third with bases in TCAG order. */
+const CODONS : &[u8;64] = b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+
+fn translate_codon(codon: &[u8]) -> char {
+    let mut index = 0;
+    for base in codon {
+        if let Some(value) = base_index(*base) {
+            index = index*4 + value;
+        } else {
+            return 'X';
+        }
+    }
+    CODONS[index] as char
+}
+
+fn translate(input: &str, frame: f64) -> Result<String,String> {
+    if frame != 0. && frame != 1. && frame != 2. {
+        return Err(format!("translate: frame must be 0, 1 or 2, not {}",frame));
+    }
+    let bases = input.as_bytes();
+    let bases = if bases.len() > frame as usize { &bases[(frame as usize)..] } else { &[] };
+    Ok(bases.chunks_exact(3).map(translate_codon).collect())
+}
+
+pub(crate) fn op_translate(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let frame = ctx.force_number(regs[2])?;
+        let out = translate(ctx.force_string(regs[1])?,frame)?;
+        ctx.set(regs[0],Value::String(out))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_translate_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let frame = ctx.force_number(regs[2])?;
+        if ctx.is_finite(regs[1])? {
+            let input = ctx.force_finite_string(regs[1])?;
+            let out = input.iter().map(|x| translate(x,frame)).collect::<Result<_,_>>()?;
+            ctx.set(regs[0],Value::FiniteString(out))?;
+        } else {
+            let out = translate(ctx.force_infinite_string(regs[1])?,frame)?;
+            ctx.set(regs[0],Value::InfiniteString(out))?;
+        }
+        Ok(Return::Sync)
+    }))
+}
+
+/* Proportion of G and C among the unambiguous bases of each window. Windows of only ambiguous
+ * bases (eg all N) give zero. The last window may be short.
+ */
+fn gc_content(out: &mut Vec<f64>, input: &str, window: f64) -> Result<(),String> {
+    if !window.is_finite() || window < 1. {
+        return Err(format!("gc_content: window must be at least 1, not {}",window));
+    }
+    for chunk in input.as_bytes().chunks(window as usize) {
+        let mut gc = 0;
+        let mut total = 0;
+        for base in chunk {
+            match base_index(*base) {
+                Some(1) | Some(3) => { gc += 1; total += 1; },
+                Some(_) => { total += 1; },
+                None => {}
+            }
+        }
+        out.push(if total > 0 { gc as f64 / total as f64 } else { 0. });
+    }
+    Ok(())
+}
+
+pub(crate) fn op_gc_content(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let window = ctx.force_number(regs[2])?;
+        let mut out = vec![];
+        gc_content(&mut out,ctx.force_string(regs[1])?,window)?;
+        ctx.set(regs[0],Value::FiniteNumber(out))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_gc_content_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let window = ctx.force_number(regs[3])?;
+        let input = ctx.force_finite_string(regs[2])?;
+        let mut out = vec![];
+        let mut index = vec![];
+        for (i,s) in input.iter().enumerate() {
+            gc_content(&mut out,s,window)?;
+            index.resize(out.len(),i as f64);
+        }
+        ctx.set(regs[0],Value::FiniteNumber(out))?;
+        ctx.set(regs[1],Value::FiniteNumber(index))?;
+        Ok(Return::Sync)
+    }))
+}
+
 fn ruler_interval(region: i64, max_points: i64, seps: &[i64]) -> i64 {
     let mut b10_value = 1;
     for _ in 0..20 {
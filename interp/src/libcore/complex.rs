@@ -77,3 +77,128 @@ pub(crate) fn op_gaps(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut Glob
         Ok(Return::Sync)
     }))
 }
+
+fn check_intervals(name: &str, starts: &[f64], ends: &[f64]) -> Result<(),String> {
+    if starts.len() != ends.len() {
+        return Err(format!("{}: {} starts but {} ends",name,starts.len(),ends.len()));
+    }
+    Ok(())
+}
+
+fn by_start(starts: &[f64], ends: &[f64]) -> Vec<usize> {
+    let mut order = (0..starts.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| (OrderedFloat(starts[*i]),OrderedFloat(ends[*i])));
+    order
+}
+
+/* Union of a set of intervals. Intervals which overlap or touch are merged. Also returns, for each
+ * input interval, which of the merged intervals it ended up in.
+ */
+
+fn merge_intervals(starts: &[f64], ends: &[f64]) -> (Vec<f64>,Vec<f64>,Vec<usize>) {
+    let mut out_start : Vec<f64> = vec![];
+    let mut out_end : Vec<f64> = vec![];
+    let mut mapping = vec![0;starts.len()];
+    for index in by_start(starts,ends) {
+        let (start,end) = (starts[index],ends[index]);
+        match out_end.last_mut() {
+            Some(last_end) if start <= *last_end => {
+                *last_end = last_end.max(end);
+            },
+            _ => {
+                out_start.push(start);
+                out_end.push(end);
+            }
+        }
+        mapping[index] = out_start.len()-1;
+    }
+    (out_start,out_end,mapping)
+}
+
+/* All pairs (a,b) of intervals from the two sets which share some extent. Intervals which merely
+ * touch do not overlap. Pairs are ordered by a then b.
+ */
+
+fn overlaps(a_starts: &[f64], a_ends: &[f64], b_starts: &[f64], b_ends: &[f64]) -> (Vec<usize>,Vec<usize>) {
+    let b_order = by_start(b_starts,b_ends);
+    let mut out_a = vec![];
+    let mut out_b = vec![];
+    for (a,(a_start,a_end)) in a_starts.iter().zip(a_ends.iter()).enumerate() {
+        let mut hits = b_order.iter()
+            .take_while(|b| b_starts[**b] < *a_end)
+            .filter(|b| b_ends[**b] > *a_start)
+            .cloned().collect::<Vec<_>>();
+        hits.sort();
+        for b in hits {
+            out_a.push(a);
+            out_b.push(b);
+        }
+    }
+    (out_a,out_b)
+}
+
+/* Assign each interval the lowest row in which it is at least gap clear of everything already
+ * placed. Intervals are placed in order of start (ties by end, then by input order) so the
+ * layout is deterministic.
+ */
+
+fn bump(starts: &[f64], ends: &[f64], gap: f64) -> Vec<usize> {
+    let mut row_ends : Vec<f64> = vec![];
+    let mut rows = vec![0;starts.len()];
+    for index in by_start(starts,ends) {
+        let row = row_ends.iter().position(|row_end| *row_end + gap <= starts[index]);
+        let row = if let Some(row) = row {
+            row_ends[row] = ends[index];
+            row
+        } else {
+            row_ends.push(ends[index]);
+            row_ends.len()-1
+        };
+        rows[index] = row;
+    }
+    rows
+}
+
+fn to_numbers(values: &[usize]) -> Vec<f64> {
+    values.iter().map(|x| *x as f64).collect()
+}
+
+pub(crate) fn op_merge_intervals(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(|ctx,regs| {
+        let starts = ctx.force_finite_number(regs[3])?;
+        let ends = ctx.force_finite_number(regs[4])?;
+        check_intervals("merge_intervals",starts,ends)?;
+        let (out_start,out_end,mapping) = merge_intervals(starts,ends);
+        ctx.set(regs[0],Value::FiniteNumber(out_start))?;
+        ctx.set(regs[1],Value::FiniteNumber(out_end))?;
+        ctx.set(regs[2],Value::FiniteNumber(to_numbers(&mapping)))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_overlaps(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(|ctx,regs| {
+        let a_starts = ctx.force_finite_number(regs[2])?;
+        let a_ends = ctx.force_finite_number(regs[3])?;
+        let b_starts = ctx.force_finite_number(regs[4])?;
+        let b_ends = ctx.force_finite_number(regs[5])?;
+        check_intervals("overlaps",a_starts,a_ends)?;
+        check_intervals("overlaps",b_starts,b_ends)?;
+        let (out_a,out_b) = overlaps(a_starts,a_ends,b_starts,b_ends);
+        ctx.set(regs[0],Value::FiniteNumber(to_numbers(&out_a)))?;
+        ctx.set(regs[1],Value::FiniteNumber(to_numbers(&out_b)))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_bump(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(|ctx,regs| {
+        let starts = ctx.force_finite_number(regs[1])?;
+        let ends = ctx.force_finite_number(regs[2])?;
+        let gap = ctx.force_number(regs[3])?;
+        check_intervals("bump",starts,ends)?;
+        let rows = bump(starts,ends,gap);
+        ctx.set(regs[0],Value::FiniteNumber(to_numbers(&rows)))?;
+        Ok(Return::Sync)
+    }))
+}
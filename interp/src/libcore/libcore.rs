@@ -1,15 +1,16 @@
 use std::{pin::Pin, future::Future};
 use crate::controller::{globalcontext::{GlobalBuildContext, GlobalContext}, operation::{Return, Operation, AsyncReturn}, interpreter::{InterpreterBuilder}, context::{RunContext, ContextItem}, handles::HandleStore};
 use super::{
-    print::{op_print, op_format, op_comma_format, op_comma_format_s}, 
+    print::{op_print, op_format, op_comma_format, op_comma_format_s, op_number_format, op_number_format_s}, 
     seqctors::{op_push_b2, op_push_b3, op_finseq_b, op_infseq_b, op_push_s2, op_push_s3, op_push_n2, op_finseq_s, op_infseq_s, op_finseq_n, op_infseq_n, op_push_n3},
     checks::{op_len_n, op_len_s, op_len_b, op_total, op_bound, op_check_l, op_check_t, op_check_b, op_check_tt, op_check_li, op_check_ii},
-    arith::{op_max3, op_max2, op_min3, op_min2, op_max3s, op_max2s, op_min3s, op_min2s, op_max3ss, op_max2ss, op_min2ss, op_min3ss, op_add3, op_add2, op_add3s, op_add2s, op_add3ss, op_add2ss, op_sub2ss, op_sub3ss, op_sub2s, op_sub3s, op_sub2, op_sub3, op_mul3, op_mul2, op_div3, op_div2, op_mul3s, op_div3s, op_mul2s, op_div2s, op_mul3ss, op_mul2ss, op_div3ss, op_div2ss, op_gt, op_ge, op_gt_s, op_ge_s, op_gt_ss, op_ge_ss, op_eq_num, op_eq_str, op_eq_num_s, op_eq_str_s, op_eq_num_ss, op_eq_str_ss, op_mod3, op_mod2, op_mod3s, op_mod2s, op_mod3ss, op_mod2ss, op_max_s, op_min_s, op_lt_s, op_le_s},
-    opntn::{op_neg2, op_neg1, op_neg2s, op_neg1s}, 
+    arith::{op_max3, op_max2, op_min3, op_min2, op_max3s, op_max2s, op_min3s, op_min2s, op_max3ss, op_max2ss, op_min2ss, op_min3ss, op_add3, op_add2, op_add3s, op_add2s, op_add3ss, op_add2ss, op_sub2ss, op_sub3ss, op_sub2s, op_sub3s, op_sub2, op_sub3, op_mul3, op_mul2, op_div3, op_div2, op_mul3s, op_div3s, op_mul2s, op_div2s, op_mul3ss, op_mul2ss, op_div3ss, op_div2ss, op_gt, op_ge, op_gt_s, op_ge_s, op_gt_ss, op_ge_ss, op_eq_num, op_eq_str, op_eq_num_s, op_eq_str_s, op_eq_num_ss, op_eq_str_ss, op_mod3, op_mod2, op_mod3s, op_mod2s, op_mod3ss, op_mod2ss, op_max_s, op_min_s, op_lt_s, op_le_s, op_pow3, op_pow2, op_pow3s, op_pow2s, op_pow3ss, op_pow2ss, op_rpow3s, op_rpow2s},
+    opntn::{op_neg2, op_neg1, op_neg2s, op_neg1s, op_floor2, op_floor2s, op_ceil2, op_ceil2s, op_round2, op_round2s, op_abs2, op_abs2s, op_log10_2, op_log10_2s, op_ln2, op_ln2s, op_exp2, op_exp2s}, 
     opbtb::{op_not2, op_not1, op_not2s, op_not1s},
-    seq::{op_repeat, op_if, op_set, op_set_m, op_set_skip, op_set_skip_m, op_set_at, op_set_at_m, op_set_from, op_set_from_m, op_index, op_index_s, op_count, op_enumerate, op_any, op_all, op_position, op_select, op_if_s, op_find, op_find_s},
+    seq::{op_repeat, op_if, op_set, op_set_m, op_set_skip, op_set_skip_m, op_set_at, op_set_at_m, op_set_from, op_set_from_m, op_index, op_index_s, op_count, op_enumerate, op_any, op_all, op_position, op_select, op_if_s, op_find, op_find_s, op_sum, op_mean, op_cumsum, op_group_sum, op_argsort_n, op_argsort_s, op_filter, op_scatter, op_scatter_m, op_unique, op_in_set, op_union, op_intersect, op_difference, op_map, op_map_all},
     opbbtb::{op_eq3_bool, op_eq3_bool_s, op_eq3_bool_ss, op_and3, op_and2_s, op_or3, op_or2_s, op_or2_ss, op_and2, op_and3_ss, op_or3_s, op_or3_ss, op_and3_s, op_and2_ss, op_or2}, 
-    string::{op_concat, op_push_str, op_push_str_s, op_push_str_revs, op_split, op_template, op_split_start, op_split_get, Template, op_template_start, op_template_set, op_template_end, op_push_str_ss, op_strlen, op_strlen_s}, convert::{op_to_bool, op_to_bool_m, op_to_bool_s, op_to_bool_s_m, op_to_num, op_to_num_m, op_to_num_s, op_to_num_s_m, op_to_str, op_to_str_m, op_to_str_s, op_to_str_s_m}, bio::{op_base_flip, op_base_flip_s, op_ruler_interval, op_ruler_markings}, complex::op_gaps
+    string::{op_concat, op_push_str, op_push_str_s, op_push_str_revs, op_split, op_template, op_split_start, op_split_get, Template, op_template_start, op_template_set, op_template_end, op_push_str_ss, op_strlen, op_strlen_s, op_substr, op_substr_s, op_upper, op_upper_s, op_lower, op_lower_s, op_trim, op_trim_s, op_pad_start, op_pad_start_s, op_pad_end, op_pad_end_s, op_starts_with, op_starts_with_s, op_ends_with, op_ends_with_s, op_contains, op_contains_s}, convert::{op_to_bool, op_to_bool_m, op_to_bool_s, op_to_bool_s_m, op_to_num, op_to_num_m, op_to_num_s, op_to_num_s_m, op_to_str, op_to_str_m, op_to_str_s, op_to_str_s_m}, bio::{op_base_flip, op_base_flip_s, op_ruler_interval, op_ruler_markings, op_reverse_complement, op_reverse_complement_s, op_translate, op_translate_s, op_gc_content, op_gc_content_s}, complex::{op_gaps, op_merge_intervals, op_overlaps, op_bump},
+    regexp::{op_regex_match, op_regex_match_s, op_regex_capture, op_regex_group, op_regex_replace, op_regex_replace_s}
 };
 
 pub trait LibcoreTemplate {
@@ -21,7 +22,8 @@ pub trait LibcoreTemplate {
 pub struct LibcoreBuilder {
     context: ContextItem<Box<dyn LibcoreTemplate>>,
     splits: ContextItem<HandleStore<Vec<Vec<String>>>>,
-    templates: ContextItem<HandleStore<Template>>
+    templates: ContextItem<HandleStore<Template>>,
+    captures: ContextItem<HandleStore<Vec<Vec<String>>>>
 }
 
 fn op_const(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
@@ -59,172 +61,248 @@ pub fn build_libcore(builder: &mut InterpreterBuilder) -> Result<LibcoreBuilder,
     let context = builder.add_context::<Box<dyn LibcoreTemplate>>("libcore")?;
     let splits = builder.add_context::<HandleStore<Vec<Vec<String>>>>("splits")?;
     let templates = builder.add_context::<HandleStore<Template>>("templates")?;
+    let captures = builder.add_context::<HandleStore<Vec<Vec<String>>>>("captures")?;
     builder.add_version("libcore",(0,0));
-    builder.add_operation(0,Operation::new(op_const));
-    builder.add_operation(1,Operation::new(op_async));
-    builder.add_operation(2,Operation::new(op_halt));
-    builder.add_operation(3,Operation::new(op_push_n3));
-    builder.add_operation(4,Operation::new(op_push_n2));
-    builder.add_operation(5,Operation::new(op_len_n));
-    builder.add_operation(6,Operation::new(op_total));
-    builder.add_operation(7,Operation::new(op_bound));
-    builder.add_operation(8,Operation::new(op_infseq_n));
-    builder.add_operation(9,Operation::new(op_finseq_n));
-    builder.add_operation(10,Operation::new(op_check_l));
-    builder.add_operation(11,Operation::new(op_check_tt));
-    builder.add_operation(12,Operation::new(op_select));
-    builder.add_operation(13,Operation::new(op_check_t));
-    builder.add_operation(14,Operation::new(op_check_b));
-    builder.add_operation(15,Operation::new(op_check_ii));
-    builder.add_operation(16,Operation::new(op_check_li));
-    builder.add_operation(17,Operation::new(op_add3));
-    builder.add_operation(18,Operation::new(op_add2));
-    builder.add_operation(19,Operation::new(op_sub3));
-    builder.add_operation(20,Operation::new(op_sub2));
-    builder.add_operation(21,Operation::new(op_copy));
-    builder.add_operation(22,Operation::new(op_mul3));
-    builder.add_operation(23,Operation::new(op_mul2));
-    builder.add_operation(24,Operation::new(op_div3));
-    builder.add_operation(25,Operation::new(op_div2));
-    builder.add_operation(26,Operation::new(op_gt));
-    builder.add_operation(27,Operation::new(op_ge));
-    builder.add_operation(28,Operation::new(op_not2));
-    builder.add_operation(29,Operation::new(op_not1));
-    builder.add_operation(30,Operation::new(op_eq_num));
-    builder.add_operation(31,Operation::new(op_eq_str));
-    builder.add_operation(32,Operation::new(op_eq3_bool));
-    builder.add_operation(33,Operation::new(op_eq_num_s));
-    builder.add_operation(34,Operation::new(op_eq_str_s));
-    builder.add_operation(35,Operation::new(op_eq3_bool_s));
-    builder.add_operation(36,Operation::new(op_eq_num_ss));
-    builder.add_operation(37,Operation::new(op_eq_str_ss));
-    builder.add_operation(38,Operation::new(op_eq3_bool_ss));
-    builder.add_operation(39,Operation::new(op_not2s));
-    builder.add_operation(40,Operation::new(op_not1s));
-    builder.add_operation(41,Operation::new(op_infseq_s));
-    builder.add_operation(42,Operation::new(op_finseq_s));
-    builder.add_operation(43,Operation::new(op_push_s3));
-    builder.add_operation(44,Operation::new(op_push_s2));
-    builder.add_operation(45,Operation::new(op_len_s));
-    builder.add_operation(46,Operation::new(op_max3));
-    builder.add_operation(47,Operation::new(op_max2));
-    builder.add_operation(48,Operation::new(op_gt_s));
-    builder.add_operation(49,Operation::new(op_ge_s));
-    builder.add_operation(50,Operation::new(op_repeat));
-    builder.add_operation(51,Operation::new(op_infseq_b));
-    builder.add_operation(52,Operation::new(op_finseq_b));
-    builder.add_operation(53,Operation::new(op_push_b3));
-    builder.add_operation(54,Operation::new(op_push_b2));
-    builder.add_operation(55,Operation::new(op_len_b));
-    builder.add_operation(56,Operation::new(op_min3));
-    builder.add_operation(57,Operation::new(op_min2));
-    builder.add_operation(58,Operation::new(op_gt_ss));
-    builder.add_operation(59,Operation::new(op_ge_ss));
-    builder.add_operation(60,Operation::new(op_add3s));
-    builder.add_operation(61,Operation::new(op_add2s));
-    builder.add_operation(62,Operation::new(op_sub3s));
-    builder.add_operation(63,Operation::new(op_sub2s));
-    builder.add_operation(64,Operation::new(op_mul3s));
-    builder.add_operation(65,Operation::new(op_mul2s));
-    builder.add_operation(66,Operation::new(op_div3s));
-    builder.add_operation(67,Operation::new(op_div2s));
-    builder.add_operation(68,Operation::new(op_neg2));
-    builder.add_operation(69,Operation::new(op_neg1));
-    builder.add_operation(70,Operation::new(op_add3ss));
-    builder.add_operation(71,Operation::new(op_add2ss));
-    builder.add_operation(72,Operation::new(op_sub3ss));
-    builder.add_operation(73,Operation::new(op_sub2ss));
-    builder.add_operation(74,Operation::new(op_mul3ss));
-    builder.add_operation(75,Operation::new(op_mul2ss));
-    builder.add_operation(76,Operation::new(op_div3ss));
-    builder.add_operation(77,Operation::new(op_div2ss));
-    builder.add_operation(78,Operation::new(op_neg2s));
-    builder.add_operation(79,Operation::new(op_neg1s));
-    builder.add_operation(80,Operation::new(op_and3));
-    builder.add_operation(81,Operation::new(op_and2));
-    builder.add_operation(82,Operation::new(op_and3_s));
-    builder.add_operation(83,Operation::new(op_and2_s));
-    builder.add_operation(84,Operation::new(op_and3_ss));
-    builder.add_operation(85,Operation::new(op_and2_ss));
-    builder.add_operation(86,Operation::new(op_or3));
-    builder.add_operation(87,Operation::new(op_or2));
-    builder.add_operation(88,Operation::new(op_or3_s));
-    builder.add_operation(89,Operation::new(op_or2_s));
-    builder.add_operation(90,Operation::new(op_or3_ss));
-    builder.add_operation(91,Operation::new(op_or2_ss));
-    builder.add_operation(92,Operation::new(op_if));
-    builder.add_operation(93,Operation::new(op_if_s));
-    builder.add_operation(94,Operation::new(op_set));
-    builder.add_operation(95,Operation::new(op_set_m));
-    builder.add_operation(96,Operation::new(op_set_skip));
-    builder.add_operation(97,Operation::new(op_set_skip_m));
-    builder.add_operation(98,Operation::new(op_set_at));
-    builder.add_operation(99,Operation::new(op_set_at_m));
-    builder.add_operation(100,Operation::new(op_set_from));
-    builder.add_operation(101,Operation::new(op_set_from_m));
-    builder.add_operation(102,Operation::new(op_index));
-    builder.add_operation(103,Operation::new(op_index_s));
-    builder.add_operation(104,Operation::new(op_count));
-    builder.add_operation(105,Operation::new(op_enumerate));
-    builder.add_operation(106,Operation::new(op_concat));
-    builder.add_operation(107,Operation::new(op_push_str));
-    builder.add_operation(108,Operation::new(op_push_str_s));
-    builder.add_operation(109,Operation::new(op_split));
-    builder.add_operation(110,Operation::new(op_template));
-    builder.add_operation(111,Operation::new(op_split_start));
-    builder.add_operation(112,Operation::new(op_split_get));
-    builder.add_operation(113,Operation::new(op_template_start));
-    builder.add_operation(114,Operation::new(op_template_set));
-    builder.add_operation(115,Operation::new(op_template_end));
-    builder.add_operation(116,Operation::new(op_any));
-    builder.add_operation(117,Operation::new(op_all));
-    builder.add_operation(118,Operation::new(op_position));
-    builder.add_operation(119,Operation::new(op_mod3));
-    builder.add_operation(120,Operation::new(op_mod2));
-    builder.add_operation(121,Operation::new(op_mod3s));
-    builder.add_operation(122,Operation::new(op_mod2s));
-    builder.add_operation(123,Operation::new(op_mod3ss));
-    builder.add_operation(124,Operation::new(op_mod2ss));
-    builder.add_operation(125,Operation::new(op_to_bool));
-    builder.add_operation(126,Operation::new(op_to_bool_m));
-    builder.add_operation(127,Operation::new(op_to_bool_s));
-    builder.add_operation(128,Operation::new(op_to_bool_s_m));
-    builder.add_operation(129,Operation::new(op_to_num));
-    builder.add_operation(130,Operation::new(op_to_num_m));
-    builder.add_operation(131,Operation::new(op_to_num_s));
-    builder.add_operation(132,Operation::new(op_to_num_s_m));
-    builder.add_operation(133,Operation::new(op_to_str));
-    builder.add_operation(134,Operation::new(op_to_str_m));
-    builder.add_operation(135,Operation::new(op_to_str_s));
-    builder.add_operation(136,Operation::new(op_to_str_s_m));
-    builder.add_operation(137,Operation::new(op_print));
-    builder.add_operation(138,Operation::new(op_format));
-    builder.add_operation(139,Operation::new(op_max_s));
-    builder.add_operation(140,Operation::new(op_min_s));
-    builder.add_operation(141,Operation::new(op_max3s));
-    builder.add_operation(142,Operation::new(op_max2s));
-    builder.add_operation(143,Operation::new(op_max3ss));
-    builder.add_operation(144,Operation::new(op_max2ss));
-    builder.add_operation(145,Operation::new(op_min3s));
-    builder.add_operation(146,Operation::new(op_min2s));
-    builder.add_operation(147,Operation::new(op_min3ss));
-    builder.add_operation(148,Operation::new(op_min2ss));
-    builder.add_operation(149,Operation::new(op_base_flip));
-    builder.add_operation(150,Operation::new(op_base_flip_s));
-    builder.add_operation(151,Operation::new(op_ruler_interval));
-    builder.add_operation(152,Operation::new(op_ruler_markings));
-    builder.add_operation(153,Operation::new(op_comma_format));
-    builder.add_operation(154,Operation::new(op_comma_format_s));
-    builder.add_operation(155,Operation::new(op_push_str_ss));
-    builder.add_operation(156,Operation::new(op_push_str_revs));
-    builder.add_operation(157,Operation::new(op_lt_s));
-    builder.add_operation(158,Operation::new(op_le_s));
-    builder.add_operation(159,Operation::new(op_gaps));
-    builder.add_operation(160,Operation::new(op_find));
-    builder.add_operation(161,Operation::new(op_find_s));
-    builder.add_operation(162,Operation::new(op_strlen));
-    builder.add_operation(163,Operation::new(op_strlen_s));
-    Ok(LibcoreBuilder { context, splits, templates })
+    builder.add_operation_named(0,"const",Operation::new(op_const).loads_constant());
+    builder.add_operation_named(1,"call_up",Operation::new(op_async));
+    builder.add_operation_named(2,"halt",Operation::new(op_halt));
+    builder.add_operation_named(3,"push number",Operation::new(op_push_n3));
+    builder.add_operation_named(4,"push number modify",Operation::new(op_push_n2));
+    builder.add_operation_named(5,"length number",Operation::new(op_len_n));
+    builder.add_operation_named(6,"total",Operation::new(op_total));
+    builder.add_operation_named(7,"bound",Operation::new(op_bound));
+    builder.add_operation_named(8,"infseq number",Operation::new(op_infseq_n));
+    builder.add_operation_named(9,"finseq number",Operation::new(op_finseq_n));
+    builder.add_operation_named(10,"check length/length",Operation::new(op_check_l));
+    builder.add_operation_named(11,"check total/total",Operation::new(op_check_tt));
+    builder.add_operation_named(12,"select",Operation::new(op_select));
+    builder.add_operation_named(13,"check length/total",Operation::new(op_check_t));
+    builder.add_operation_named(14,"check length/bound",Operation::new(op_check_b));
+    builder.add_operation_named(15,"check inf/inf",Operation::new(op_check_ii));
+    builder.add_operation_named(16,"check length/inf",Operation::new(op_check_li));
+    builder.add_operation_named(17,"add",Operation::new(op_add3));
+    builder.add_operation_named(18,"add modify",Operation::new(op_add2));
+    builder.add_operation_named(19,"sub",Operation::new(op_sub3));
+    builder.add_operation_named(20,"sub modify",Operation::new(op_sub2));
+    builder.add_operation_named(21,"copy",Operation::new(op_copy));
+    builder.add_operation_named(22,"mul",Operation::new(op_mul3));
+    builder.add_operation_named(23,"mul modify",Operation::new(op_mul2));
+    builder.add_operation_named(24,"div",Operation::new(op_div3));
+    builder.add_operation_named(25,"div modify",Operation::new(op_div2));
+    builder.add_operation_named(26,"gt",Operation::new(op_gt));
+    builder.add_operation_named(27,"ge",Operation::new(op_ge));
+    builder.add_operation_named(28,"not",Operation::new(op_not2));
+    builder.add_operation_named(29,"not modify",Operation::new(op_not1));
+    builder.add_operation_named(30,"eq number",Operation::new(op_eq_num));
+    builder.add_operation_named(31,"eq string",Operation::new(op_eq_str));
+    builder.add_operation_named(32,"eq boolean",Operation::new(op_eq3_bool));
+    builder.add_operation_named(33,"eq number seq",Operation::new(op_eq_num_s));
+    builder.add_operation_named(34,"eq string seq",Operation::new(op_eq_str_s));
+    builder.add_operation_named(35,"eq boolean seq",Operation::new(op_eq3_bool_s));
+    builder.add_operation_named(36,"eq number seq/seq",Operation::new(op_eq_num_ss));
+    builder.add_operation_named(37,"eq string seq/seq",Operation::new(op_eq_str_ss));
+    builder.add_operation_named(38,"eq boolean seq/seq",Operation::new(op_eq3_bool_ss));
+    builder.add_operation_named(39,"not seq",Operation::new(op_not2s));
+    builder.add_operation_named(40,"not seq modify",Operation::new(op_not1s));
+    builder.add_operation_named(41,"infseq string",Operation::new(op_infseq_s));
+    builder.add_operation_named(42,"finseq string",Operation::new(op_finseq_s));
+    builder.add_operation_named(43,"push string",Operation::new(op_push_s3));
+    builder.add_operation_named(44,"push string modify",Operation::new(op_push_s2));
+    builder.add_operation_named(45,"length string",Operation::new(op_len_s));
+    builder.add_operation_named(46,"max",Operation::new(op_max3));
+    builder.add_operation_named(47,"max modify",Operation::new(op_max2));
+    builder.add_operation_named(48,"gt seq",Operation::new(op_gt_s));
+    builder.add_operation_named(49,"ge seq",Operation::new(op_ge_s));
+    builder.add_operation_named(50,"repeat",Operation::new(op_repeat));
+    builder.add_operation_named(51,"infseq boolean",Operation::new(op_infseq_b));
+    builder.add_operation_named(52,"finseq boolean",Operation::new(op_finseq_b));
+    builder.add_operation_named(53,"push boolean",Operation::new(op_push_b3));
+    builder.add_operation_named(54,"push boolean modify",Operation::new(op_push_b2));
+    builder.add_operation_named(55,"length boolean",Operation::new(op_len_b));
+    builder.add_operation_named(56,"min",Operation::new(op_min3));
+    builder.add_operation_named(57,"min modify",Operation::new(op_min2));
+    builder.add_operation_named(58,"gt seq/seq",Operation::new(op_gt_ss));
+    builder.add_operation_named(59,"ge seq/seq",Operation::new(op_ge_ss));
+    builder.add_operation_named(60,"add seq",Operation::new(op_add3s));
+    builder.add_operation_named(61,"add seq modify",Operation::new(op_add2s));
+    builder.add_operation_named(62,"sub seq",Operation::new(op_sub3s));
+    builder.add_operation_named(63,"sub seq modify",Operation::new(op_sub2s));
+    builder.add_operation_named(64,"mul seq",Operation::new(op_mul3s));
+    builder.add_operation_named(65,"mul seq modify",Operation::new(op_mul2s));
+    builder.add_operation_named(66,"div seq",Operation::new(op_div3s));
+    builder.add_operation_named(67,"div seq modify",Operation::new(op_div2s));
+    builder.add_operation_named(68,"negate",Operation::new(op_neg2));
+    builder.add_operation_named(69,"negate modify",Operation::new(op_neg1));
+    builder.add_operation_named(70,"add seq/seq",Operation::new(op_add3ss));
+    builder.add_operation_named(71,"add seq/seq modify",Operation::new(op_add2ss));
+    builder.add_operation_named(72,"sub seq/seq",Operation::new(op_sub3ss));
+    builder.add_operation_named(73,"sub seq/seq modify",Operation::new(op_sub2ss));
+    builder.add_operation_named(74,"mul seq/seq",Operation::new(op_mul3ss));
+    builder.add_operation_named(75,"mul seq/seq modify",Operation::new(op_mul2ss));
+    builder.add_operation_named(76,"div seq/seq",Operation::new(op_div3ss));
+    builder.add_operation_named(77,"div seq/seq modify",Operation::new(op_div2ss));
+    builder.add_operation_named(78,"negate seq",Operation::new(op_neg2s));
+    builder.add_operation_named(79,"negate seq modify",Operation::new(op_neg1s));
+    builder.add_operation_named(80,"and",Operation::new(op_and3));
+    builder.add_operation_named(81,"and modify",Operation::new(op_and2));
+    builder.add_operation_named(82,"and seq",Operation::new(op_and3_s));
+    builder.add_operation_named(83,"and seq modify",Operation::new(op_and2_s));
+    builder.add_operation_named(84,"and seq/seq",Operation::new(op_and3_ss));
+    builder.add_operation_named(85,"and seq/seq modify",Operation::new(op_and2_ss));
+    builder.add_operation_named(86,"or",Operation::new(op_or3));
+    builder.add_operation_named(87,"or modify",Operation::new(op_or2));
+    builder.add_operation_named(88,"or seq",Operation::new(op_or3_s));
+    builder.add_operation_named(89,"or seq modify",Operation::new(op_or2_s));
+    builder.add_operation_named(90,"or seq/seq",Operation::new(op_or3_ss));
+    builder.add_operation_named(91,"or seq/seq modify",Operation::new(op_or2_ss));
+    builder.add_operation_named(92,"if",Operation::new(op_if));
+    builder.add_operation_named(93,"if seq",Operation::new(op_if_s));
+    builder.add_operation_named(94,"set",Operation::new(op_set));
+    builder.add_operation_named(95,"set modify",Operation::new(op_set_m));
+    builder.add_operation_named(96,"set_skip",Operation::new(op_set_skip));
+    builder.add_operation_named(97,"set_skip modify",Operation::new(op_set_skip_m));
+    builder.add_operation_named(98,"set_at",Operation::new(op_set_at));
+    builder.add_operation_named(99,"set_at modify",Operation::new(op_set_at_m));
+    builder.add_operation_named(100,"set_from",Operation::new(op_set_from));
+    builder.add_operation_named(101,"set_from modify",Operation::new(op_set_from_m));
+    builder.add_operation_named(102,"index",Operation::new(op_index));
+    builder.add_operation_named(103,"index seq",Operation::new(op_index_s));
+    builder.add_operation_named(104,"count",Operation::new(op_count));
+    builder.add_operation_named(105,"enumerate",Operation::new(op_enumerate));
+    builder.add_operation_named(106,"concat",Operation::new(op_concat));
+    builder.add_operation_named(107,"push_str",Operation::new(op_push_str));
+    builder.add_operation_named(108,"push_str seq",Operation::new(op_push_str_s));
+    builder.add_operation_named(109,"split",Operation::new(op_split));
+    builder.add_operation_named(110,"template",Operation::new(op_template));
+    builder.add_operation_named(111,"split_start",Operation::new(op_split_start));
+    builder.add_operation_named(112,"split_get",Operation::new(op_split_get));
+    builder.add_operation_named(113,"template_start",Operation::new(op_template_start));
+    builder.add_operation_named(114,"template_set",Operation::new(op_template_set));
+    builder.add_operation_named(115,"template_end",Operation::new(op_template_end));
+    builder.add_operation_named(116,"any",Operation::new(op_any));
+    builder.add_operation_named(117,"all",Operation::new(op_all));
+    builder.add_operation_named(118,"position",Operation::new(op_position));
+    builder.add_operation_named(119,"mod",Operation::new(op_mod3));
+    builder.add_operation_named(120,"mod modify",Operation::new(op_mod2));
+    builder.add_operation_named(121,"mod seq",Operation::new(op_mod3s));
+    builder.add_operation_named(122,"mod seq modify",Operation::new(op_mod2s));
+    builder.add_operation_named(123,"mod seq/seq",Operation::new(op_mod3ss));
+    builder.add_operation_named(124,"mod seq/seq modify",Operation::new(op_mod2ss));
+    builder.add_operation_named(125,"to_boolean",Operation::new(op_to_bool));
+    builder.add_operation_named(126,"to_boolean modify",Operation::new(op_to_bool_m));
+    builder.add_operation_named(127,"to_boolean seq",Operation::new(op_to_bool_s));
+    builder.add_operation_named(128,"to_boolean seq modify",Operation::new(op_to_bool_s_m));
+    builder.add_operation_named(129,"to_number",Operation::new(op_to_num));
+    builder.add_operation_named(130,"to_number modify",Operation::new(op_to_num_m));
+    builder.add_operation_named(131,"to_number seq",Operation::new(op_to_num_s));
+    builder.add_operation_named(132,"to_number seq modify",Operation::new(op_to_num_s_m));
+    builder.add_operation_named(133,"to_string",Operation::new(op_to_str));
+    builder.add_operation_named(134,"to_string modify",Operation::new(op_to_str_m));
+    builder.add_operation_named(135,"to_string seq",Operation::new(op_to_str_s));
+    builder.add_operation_named(136,"to_string seq modify",Operation::new(op_to_str_s_m));
+    builder.add_operation_named(137,"print",Operation::new(op_print));
+    builder.add_operation_named(138,"format",Operation::new(op_format));
+    builder.add_operation_named(139,"max reduce",Operation::new(op_max_s));
+    builder.add_operation_named(140,"min reduce",Operation::new(op_min_s));
+    builder.add_operation_named(141,"max seq",Operation::new(op_max3s));
+    builder.add_operation_named(142,"max seq modify",Operation::new(op_max2s));
+    builder.add_operation_named(143,"max seq/seq",Operation::new(op_max3ss));
+    builder.add_operation_named(144,"max seq/seq modify",Operation::new(op_max2ss));
+    builder.add_operation_named(145,"min seq",Operation::new(op_min3s));
+    builder.add_operation_named(146,"min seq modify",Operation::new(op_min2s));
+    builder.add_operation_named(147,"min seq/seq",Operation::new(op_min3ss));
+    builder.add_operation_named(148,"min seq/seq modify",Operation::new(op_min2ss));
+    builder.add_operation_named(149,"base_flip",Operation::new(op_base_flip));
+    builder.add_operation_named(150,"base_flip seq",Operation::new(op_base_flip_s));
+    builder.add_operation_named(151,"ruler_interval",Operation::new(op_ruler_interval));
+    builder.add_operation_named(152,"ruler_markings",Operation::new(op_ruler_markings));
+    builder.add_operation_named(153,"comma_format",Operation::new(op_comma_format));
+    builder.add_operation_named(154,"comma_format seq",Operation::new(op_comma_format_s));
+    builder.add_operation_named(155,"push_str seq/seq",Operation::new(op_push_str_ss));
+    builder.add_operation_named(156,"push_str seq rev",Operation::new(op_push_str_revs));
+    builder.add_operation_named(157,"lt seq",Operation::new(op_lt_s));
+    builder.add_operation_named(158,"le seq",Operation::new(op_le_s));
+    builder.add_operation_named(159,"gaps",Operation::new(op_gaps));
+    builder.add_operation_named(160,"find",Operation::new(op_find));
+    builder.add_operation_named(161,"find seq",Operation::new(op_find_s));
+    builder.add_operation_named(162,"strlen",Operation::new(op_strlen));
+    builder.add_operation_named(163,"strlen seq",Operation::new(op_strlen_s));
+    builder.add_operation_named(164,"substr",Operation::new(op_substr));
+    builder.add_operation_named(165,"substr seq",Operation::new(op_substr_s));
+    builder.add_operation_named(166,"upper",Operation::new(op_upper));
+    builder.add_operation_named(167,"upper seq",Operation::new(op_upper_s));
+    builder.add_operation_named(168,"lower",Operation::new(op_lower));
+    builder.add_operation_named(169,"lower seq",Operation::new(op_lower_s));
+    builder.add_operation_named(170,"trim",Operation::new(op_trim));
+    builder.add_operation_named(171,"trim seq",Operation::new(op_trim_s));
+    builder.add_operation_named(172,"pad_start",Operation::new(op_pad_start));
+    builder.add_operation_named(173,"pad_start seq",Operation::new(op_pad_start_s));
+    builder.add_operation_named(174,"pad_end",Operation::new(op_pad_end));
+    builder.add_operation_named(175,"pad_end seq",Operation::new(op_pad_end_s));
+    builder.add_operation_named(176,"starts_with",Operation::new(op_starts_with));
+    builder.add_operation_named(177,"starts_with seq",Operation::new(op_starts_with_s));
+    builder.add_operation_named(178,"ends_with",Operation::new(op_ends_with));
+    builder.add_operation_named(179,"ends_with seq",Operation::new(op_ends_with_s));
+    builder.add_operation_named(180,"contains",Operation::new(op_contains));
+    builder.add_operation_named(181,"contains seq",Operation::new(op_contains_s));
+    builder.add_operation_named(182,"regex_match",Operation::new_with_constants(op_regex_match));
+    builder.add_operation_named(183,"regex_match seq",Operation::new_with_constants(op_regex_match_s));
+    builder.add_operation_named(184,"regex_capture",Operation::new_with_constants(op_regex_capture));
+    builder.add_operation_named(185,"regex_group",Operation::new(op_regex_group));
+    builder.add_operation_named(186,"regex_replace",Operation::new_with_constants(op_regex_replace));
+    builder.add_operation_named(187,"regex_replace seq",Operation::new_with_constants(op_regex_replace_s));
+    builder.add_operation_named(188,"floor",Operation::new(op_floor2));
+    builder.add_operation_named(189,"floor seq",Operation::new(op_floor2s));
+    builder.add_operation_named(190,"ceil",Operation::new(op_ceil2));
+    builder.add_operation_named(191,"ceil seq",Operation::new(op_ceil2s));
+    builder.add_operation_named(192,"round",Operation::new(op_round2));
+    builder.add_operation_named(193,"round seq",Operation::new(op_round2s));
+    builder.add_operation_named(194,"abs",Operation::new(op_abs2));
+    builder.add_operation_named(195,"abs seq",Operation::new(op_abs2s));
+    builder.add_operation_named(196,"log10",Operation::new(op_log10_2));
+    builder.add_operation_named(197,"log10 seq",Operation::new(op_log10_2s));
+    builder.add_operation_named(198,"ln",Operation::new(op_ln2));
+    builder.add_operation_named(199,"ln seq",Operation::new(op_ln2s));
+    builder.add_operation_named(200,"exp",Operation::new(op_exp2));
+    builder.add_operation_named(201,"exp seq",Operation::new(op_exp2s));
+    builder.add_operation_named(202,"pow",Operation::new(op_pow3));
+    builder.add_operation_named(203,"pow modify",Operation::new(op_pow2));
+    builder.add_operation_named(204,"pow seq",Operation::new(op_pow3s));
+    builder.add_operation_named(205,"pow seq modify",Operation::new(op_pow2s));
+    builder.add_operation_named(206,"pow seq/seq",Operation::new(op_pow3ss));
+    builder.add_operation_named(207,"pow seq/seq modify",Operation::new(op_pow2ss));
+    builder.add_operation_named(208,"sum",Operation::new(op_sum));
+    builder.add_operation_named(209,"mean",Operation::new(op_mean));
+    builder.add_operation_named(210,"cumsum",Operation::new(op_cumsum));
+    builder.add_operation_named(211,"group_sum",Operation::new(op_group_sum));
+    builder.add_operation_named(212,"argsort number",Operation::new(op_argsort_n));
+    builder.add_operation_named(213,"argsort string",Operation::new(op_argsort_s));
+    builder.add_operation_named(214,"filter",Operation::new(op_filter));
+    builder.add_operation_named(215,"scatter",Operation::new(op_scatter));
+    builder.add_operation_named(216,"scatter modify",Operation::new(op_scatter_m));
+    builder.add_operation_named(217,"merge_intervals",Operation::new(op_merge_intervals));
+    builder.add_operation_named(218,"overlaps",Operation::new(op_overlaps));
+    builder.add_operation_named(219,"bump",Operation::new(op_bump));
+    builder.add_operation_named(220,"reverse_complement",Operation::new(op_reverse_complement));
+    builder.add_operation_named(221,"reverse_complement seq",Operation::new(op_reverse_complement_s));
+    builder.add_operation_named(222,"translate",Operation::new(op_translate));
+    builder.add_operation_named(223,"translate seq",Operation::new(op_translate_s));
+    builder.add_operation_named(224,"gc_content",Operation::new(op_gc_content));
+    builder.add_operation_named(225,"gc_content seq",Operation::new(op_gc_content_s));
+    builder.add_operation_named(226,"unique",Operation::new(op_unique));
+    builder.add_operation_named(227,"in_set",Operation::new(op_in_set));
+    builder.add_operation_named(228,"union",Operation::new(op_union));
+    builder.add_operation_named(229,"intersect",Operation::new(op_intersect));
+    builder.add_operation_named(230,"difference",Operation::new(op_difference));
+    builder.add_operation_named(231,"number_format",Operation::new(op_number_format));
+    builder.add_operation_named(232,"number_format seq",Operation::new(op_number_format_s));
+    builder.add_operation_named(233,"map",Operation::new_with_constants(op_map));
+    builder.add_operation_named(234,"map seq",Operation::new_with_constants(op_map));
+    builder.add_operation_named(235,"map_all",Operation::new_with_constants(op_map_all));
+    builder.add_operation_named(236,"map_all seq",Operation::new_with_constants(op_map_all));
+    builder.add_operation_named(237,"pow number/seq",Operation::new(op_rpow3s));
+    builder.add_operation_named(238,"pow number/seq modify",Operation::new(op_rpow2s));
+    Ok(LibcoreBuilder { context, splits, templates, captures })
 }
 
 pub fn prepare_libcore<F>(context: &mut RunContext, builder: &LibcoreBuilder, libcore_template: F)
@@ -232,4 +310,5 @@ pub fn prepare_libcore<F>(context: &mut RunContext, builder: &LibcoreBuilder, li
     context.add(&builder.context,Box::new(libcore_template));
     context.add(&builder.splits,HandleStore::new());
     context.add(&builder.templates,HandleStore::new());
+    context.add(&builder.captures,HandleStore::new());
 }
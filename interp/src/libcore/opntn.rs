@@ -44,7 +44,7 @@ fn op_unum1s<F>(f: F) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Resul
 fn op_unum2s<F>(f: F) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String>
         where F: Fn(&mut f64) + 'static {
     Ok(Box::new(move |ctx,regs| {
-        if ctx.is_finite(regs[0])? {
+        if ctx.is_finite(regs[1])? {
             let mut a = ctx.force_finite_number(regs[1])?.to_vec();
             for v in &mut a {
                 f(v);
@@ -82,3 +82,10 @@ macro_rules! op_unum {
 }
 
 op_unum!(op_neg1,op_neg2,op_neg1s,op_neg2s,|a| *a = -*a);
+op_unum!(op_floor1,op_floor2,op_floor1s,op_floor2s,|a| *a = a.floor());
+op_unum!(op_ceil1,op_ceil2,op_ceil1s,op_ceil2s,|a| *a = a.ceil());
+op_unum!(op_round1,op_round2,op_round1s,op_round2s,|a| *a = a.round());
+op_unum!(op_abs1,op_abs2,op_abs1s,op_abs2s,|a| *a = a.abs());
+op_unum!(op_log10_1,op_log10_2,op_log10_1s,op_log10_2s,|a| *a = a.log10());
+op_unum!(op_ln1,op_ln2,op_ln1s,op_ln2s,|a| *a = a.ln());
+op_unum!(op_exp1,op_exp2,op_exp1s,op_exp2s,|a| *a = a.exp());
@@ -1,15 +1,8 @@
 use std::fmt;
 use crate::controller::{value::Value, globalcontext::{GlobalBuildContext, GlobalContext}, operation::Return};
+use eard_number_format::{NumberFormat, reduce_num};
 use super::libcore::LibcoreTemplate;
 
-fn reduce_num(n: f64) -> String {
-    if n.fract() == 0.0 {
-        format!("{}",n as i64)
-    } else {
-        format!("{}",n)
-    }
-}
-
 fn fmt_seq<T: fmt::Debug>(x: &[T]) -> String {
     x.iter().map(|x| format!("{:?}",x)).collect::<Vec<_>>().join(",")
 }
@@ -81,3 +74,27 @@ pub(crate) fn op_comma_format_s(gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(
         Ok(Return::Sync)    
     }))
 }
+
+pub(crate) fn op_number_format(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let format = NumberFormat::new(ctx.force_string(regs[2])?)?;
+        let value = ctx.force_number(regs[1])?;
+        ctx.set(regs[0],Value::String(format.format(value)))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_number_format_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let format = NumberFormat::new(ctx.force_string(regs[2])?)?;
+        if ctx.is_finite(regs[1])? {
+            let value = ctx.force_finite_number(regs[1])?;
+            let value = value.iter().map(|v| format.format(*v)).collect();
+            ctx.set(regs[0],Value::FiniteString(value))?;
+        } else {
+            let value = ctx.force_infinite_number(regs[1])?;
+            ctx.set(regs[0],Value::InfiniteString(format.format(value)))?;
+        }
+        Ok(Return::Sync)
+    }))
+}
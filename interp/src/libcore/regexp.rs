@@ -0,0 +1,112 @@
+use std::borrow::Cow;
+use regex::Regex;
+use crate::controller::{globalcontext::{GlobalBuildContext, GlobalContext}, value::Value, operation::Return, handles::HandleStore};
+
+/* If the pattern is a constant, compile it now rather than at each run. A bad pattern is left to
+ * fail at runtime, where the error can be located.
+ */
+fn fixed_pattern(constants: &[Option<&Value>], index: usize) -> Option<Regex> {
+    match constants.get(index) {
+        Some(Some(Value::String(pattern))) => Regex::new(pattern).ok(),
+        _ => None
+    }
+}
+
+fn get_pattern<'a>(ctx: &GlobalContext, fixed: &'a Option<Regex>, reg: usize) -> Result<Cow<'a,Regex>,String> {
+    if let Some(fixed) = fixed {
+        return Ok(Cow::Borrowed(fixed));
+    }
+    let pattern = ctx.force_string(reg)?;
+    Ok(Cow::Owned(Regex::new(pattern).map_err(|e| format!("bad regular expression {:?}: {}",pattern,e))?))
+}
+
+pub(crate) fn op_regex_match(_gctx: &GlobalBuildContext, constants: &[Option<&Value>]) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    let fixed = fixed_pattern(constants,2);
+    Ok(Box::new(move |ctx,regs| {
+        let re = get_pattern(ctx,&fixed,regs[2])?;
+        let out = re.is_match(ctx.force_string(regs[1])?);
+        ctx.set(regs[0],Value::Boolean(out))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_regex_match_s(_gctx: &GlobalBuildContext, constants: &[Option<&Value>]) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    let fixed = fixed_pattern(constants,2);
+    Ok(Box::new(move |ctx,regs| {
+        let re = get_pattern(ctx,&fixed,regs[2])?;
+        let value = if ctx.is_finite(regs[1])? {
+            Value::FiniteBoolean(ctx.force_finite_string(regs[1])?.iter().map(|s| re.is_match(s)).collect())
+        } else {
+            Value::InfiniteBoolean(re.is_match(ctx.force_infinite_string(regs[1])?))
+        };
+        ctx.set(regs[0],value)?;
+        Ok(Return::Sync)
+    }))
+}
+
+/* Stored by group then by input, like splits. Non-matching inputs and groups are empty strings, group 0 is the whole match. */
+pub(crate) fn op_regex_capture(gctx: &GlobalBuildContext, constants: &[Option<&Value>]) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    let libcore_captures = gctx.patterns.lookup::<HandleStore<Vec<Vec<String>>>>("captures")?;
+    let fixed = fixed_pattern(constants,2);
+    Ok(Box::new(move |ctx,regs| {
+        let re = get_pattern(ctx,&fixed,regs[2])?;
+        let input = ctx.force_finite_string(regs[1])?;
+        let mut groups = vec![vec![];re.captures_len()];
+        for item in input {
+            let captures = re.captures(item);
+            for (i,group) in groups.iter_mut().enumerate() {
+                let value = captures.as_ref().and_then(|c| c.get(i)).map(|m| m.as_str());
+                group.push(value.unwrap_or("").to_string());
+            }
+        }
+        let captures = ctx.context.get_mut(&libcore_captures);
+        let h = captures.push(groups);
+        ctx.set(regs[0],Value::Number(h as f64))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_regex_group(gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    let libcore_captures = gctx.patterns.lookup::<HandleStore<Vec<Vec<String>>>>("captures")?;
+    Ok(Box::new(move |ctx,regs| {
+        let h = ctx.force_number(regs[1])? as usize;
+        let idx = ctx.force_number(regs[2])?;
+        if idx < 0. || idx.fract() != 0. {
+            return Err(format!("bad regex group {}",idx));
+        }
+        let idx = idx as usize;
+        let captures = ctx.context.get(&libcore_captures);
+        let groups = captures.get(h)?;
+        let values = groups.get(idx).cloned().ok_or_else(|| format!("no such regex group {}, expression has {}",idx,groups.len()-1))?;
+        ctx.set(regs[0],Value::FiniteString(values))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_regex_replace(_gctx: &GlobalBuildContext, constants: &[Option<&Value>]) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    let fixed = fixed_pattern(constants,2);
+    Ok(Box::new(move |ctx,regs| {
+        let re = get_pattern(ctx,&fixed,regs[2])?;
+        let replacement = ctx.force_string(regs[3])?;
+        let out = re.replace_all(ctx.force_string(regs[1])?,replacement).to_string();
+        ctx.set(regs[0],Value::String(out))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_regex_replace_s(_gctx: &GlobalBuildContext, constants: &[Option<&Value>]) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    let fixed = fixed_pattern(constants,2);
+    Ok(Box::new(move |ctx,regs| {
+        let re = get_pattern(ctx,&fixed,regs[2])?;
+        let replacement = ctx.force_string(regs[3])?;
+        let value = if ctx.is_finite(regs[1])? {
+            let input = ctx.force_finite_string(regs[1])?;
+            Value::FiniteString(input.iter().map(|s| re.replace_all(s,replacement).to_string()).collect())
+        } else {
+            let input = ctx.force_infinite_string(regs[1])?;
+            Value::InfiniteString(re.replace_all(input,replacement).to_string())
+        };
+        ctx.set(regs[0],value)?;
+        Ok(Return::Sync)
+    }))
+}
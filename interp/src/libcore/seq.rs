@@ -1,4 +1,4 @@
-use std::{mem, collections::HashMap, hash};
+use std::{mem, collections::{HashMap, HashSet}, hash, borrow::Cow};
 use ordered_float::OrderedFloat;
 
 use crate::controller::{globalcontext::{GlobalContext, GlobalBuildContext}, operation::Return, value::{Value}};
@@ -452,3 +452,390 @@ pub(crate) fn op_find_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut Gl
         Ok(Return::Sync)
     }))
 }
+
+pub(crate) fn op_sum(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let out = ctx.force_finite_number(regs[1])?.iter().sum();
+        ctx.set(regs[0],Value::Number(out))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_mean(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let input = ctx.force_finite_number(regs[1])?;
+        let out = input.iter().sum::<f64>() / (input.len() as f64);
+        ctx.set(regs[0],Value::Number(out))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_cumsum(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let mut total = 0.;
+        let out = ctx.force_finite_number(regs[1])?.iter().map(|v| { total += v; total }).collect();
+        ctx.set(regs[0],Value::FiniteNumber(out))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_group_sum(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let values = ctx.force_finite_number(regs[1])?;
+        let mapping = ctx.force_finite_number(regs[2])?;
+        if values.len() != mapping.len() {
+            return Err(format!("group_sum has {} values but {} groups assigned",values.len(),mapping.len()));
+        }
+        let groups = ctx.force_number(regs[3])?;
+        if groups < 0. || groups.fract() != 0. {
+            return Err(format!("bad group count {}",groups));
+        }
+        let mut out = vec![0.;groups as usize];
+        for (value,group) in values.iter().zip(mapping.iter()) {
+            if *group < 0. || group.fract() != 0. {
+                return Err(format!("bad group {}",group));
+            }
+            *out.get_mut(*group as usize).ok_or_else(|| format!("group {} out of range",group))? += value;
+        }
+        ctx.set(regs[0],Value::FiniteNumber(out))?;
+        Ok(Return::Sync)
+    }))
+}
+
+/* Stable, so ties keep their input order whichever the direction. */
+fn argsort<T: Ord>(input: &[T], descending: bool) -> Vec<f64> {
+    let mut order = (0..input.len()).collect::<Vec<_>>();
+    if descending {
+        order.sort_by(|a,b| input[*b].cmp(&input[*a]));
+    } else {
+        order.sort_by(|a,b| input[*a].cmp(&input[*b]));
+    }
+    order.drain(..).map(|x| x as f64).collect()
+}
+
+pub(crate) fn op_argsort_n(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let input = orderable_seq(ctx.force_finite_number(regs[1])?);
+        let out = argsort(&input,ctx.force_boolean(regs[2])?);
+        ctx.set(regs[0],Value::FiniteNumber(out))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_argsort_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let out = argsort(ctx.force_finite_string(regs[1])?,ctx.force_boolean(regs[2])?);
+        ctx.set(regs[0],Value::FiniteNumber(out))?;
+        Ok(Return::Sync)
+    }))
+}
+
+fn filter<T: Clone>(input: &[T], mask: &[bool]) -> Result<Vec<T>,String> {
+    if input.len() != mask.len() {
+        return Err(format!("filter: mask has length {} but sequence has length {}",mask.len(),input.len()));
+    }
+    Ok(select(input,mask))
+}
+
+pub(crate) fn op_filter(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let out = if ctx.is_finite(regs[2])? {
+            let mask = ctx.force_finite_boolean(regs[2])?;
+            let trues = mask.iter().filter(|x| **x).count();
+            match ctx.get(regs[1])? {
+                Value::FiniteBoolean(b) => Value::FiniteBoolean(filter(b,mask)?),
+                Value::FiniteNumber(n) => Value::FiniteNumber(filter(n,mask)?),
+                Value::FiniteString(s) => Value::FiniteString(filter(s,mask)?),
+                Value::InfiniteBoolean(b) => Value::FiniteBoolean(vec![*b;trues]),
+                Value::InfiniteNumber(n) => Value::FiniteNumber(vec![*n;trues]),
+                Value::InfiniteString(s) => Value::FiniteString(vec![s.to_string();trues]),
+                _ => { return Err("invalid type for filter".to_string()); }
+            }
+        } else if ctx.force_infinite_boolean(regs[2])? {
+            ctx.get(regs[1])?.clone()
+        } else {
+            match ctx.get(regs[1])? {
+                Value::FiniteBoolean(_) | Value::InfiniteBoolean(_) => Value::FiniteBoolean(vec![]),
+                Value::FiniteNumber(_) | Value::InfiniteNumber(_) => Value::FiniteNumber(vec![]),
+                Value::FiniteString(_) | Value::InfiniteString(_) => Value::FiniteString(vec![]),
+                _ => { return Err("invalid type for filter".to_string()); }
+            }
+        };
+        ctx.set(regs[0],out)?;
+        Ok(Return::Sync)
+    }))
+}
+
+/* Unlike set, insist that there is exactly one value for each true in the mask. */
+fn scatter_check(ctx: &GlobalContext, dst: usize, mask: usize, values: usize) -> Result<(),String> {
+    if !ctx.is_finite(mask)? {
+        return Ok(());
+    }
+    let mask = ctx.force_finite_boolean(mask)?;
+    let dst = ctx.get(dst)?;
+    if dst.is_finite() && dst.size() != mask.len() {
+        return Err(format!("scatter: mask has length {} but sequence has length {}",mask.len(),dst.size()));
+    }
+    let trues = mask.iter().filter(|x| **x).count();
+    let values = ctx.get(values)?;
+    if values.is_finite() && values.size() != trues {
+        return Err(format!("scatter: {} values for {} positions",values.size(),trues));
+    }
+    Ok(())
+}
+
+pub(crate) fn op_scatter(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        scatter_check(ctx,regs[1],regs[2],regs[3])?;
+        let mut dst = ctx.get(regs[1])?.clone();
+        set_merge(ctx,&mut dst,regs[2],ctx.get(regs[3])?,false)?;
+        ctx.set(regs[0],dst)?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_scatter_m(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        scatter_check(ctx,regs[0],regs[1],regs[2])?;
+        let mut value = mem::replace(ctx.get_mut(regs[0])?,Value::Boolean(false));
+        set_merge(ctx,&mut value,regs[1],ctx.get(regs[2])?,false)?;
+        ctx.set(regs[0],value)?;
+        Ok(Return::Sync)
+    }))
+}
+
+fn unique<T: hash::Hash + Eq + Clone>(input: &[T]) -> (Vec<T>,Vec<f64>) {
+    let mut positions = HashMap::new();
+    let mut values = vec![];
+    let mapping = input.iter().map(|value| {
+        *positions.entry(value).or_insert_with(|| {
+            values.push(value.clone());
+            values.len()-1
+        }) as f64
+    }).collect();
+    (values,mapping)
+}
+
+fn in_set<T: hash::Hash + Eq>(needles: &[T], haystack: &[T]) -> Vec<bool> {
+    let haystack = haystack.iter().collect::<HashSet<_>>();
+    needles.iter().map(|needle| haystack.contains(needle)).collect()
+}
+
+fn union<T: hash::Hash + Eq + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut seen = HashSet::new();
+    a.iter().chain(b.iter()).filter(|x| seen.insert(*x)).cloned().collect()
+}
+
+/* intersect when members is true, difference when false */
+fn intersect<T: hash::Hash + Eq + Clone>(a: &[T], b: &[T], members: bool) -> Vec<T> {
+    let b = b.iter().collect::<HashSet<_>>();
+    let mut seen = HashSet::new();
+    a.iter().filter(|x| b.contains(x) == members && seen.insert(*x)).cloned().collect()
+}
+
+fn from_orderable(data: Vec<OrderedFloat<f64>>) -> Vec<f64> {
+    data.iter().map(|x| x.0).collect()
+}
+
+/* As a set, an infinite sequence is just its one repeated value. */
+fn finite_set(value: &Value) -> Cow<'_,Value> {
+    match value {
+        Value::InfiniteBoolean(b) => Cow::Owned(Value::FiniteBoolean(vec![*b])),
+        Value::InfiniteNumber(n) => Cow::Owned(Value::FiniteNumber(vec![*n])),
+        Value::InfiniteString(s) => Cow::Owned(Value::FiniteString(vec![s.to_string()])),
+        _ => Cow::Borrowed(value)
+    }
+}
+
+pub(crate) fn op_unique(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let input = ctx.get(regs[2])?;
+        let infinite = !input.is_finite();
+        let (values,mapping) = match finite_set(input).as_ref() {
+            Value::FiniteBoolean(b) => {
+                let (values,mapping) = unique(b);
+                (Value::FiniteBoolean(values),mapping)
+            },
+            Value::FiniteNumber(n) => {
+                let (values,mapping) = unique(&orderable_seq(n));
+                (Value::FiniteNumber(from_orderable(values)),mapping)
+            },
+            Value::FiniteString(s) => {
+                let (values,mapping) = unique(s);
+                (Value::FiniteString(values),mapping)
+            },
+            _ => { return Err("bad type in unique".to_string()) }
+        };
+        let mapping = if infinite { Value::InfiniteNumber(0.) } else { Value::FiniteNumber(mapping) };
+        ctx.set(regs[0],values)?;
+        ctx.set(regs[1],mapping)?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_in_set(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let needles = ctx.get(regs[1])?;
+        let infinite = !needles.is_finite();
+        let out = match (finite_set(needles).as_ref(),finite_set(ctx.get(regs[2])?).as_ref()) {
+            (Value::FiniteBoolean(needles),Value::FiniteBoolean(haystack)) => {
+                in_set(needles,haystack)
+            },
+            (Value::FiniteNumber(needles),Value::FiniteNumber(haystack)) => {
+                in_set(&orderable_seq(needles),&orderable_seq(haystack))
+            },
+            (Value::FiniteString(needles),Value::FiniteString(haystack)) => {
+                in_set(needles,haystack)
+            },
+            _ => { return Err("bad type in in_set".to_string()) }
+        };
+        let out = if infinite { Value::InfiniteBoolean(out[0]) } else { Value::FiniteBoolean(out) };
+        ctx.set(regs[0],out)?;
+        Ok(Return::Sync)
+    }))
+}
+
+macro_rules! op_set_combine {
+    ($name:ident,$label:expr,$a:ident,$b:ident,$combine:expr) => {
+        pub(crate) fn $name(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+            Ok(Box::new(move |ctx,regs| {
+                let out = match (finite_set(ctx.get(regs[1])?).as_ref(),finite_set(ctx.get(regs[2])?).as_ref()) {
+                    (Value::FiniteBoolean($a),Value::FiniteBoolean($b)) => {
+                        Value::FiniteBoolean($combine)
+                    },
+                    (Value::FiniteNumber($a),Value::FiniteNumber($b)) => {
+                        let ($a,$b) = (&orderable_seq($a),&orderable_seq($b));
+                        Value::FiniteNumber(from_orderable($combine))
+                    },
+                    (Value::FiniteString($a),Value::FiniteString($b)) => {
+                        Value::FiniteString($combine)
+                    },
+                    _ => { return Err(format!("bad type in {}",$label)) }
+                };
+                ctx.set(regs[0],out)?;
+                Ok(Return::Sync)
+            }))
+        }
+    }
+}
+
+op_set_combine!(op_union,"union",a,b,union(a,b));
+op_set_combine!(op_intersect,"intersect",a,b,intersect(a,b,true));
+op_set_combine!(op_difference,"difference",a,b,intersect(a,b,false));
+
+enum MapKeys {
+    Boolean(HashMap<bool,usize>),
+    Number(HashMap<OrderedFloat<f64>,usize>),
+    String(HashMap<String,usize>)
+}
+
+fn map_positions<T: hash::Hash + Eq>(keys: impl Iterator<Item=T>) -> HashMap<T,usize> {
+    let mut out = HashMap::new();
+    for (i,key) in keys.enumerate() {
+        out.entry(key).or_insert(i);
+    }
+    out
+}
+
+/* The table of a map!, with any default stored after the values so that a key which isn't
+ * present finds it. Without a default, such a key is an error.
+ */
+struct MapTable {
+    keys: MapKeys,
+    values: Value,
+    missing: Option<usize>
+}
+
+macro_rules! map_pick {
+    ($x:expr,$idx:expr,$values:expr,$atom:tt,$inf:tt,$fin:tt) => {
+        if $x.is_atomic() {
+            Value::$atom($values[$idx[0]].clone())
+        } else if !$x.is_finite() {
+            Value::$inf($values[$idx[0]].clone())
+        } else {
+            Value::$fin($idx.iter().map(|i| $values[*i].clone()).collect())
+        }
+    }
+}
+
+impl MapTable {
+    fn new(keys: &Value, values: &Value, default: Option<&Value>) -> Result<MapTable,String> {
+        let keys = match keys {
+            Value::FiniteBoolean(k) => MapKeys::Boolean(map_positions(k.iter().cloned())),
+            Value::FiniteNumber(k) => MapKeys::Number(map_positions(k.iter().map(|x| OrderedFloat(*x)))),
+            Value::FiniteString(k) => MapKeys::String(map_positions(k.iter().cloned())),
+            _ => { return Err("bad type in map".to_string()); }
+        };
+        let mut values = values.clone();
+        let missing = values.size();
+        match (&mut values,default) {
+            (Value::FiniteBoolean(v),Some(Value::Boolean(d))) => { v.push(*d); },
+            (Value::FiniteNumber(v),Some(Value::Number(d))) => { v.push(*d); },
+            (Value::FiniteString(v),Some(Value::String(d))) => { v.push(d.clone()); },
+            (_,None) => {},
+            _ => { return Err("bad type in map".to_string()); }
+        }
+        Ok(MapTable { keys, values, missing: default.map(|_| missing) })
+    }
+
+    fn find<T,Q>(&self, positions: &HashMap<T,usize>, key: &Q) -> Result<usize,String>
+            where T: hash::Hash + Eq + std::borrow::Borrow<Q>, Q: hash::Hash + Eq + std::fmt::Debug + ?Sized {
+        positions.get(key).cloned().or(self.missing).ok_or_else(|| format!("{:?} is not in map table",key))
+    }
+
+    fn indexes(&self, x: &Value) -> Result<Vec<usize>,String> {
+        Ok(match (&self.keys,x) {
+            (MapKeys::Boolean(p),Value::Boolean(k)) |
+            (MapKeys::Boolean(p),Value::InfiniteBoolean(k)) => vec![self.find(p,k)?],
+            (MapKeys::Boolean(p),Value::FiniteBoolean(k)) => k.iter().map(|k| self.find(p,k)).collect::<Result<_,_>>()?,
+            (MapKeys::Number(p),Value::Number(k)) |
+            (MapKeys::Number(p),Value::InfiniteNumber(k)) => vec![self.find(p,&OrderedFloat(*k))?],
+            (MapKeys::Number(p),Value::FiniteNumber(k)) => k.iter().map(|k| self.find(p,&OrderedFloat(*k))).collect::<Result<_,_>>()?,
+            (MapKeys::String(p),Value::String(k)) |
+            (MapKeys::String(p),Value::InfiniteString(k)) => vec![self.find(p,k.as_str())?],
+            (MapKeys::String(p),Value::FiniteString(k)) => k.iter().map(|k| self.find(p,k.as_str())).collect::<Result<_,_>>()?,
+            _ => { return Err("bad type in map".to_string()); }
+        })
+    }
+
+    fn lookup(&self, x: &Value) -> Result<Value,String> {
+        let idx = self.indexes(x)?;
+        Ok(match &self.values {
+            Value::FiniteBoolean(v) => map_pick!(x,idx,v,Boolean,InfiniteBoolean,FiniteBoolean),
+            Value::FiniteNumber(v) => map_pick!(x,idx,v,Number,InfiniteNumber,FiniteNumber),
+            Value::FiniteString(v) => map_pick!(x,idx,v,String,InfiniteString,FiniteString),
+            _ => { return Err("bad type in map".to_string()); }
+        })
+    }
+}
+
+/* The keys and values come from the constant pool, so the table is usually built just once. */
+fn map_op(constants: &[Option<&Value>], with_default: bool) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    let fixed = match (constants.get(2),constants.get(3),constants.get(4)) {
+        (Some(Some(keys)),Some(Some(values)),Some(Some(default))) if with_default => MapTable::new(keys,values,Some(default)).ok(),
+        (Some(Some(keys)),Some(Some(values)),_) if !with_default => MapTable::new(keys,values,None).ok(),
+        _ => None
+    };
+    Ok(Box::new(move |ctx,regs| {
+        let built;
+        let table = match &fixed {
+            Some(table) => table,
+            None => {
+                let default = if with_default { Some(ctx.get(regs[4])?) } else { None };
+                built = MapTable::new(ctx.get(regs[2])?,ctx.get(regs[3])?,default)?;
+                &built
+            }
+        };
+        let out = table.lookup(ctx.get(regs[1])?)?;
+        ctx.set(regs[0],out)?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_map(_gctx: &GlobalBuildContext, constants: &[Option<&Value>]) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    map_op(constants,true)
+}
+
+pub(crate) fn op_map_all(_gctx: &GlobalBuildContext, constants: &[Option<&Value>]) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    map_op(constants,false)
+}
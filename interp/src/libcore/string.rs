@@ -268,3 +268,184 @@ pub(crate) fn op_strlen_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut
         Ok(Return::Sync)
     }))
 }
+
+/* Applies cb to a string or, for seq, to each string in a sequence. */
+fn map_strings<F>(ctx: &mut GlobalContext, regs: &[usize], seq: bool, cb: F) -> Result<(),String>
+        where F: Fn(&str) -> String {
+    let value = if !seq {
+        Value::String(cb(ctx.force_string(regs[1])?))
+    } else if ctx.is_finite(regs[1])? {
+        Value::FiniteString(ctx.force_finite_string(regs[1])?.iter().map(|s| cb(s)).collect())
+    } else {
+        Value::InfiniteString(cb(ctx.force_infinite_string(regs[1])?))
+    };
+    ctx.set(regs[0],value)
+}
+
+fn test_strings<F>(ctx: &mut GlobalContext, regs: &[usize], seq: bool, cb: F) -> Result<(),String>
+        where F: Fn(&str) -> bool {
+    let value = if !seq {
+        Value::Boolean(cb(ctx.force_string(regs[1])?))
+    } else if ctx.is_finite(regs[1])? {
+        Value::FiniteBoolean(ctx.force_finite_string(regs[1])?.iter().map(|s| cb(s)).collect())
+    } else {
+        Value::InfiniteBoolean(cb(ctx.force_infinite_string(regs[1])?))
+    };
+    ctx.set(regs[0],value)
+}
+
+/* Positions are in characters. A negative start counts from the end. */
+fn substr(input: &str, start: f64, len: f64) -> String {
+    let chars = input.chars().count() as f64;
+    let start = if start < 0. { (chars+start).max(0.) } else { start.min(chars) };
+    input.chars().skip(start as usize).take(len.max(0.) as usize).collect()
+}
+
+fn padding(input: &str, width: f64, pad: &str) -> String {
+    let missing = (width.max(0.) as usize).saturating_sub(input.chars().count());
+    pad.chars().cycle().take(if !pad.is_empty() { missing } else { 0 }).collect()
+}
+
+pub(crate) fn op_substr(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let start = ctx.force_number(regs[2])?;
+        let len = ctx.force_number(regs[3])?;
+        map_strings(ctx,regs,false,|s| substr(s,start,len))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_substr_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let start = ctx.force_number(regs[2])?;
+        let len = ctx.force_number(regs[3])?;
+        map_strings(ctx,regs,true,|s| substr(s,start,len))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_upper(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        map_strings(ctx,regs,false,|s| s.to_uppercase())?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_upper_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        map_strings(ctx,regs,true,|s| s.to_uppercase())?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_lower(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        map_strings(ctx,regs,false,|s| s.to_lowercase())?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_lower_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        map_strings(ctx,regs,true,|s| s.to_lowercase())?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_trim(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        map_strings(ctx,regs,false,|s| s.trim().to_string())?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_trim_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        map_strings(ctx,regs,true,|s| s.trim().to_string())?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_pad_start(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let width = ctx.force_number(regs[2])?;
+        let pad = ctx.force_string(regs[3])?.to_string();
+        map_strings(ctx,regs,false,|s| format!("{}{}",padding(s,width,&pad),s))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_pad_start_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let width = ctx.force_number(regs[2])?;
+        let pad = ctx.force_string(regs[3])?.to_string();
+        map_strings(ctx,regs,true,|s| format!("{}{}",padding(s,width,&pad),s))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_pad_end(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let width = ctx.force_number(regs[2])?;
+        let pad = ctx.force_string(regs[3])?.to_string();
+        map_strings(ctx,regs,false,|s| format!("{}{}",s,padding(s,width,&pad)))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_pad_end_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let width = ctx.force_number(regs[2])?;
+        let pad = ctx.force_string(regs[3])?.to_string();
+        map_strings(ctx,regs,true,|s| format!("{}{}",s,padding(s,width,&pad)))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_starts_with(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let prefix = ctx.force_string(regs[2])?.to_string();
+        test_strings(ctx,regs,false,|s| s.starts_with(&prefix))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_starts_with_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let prefix = ctx.force_string(regs[2])?.to_string();
+        test_strings(ctx,regs,true,|s| s.starts_with(&prefix))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_ends_with(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let suffix = ctx.force_string(regs[2])?.to_string();
+        test_strings(ctx,regs,false,|s| s.ends_with(&suffix))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_ends_with_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let suffix = ctx.force_string(regs[2])?.to_string();
+        test_strings(ctx,regs,true,|s| s.ends_with(&suffix))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_contains(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let needle = ctx.force_string(regs[2])?.to_string();
+        test_strings(ctx,regs,false,|s| s.contains(&needle))?;
+        Ok(Return::Sync)
+    }))
+}
+
+pub(crate) fn op_contains_s(_gctx: &GlobalBuildContext) -> Result<Box<dyn Fn(&mut GlobalContext,&[usize]) -> Result<Return,String>>,String> {
+    Ok(Box::new(move |ctx,regs| {
+        let needle = ctx.force_string(regs[2])?.to_string();
+        test_strings(ctx,regs,true,|s| s.contains(&needle))?;
+        Ok(Return::Sync)
+    }))
+}
@@ -1,6 +1,6 @@
 use std::{time::Duration, sync::{Arc, Mutex}, mem, pin::Pin, future::Future, collections::HashSet};
 use async_std::task::{self, block_on};
-use crate::{controller::{interpreter::{Interpreter, InterpreterBuilder}, objectcode::{ProgramName, ObjectFile}, context::RunContext}, libcore::libcore::{LibcoreTemplate, build_libcore, prepare_libcore, LibcoreBuilder}};
+use crate::{controller::{interpreter::{Interpreter, InterpreterBuilder}, objectcode::{ProgramName, ObjectFile}, context::RunContext, debugger::{DebugHook, StepView}, profile::Profile, operation::{Operation, Return}, value::Value}, libcore::libcore::{LibcoreTemplate, build_libcore, prepare_libcore, LibcoreBuilder}};
 
 #[derive(Clone)]
 struct LibcoreTest {
@@ -180,7 +180,27 @@ fn test_load() {
         "1", "[2,2,0]",
         "5", "[5,0,1,-1]",
         "3", "[1,2,3]",
-        "3", "[1,2,3]"
+        "3", "[1,2,3]",
+        "\"00000\"", "[\"r1\",\"13\"]", "[\"BRCA2\",\"TP53\"]", "\"brca2\"", "[\"gene\",\"x\"]",
+        "\"007\"", "[\"a-.-\",\"bb-.\"]", "true", "[false,true]", "[true,false]",
+        "[true,true,false]", "true", "[\"ENSG\",\"ENST\",\"\"]", "[\"17\",\"8\",\"\"]",
+        "[\"ENSG00000139618\",\"ENST00000380152\",\"rs1234\"]", "\"protein_coding (gene_biotype)\"",
+        "2", "[3,-2]", "[3,-3,0]", "[1,...]", "[0,3]", "0", "[1,...]", "1024", "[1,4,9]", "[8,9]", "[2,4,8]",
+        "[0,5,10]", "[-1,...]",
+        "450", "90", "[100,150,350,375,450]", "[150,0,300]",
+        "[2,4,0,1,3]", "[1,3,0,4,2]", "[9,9,5,3,1]", "[1,3,0,2]", "[1,5,9,9]",
+        "[\"A\",\"C\"]", "[1,1]", "[]", "[\"X\",\"B\",\"Y\",\"D\"]", "[0,2,0,4]",
+        "[10,30,50]", "[25,45,60]", "[1,0,0,2,1]", "[0,0,1,1]", "[0,3,2,3]", "[0,0,1,0,2]", "[3,1,0,2]",
+        "nACGTT", "[\"CGT\",\"TAA\"]", "MA*", "MA*", "[\"MW\",\"FX\"]", "[1,0,0]", "[1,0,1,1]", "[0,0,1,1]",
+        "[\"B\",\"A\",\"C\"]", "[0,1,0,2,1]", "[2,1]", "[0,0,1]", "[true,false,true]", "[3,1,2]", "[\"A\",\"C\"]", "[true]",
+        "[\"X\"]", "[0,...]", "[true,...]", "[true,false]", "[3,2]", "[1]",
+        "1,234,567.89", "3.4e-5", "[\"1.2 Mb\",\"350 kb\",\"-2.5 kb\"]", "[\"0.5%\",...]", "1.23e8",
+        "100000000000000000000", "100000000000000000000", "-100,000,000,000,000,000,000", "-100,000,000,000,000,000,000",
+        "0.5", "0.5", "100000000000000000000", "100000000000000000000", "0",
+        "[10,15,25,35]", "[\"a\",\"B\",\"C\",\"D\"]", "2",
+        "[10,15]", "[6,7]",
+        "[15,25,35]", "[1]", "narrow",
+        "[\"red\",\"blue\",\"grey\",\"red\"]", "[1,2,3,1]", "false"
         ],out);
 }
 
@@ -226,3 +246,113 @@ fn test_check() {
         run_check(&interp,&libcore,program,compare);
     }
 }
+
+#[test]
+fn test_disassemble() {
+    let (interp,_) = prepare_interpreter();
+    let listing = interp.disassemble(include_bytes!("debug.eardo")).expect("disassembling");
+    assert_eq!(include_str!("debug.dis"),listing);
+}
+
+#[test]
+fn test_debug_locations() {
+    let (mut interp,libcore) = prepare_interpreter();
+    interp.load(include_bytes!("debug.eardo")).expect("adding");
+    let out = run_interpreter(&interp,&libcore,"main");
+    assert_eq!(vec!["cowardly refusing to generate stupidly sized array at cond:97 (called from debug.eard:5) (called from debug.eard:11)".to_string()],out);
+    /* without a debug section, errors are as before */
+    let (mut interp,_) = prepare_interpreter();
+    interp.load(include_bytes!("smoke.eardo")).expect("adding");
+    let program = interp.get(&ProgramName::new("group","program",1),"main").expect("load failed");
+    assert_eq!(None,program.location(0));
+}
+
+struct RecordingHook {
+    seen: Vec<String>,
+    stop_at: usize
+}
+
+impl DebugHook for RecordingHook {
+    fn before(&mut self, view: &StepView) -> bool {
+        if view.index == self.stop_at { return false; }
+        self.seen.push(format!("{} {} {}",view.index,view.name.unwrap_or("?"),view.location().unwrap_or_default()));
+        true
+    }
+
+    fn after(&mut self, view: &StepView) {
+        if view.opcode == 0 {
+            self.seen.push(format!("  r{} = {:?}",view.registers[0],view.value(view.registers[0]).expect("missing register")));
+        }
+    }
+}
+
+#[test]
+fn test_debug_hook() {
+    let (mut interp,libcore) = prepare_interpreter();
+    interp.load(include_bytes!("debug.eardo")).expect("adding");
+    let program = interp.get(&ProgramName::new("group","program",1),"main").expect("load failed");
+    let mut hook = RecordingHook { seen: vec![], stop_at: 0 };
+    let mut context = RunContext::new();
+    prepare_libcore(&mut context,&libcore,LibcoreTest { printed: Arc::new(Mutex::new(vec![])), asyncs: Arc::new(Mutex::new(0)) });
+    assert!(block_on(program.run_debug(context,&mut hook)).is_err());
+    let mut builder = InterpreterBuilder::new();
+    builder.set_step_by_step(true);
+    let libcore = build_libcore(&mut builder).expect("build failed");
+    let mut interp = Interpreter::new(builder);
+    interp.load(include_bytes!("debug.eardo")).expect("adding");
+    let program = interp.get(&ProgramName::new("group","program",1),"main").expect("load failed");
+    let mut context = RunContext::new();
+    prepare_libcore(&mut context,&libcore,LibcoreTest { printed: Arc::new(Mutex::new(vec![])), asyncs: Arc::new(Mutex::new(0)) });
+    let mut hook = RecordingHook { seen: vec![], stop_at: 2 };
+    block_on(program.run_debug(context,&mut hook)).expect("run failed");
+    assert_eq!(vec![
+        "0 const debug.eard:6 (called from debug.eard:11)",
+        "  r1 = Number(2000000000.0)",
+        "1 copy debug.eard:6 (called from debug.eard:11)"
+    ],hook.seen);
+}
+
+#[test]
+fn test_profile() {
+    let mut builder = InterpreterBuilder::new();
+    builder.set_step_by_step(true);
+    let libcore = build_libcore(&mut builder).expect("build failed");
+    let mut interp = Interpreter::new(builder);
+    interp.load(include_bytes!("smoke.eardo")).expect("adding");
+    let program = interp.get(&ProgramName::new("group","program",1),"main").expect("load failed");
+    let mut profile = Profile::new();
+    for _ in 0..2 {
+        let mut context = RunContext::new();
+        prepare_libcore(&mut context,&libcore,LibcoreTest { printed: Arc::new(Mutex::new(vec![])), asyncs: Arc::new(Mutex::new(0)) });
+        block_on(program.run_profile(context,&mut profile)).expect("run failed");
+    }
+    assert_eq!(2*program.step_count() as u64,profile.total().calls);
+    assert!(profile.steps().iter().all(|s| s.entry.calls == 2));
+    let opcodes = profile.opcodes();
+    let print = opcodes.iter().find(|((_,name),_)| name.as_deref() == Some("print")).expect("no print");
+    assert_eq!(137,(print.0).0);
+    assert!(profile.lines().is_empty()); /* no debug info */
+    let json = profile.to_json();
+    assert_eq!(json["total"]["calls"].as_u64(),Some(profile.total().calls));
+    assert_eq!(json["steps"].as_array().map(|x| x.len()),Some(program.step_count()));
+    assert!(profile.report(5).contains(" 137 print"));
+}
+
+#[test]
+fn test_constant_arguments() {
+    let mut builder = InterpreterBuilder::new();
+    build_libcore(&mut builder).expect("build failed");
+    let seen = Arc::new(Mutex::new(vec![]));
+    let seen2 = seen.clone();
+    builder.add_operation_named(183,"regex_match seq",Operation::new_with_constants(move |_,constants| {
+        seen2.lock().unwrap().push(constants.iter().map(|c| c.cloned()).collect::<Vec<_>>());
+        Ok(Box::new(|_,_| Ok(Return::Sync)))
+    }));
+    let mut interp = Interpreter::new(builder);
+    interp.load(include_bytes!("smoke.eardo")).expect("adding");
+    let seen = seen.lock().unwrap();
+    assert_eq!(1,seen.len());
+    assert!(seen[0][0].is_none()); /* output */
+    assert!(seen[0][1].is_none()); /* result of world code */
+    assert!(matches!(&seen[0][2],Some(Value::String(s)) if s == "^ENS[GT]"));
+}
@@ -17,23 +17,23 @@ pub fn build_libeoe(builder: &mut InterpreterBuilder) -> Result<LibEoEBuilder,St
     let pairs = builder.add_context::<HandleStore<StructPair>>("eoepairs")?;
     let vars = builder.add_context::<HandleStore<StructVar>>("eoevars")?;
     builder.add_version("libeoe",(0,0));
-    builder.add_operation(512,Operation::new(op_boolean));
-    builder.add_operation(513,Operation::new(op_number));
-    builder.add_operation(514,Operation::new(op_string));
-    builder.add_operation(515,Operation::new(op_null));
-    builder.add_operation(516,Operation::new(op_group));
-    builder.add_operation(517,Operation::new(op_var_boolean));
-    builder.add_operation(518,Operation::new(op_var_number));
-    builder.add_operation(519,Operation::new(op_var_string));
-    builder.add_operation(520,Operation::new(op_array));
-    builder.add_operation(521,Operation::new(op_pair));
-    builder.add_operation(522,Operation::new(op_object));
-    builder.add_operation(523,Operation::new(op_var));
-    builder.add_operation(524,Operation::new(op_all));
-    builder.add_operation(525,Operation::new(op_condition));
-    builder.add_operation(526,Operation::new(op_boolean_s));
-    builder.add_operation(527,Operation::new(op_number_s));
-    builder.add_operation(528,Operation::new(op_string_s));
+    builder.add_operation_named(512,"eoe_boolean",Operation::new(op_boolean));
+    builder.add_operation_named(513,"eoe_number",Operation::new(op_number));
+    builder.add_operation_named(514,"eoe_string",Operation::new(op_string));
+    builder.add_operation_named(515,"eoe_null",Operation::new(op_null));
+    builder.add_operation_named(516,"eoe_group",Operation::new(op_group));
+    builder.add_operation_named(517,"eoe_var_boolean",Operation::new(op_var_boolean));
+    builder.add_operation_named(518,"eoe_var_number",Operation::new(op_var_number));
+    builder.add_operation_named(519,"eoe_var_string",Operation::new(op_var_string));
+    builder.add_operation_named(520,"eoe_array",Operation::new(op_array));
+    builder.add_operation_named(521,"eoe_pair",Operation::new(op_pair));
+    builder.add_operation_named(522,"eoe_object",Operation::new(op_object));
+    builder.add_operation_named(523,"eoe_var",Operation::new(op_var));
+    builder.add_operation_named(524,"eoe_all",Operation::new(op_all));
+    builder.add_operation_named(525,"eoe_condition",Operation::new(op_condition));
+    builder.add_operation_named(526,"eoe_boolean seq",Operation::new(op_boolean_s));
+    builder.add_operation_named(527,"eoe_number seq",Operation::new(op_number_s));
+    builder.add_operation_named(528,"eoe_string seq",Operation::new(op_string_s));
     Ok(LibEoEBuilder { templates, groups, pairs, vars })
 }
 
@@ -90,3 +90,15 @@ fn test_load() {
         "Aabcd.( {\"arabic\": a=<2.0,3.0,5.0,7.0,11.0>,\"roman\": b=<\"ii\",\"iii\",\"v\",\"vii\",\"xi\">,\"type\": Q[c=<true,false,false,false,false>] (\"even\" ),\"type\": Q[d=<false,true,true,true,true>] (\"odd\" )} ) /// [{\"arabic\":2.0,\"roman\":\"ii\",\"type\":\"even\"},{\"arabic\":3.0,\"roman\":\"iii\",\"type\":\"odd\"},{\"arabic\":5.0,\"roman\":\"v\",\"type\":\"odd\"},{\"arabic\":7.0,\"roman\":\"vii\",\"type\":\"odd\"},{\"arabic\":11.0,\"roman\":\"xi\",\"type\":\"odd\"}]"
     ],out);
 }
+
+#[test]
+fn test_disassemble() {
+    let (interp,_,_) = prepare_interpreter();
+    let listing = interp.disassemble(include_bytes!("smoke.eardo")).expect("disassembling");
+    assert!(listing.contains(" 512 eoe_boolean "));
+    assert!(listing.contains(" 520 eoe_array "));
+    /* only the test's own opcode is unnamed */
+    for line in listing.lines().filter(|x| x.contains("*unknown*")) {
+        assert!(line.contains(" 9999 "),"unnamed opcode: {}",line);
+    }
+}
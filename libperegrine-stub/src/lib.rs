@@ -7,6 +7,6 @@ mod opsetting;
 mod opshape;
 mod util;
 
-pub use crate::register::{ build_libperegrine, prepare_libperegrine };
+pub use crate::register::{ build_libperegrine, prepare_libperegrine, LibPeregrineBuilder };
 pub use crate::stubs::StubDump;
 pub use crate::data::StubResponses;
\ No newline at end of file
@@ -25,51 +25,51 @@ pub fn build_libperegrine(builder: &mut InterpreterBuilder) -> Result<LibPeregri
     let graph_types = builder.add_context::<HandleStore<Plotter>>("graph-types")?;
     let pens = builder.add_context::<HandleStore<Pen>>("pens")?;
     builder.add_version("libperegrine",(0,0));
-    builder.add_operation(256,Operation::new(op_leaf));
-    builder.add_operation(257,Operation::new(op_leaf_s));
-    builder.add_operation(258,Operation::new(op_style));
-    builder.add_operation(259,Operation::new(op_colour));
-    builder.add_operation(260,Operation::new(op_paint_solid));
-    builder.add_operation(261,Operation::new(op_paint_solid_s));
-    builder.add_operation(262,Operation::new(op_coord));
-    builder.add_operation(263,Operation::new(op_rectangle));
-    builder.add_operation(264,Operation::new(op_request));
-    builder.add_operation(265,Operation::new(op_scope));
-    builder.add_operation(266,Operation::new(op_get_data));
-    builder.add_operation(267,Operation::new(op_data_boolean));
-    builder.add_operation(268,Operation::new(op_data_number));
-    builder.add_operation(269,Operation::new(op_data_string));
-    builder.add_operation(270,Operation::new(op_graph_type));
-    builder.add_operation(271,Operation::new(op_wiggle));
-    builder.add_operation(272,Operation::new(op_setting_boolean));
-    builder.add_operation(273,Operation::new(op_setting_number));
-    builder.add_operation(274,Operation::new(op_setting_string));
-    builder.add_operation(275,Operation::new(op_setting_boolean_seq));
-    builder.add_operation(276,Operation::new(op_setting_number_seq));
-    builder.add_operation(277,Operation::new(op_setting_string_seq));
-    builder.add_operation(278,Operation::new(op_pen));
-    builder.add_operation(279,Operation::new(op_text));
-    builder.add_operation(280,Operation::new(op_paint_hollow));
-    builder.add_operation(281,Operation::new(op_paint_hollow_s));
-    builder.add_operation(282,Operation::new(op_bp_range));
-    builder.add_operation(283,Operation::new(op_paint_special));
-    builder.add_operation(284,Operation::new(op_image));
-    builder.add_operation(285,Operation::new(op_running_text));
-    builder.add_operation(286,Operation::new(op_zmenu));
-    builder.add_operation(287,Operation::new(op_paint_dotted));
-    builder.add_operation(288,Operation::new(op_empty));
-    builder.add_operation(289,Operation::new(op_paint_metadata));
-    builder.add_operation(290,Operation::new(op_paint_setting));
-    builder.add_operation(291,Operation::new(op_setting_boolean_keys));
-    builder.add_operation(292,Operation::new(op_setting_number_keys));
-    builder.add_operation(293,Operation::new(op_setting_string_keys));
-    builder.add_operation(294,Operation::new(op_scope_s));
-    builder.add_operation(295,Operation::new(op_running_rectangle));
-    builder.add_operation(296,Operation::new(op_small_value));
-    builder.add_operation(297,Operation::new(op_only_warm));
-    builder.add_operation(298,Operation::new(op_stick));
-    builder.add_operation(299,Operation::new(op_rectangle_join));
-    builder.add_operation(300,Operation::new(op_polygon));
+    builder.add_operation_named(256,"leaf",Operation::new(op_leaf));
+    builder.add_operation_named(257,"leaf seq",Operation::new(op_leaf_s));
+    builder.add_operation_named(258,"style",Operation::new(op_style));
+    builder.add_operation_named(259,"colour",Operation::new(op_colour));
+    builder.add_operation_named(260,"paint_solid",Operation::new(op_paint_solid));
+    builder.add_operation_named(261,"paint_solid seq",Operation::new(op_paint_solid_s));
+    builder.add_operation_named(262,"coord",Operation::new(op_coord));
+    builder.add_operation_named(263,"rectangle",Operation::new(op_rectangle));
+    builder.add_operation_named(264,"request",Operation::new(op_request));
+    builder.add_operation_named(265,"scope",Operation::new(op_scope));
+    builder.add_operation_named(266,"get_data",Operation::new(op_get_data));
+    builder.add_operation_named(267,"data_boolean",Operation::new(op_data_boolean));
+    builder.add_operation_named(268,"data_number",Operation::new(op_data_number));
+    builder.add_operation_named(269,"data_string",Operation::new(op_data_string));
+    builder.add_operation_named(270,"graph_type",Operation::new(op_graph_type));
+    builder.add_operation_named(271,"wiggle",Operation::new(op_wiggle));
+    builder.add_operation_named(272,"setting_boolean",Operation::new(op_setting_boolean));
+    builder.add_operation_named(273,"setting_number",Operation::new(op_setting_number));
+    builder.add_operation_named(274,"setting_string",Operation::new(op_setting_string));
+    builder.add_operation_named(275,"setting_boolean_seq",Operation::new(op_setting_boolean_seq));
+    builder.add_operation_named(276,"setting_number_seq",Operation::new(op_setting_number_seq));
+    builder.add_operation_named(277,"setting_string_seq",Operation::new(op_setting_string_seq));
+    builder.add_operation_named(278,"pen",Operation::new(op_pen));
+    builder.add_operation_named(279,"text",Operation::new(op_text));
+    builder.add_operation_named(280,"paint_hollow",Operation::new(op_paint_hollow));
+    builder.add_operation_named(281,"paint_hollow seq",Operation::new(op_paint_hollow_s));
+    builder.add_operation_named(282,"bp_range",Operation::new(op_bp_range));
+    builder.add_operation_named(283,"paint_special",Operation::new(op_paint_special));
+    builder.add_operation_named(284,"image",Operation::new(op_image));
+    builder.add_operation_named(285,"running_text",Operation::new(op_running_text));
+    builder.add_operation_named(286,"zmenu",Operation::new(op_zmenu));
+    builder.add_operation_named(287,"paint_dotted",Operation::new(op_paint_dotted));
+    builder.add_operation_named(288,"empty",Operation::new(op_empty));
+    builder.add_operation_named(289,"paint_metadata",Operation::new(op_paint_metadata));
+    builder.add_operation_named(290,"paint_setting",Operation::new(op_paint_setting));
+    builder.add_operation_named(291,"setting_boolean_keys",Operation::new(op_setting_boolean_keys));
+    builder.add_operation_named(292,"setting_number_keys",Operation::new(op_setting_number_keys));
+    builder.add_operation_named(293,"setting_string_keys",Operation::new(op_setting_string_keys));
+    builder.add_operation_named(294,"scope seq",Operation::new(op_scope_s));
+    builder.add_operation_named(295,"running_rectangle",Operation::new(op_running_rectangle));
+    builder.add_operation_named(296,"small_value",Operation::new(op_small_value));
+    builder.add_operation_named(297,"only_warm",Operation::new(op_only_warm));
+    builder.add_operation_named(298,"stick",Operation::new(op_stick));
+    builder.add_operation_named(299,"rectangle_join",Operation::new(op_rectangle_join));
+    builder.add_operation_named(300,"polygon",Operation::new(op_polygon));
     Ok(LibPeregrineBuilder {
         leafs, shapes, colours, paint, coords, requests, responses, graph_types, pens
     })
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+const LIBRARY_REF : &str = include_str!("../../docs/library-ref-source.txt");
+
+pub(crate) struct DocEntry {
+    section: String,
+    signatures: Vec<String>,
+    args: Vec<String>,
+    results: Vec<String>,
+    text: Vec<String>
+}
+
+impl DocEntry {
+    fn new(section: &str) -> DocEntry {
+        DocEntry { section: section.to_string(), signatures: vec![], args: vec![], results: vec![], text: vec![] }
+    }
+
+    pub(crate) fn markdown(&self) -> String {
+        let mut out = vec![];
+        if !self.signatures.is_empty() {
+            out.push(format!("```\n{}\n```",self.signatures.join("\n")));
+        }
+        for (i,arg) in self.args.iter().enumerate() {
+            out.push(format!("* arg {}: {}",i+1,arg));
+        }
+        for (i,res) in self.results.iter().enumerate() {
+            out.push(format!("* result {}: {}",i+1,res));
+        }
+        let text = self.text.join("\n");
+        let text = text.trim();
+        if !text.is_empty() {
+            out.push(text.to_string());
+        }
+        out.push(format!("_{}_",self.section));
+        out.join("\n\n")
+    }
+}
+
+pub(crate) struct LibraryDocs {
+    entries: HashMap<String,DocEntry>
+}
+
+impl LibraryDocs {
+    pub(crate) fn new() -> LibraryDocs {
+        let mut entries = HashMap::new();
+        let mut section = String::new();
+        let mut current : Option<(String,DocEntry)> = None;
+        for line in LIBRARY_REF.lines() {
+            if let Some(directive) = line.strip_prefix("> ") {
+                let (key,value) = directive.split_once(' ').unwrap_or((directive,""));
+                let value = value.trim();
+                match (key,current.as_mut()) {
+                    ("name",_) => {
+                        if let Some((name,entry)) = current.take() {
+                            entries.insert(name,entry);
+                        }
+                        current = Some((value.to_string(),DocEntry::new(&section)));
+                    },
+                    ("section",_) => {
+                        if let Some((name,entry)) = current.take() {
+                            entries.insert(name,entry);
+                        }
+                        section = value.to_string();
+                    },
+                    ("signature",Some((_,entry))) => { entry.signatures.push(value.to_string()); },
+                    ("arg",Some((_,entry))) => { entry.args.push(strip_index(value)); },
+                    ("res",Some((_,entry))) => { entry.results.push(strip_index(value)); },
+                    _ => {}
+                }
+            } else if let Some((_,entry)) = current.as_mut() {
+                entry.text.push(line.to_string());
+            }
+        }
+        if let Some((name,entry)) = current.take() {
+            entries.insert(name,entry);
+        }
+        LibraryDocs { entries }
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&DocEntry> {
+        self.entries.get(name)
+    }
+}
+
+/* "> arg 2 new value" gives "2 new value" */
+fn strip_index(value: &str) -> String {
+    value.split_once(' ').map(|(_,rest)| rest).unwrap_or(value).to_string()
+}
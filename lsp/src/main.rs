@@ -0,0 +1,60 @@
+mod docs;
+mod server;
+
+use std::process::exit;
+use lsp_server::{Connection, Message};
+use lsp_types::{CompletionOptions, HoverProviderCapability, OneOf, SaveOptions, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions, TextDocumentSyncSaveOptions};
+use server::Server;
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
+            open_close: Some(true),
+            change: Some(TextDocumentSyncKind::FULL),
+            save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions { include_text: Some(false) })),
+            ..Default::default()
+        })),
+        definition_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions::default()),
+        ..Default::default()
+    }
+}
+
+fn do_it() -> Result<(),String> {
+    let (connection,io_threads) = Connection::stdio();
+    let capabilities = serde_json::to_value(capabilities()).map_err(|e| e.to_string())?;
+    connection.initialize(capabilities).map_err(|e| format!("cannot initialise: {}",e))?;
+    let mut server = Server::new(connection.sender.clone())?;
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request).map_err(|e| e.to_string())? {
+                    break;
+                }
+                server.request(request);
+            },
+            Message::Notification(notification) => { server.notification(notification); },
+            Message::Response(_) => {}
+        }
+    }
+    drop(server);
+    drop(connection);
+    io_threads.join().map_err(|e| format!("cannot shut down: {}",e))?;
+    Ok(())
+}
+
+fn main() {
+    match do_it() {
+        Ok(()) => { exit(0); }
+        Err(e) => {
+            eprintln!("{}",e);
+            exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    mod test;
+}
@@ -0,0 +1,323 @@
+use std::{collections::{HashMap, HashSet}, path::Path};
+use crossbeam_channel::Sender;
+use lsp_server::{ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{CompletionItem, CompletionItemKind, CompletionResponse, Diagnostic as LspDiagnostic, DiagnosticSeverity, GotoDefinitionResponse, Hover, HoverContents, Location, MarkupContent, MarkupKind, NumberOrString, Position, PublishDiagnosticsParams, Range, TextDocumentPositionParams, Url};
+use eard_compiler_lib::{Definition, DefinitionKind, Diagnostic, EardCompilation, EardCompiler, Severity, SourceSpan};
+use eard_compiler_libeoe::libeoe_add;
+use eard_compiler_libperegrine::libperegrine_add;
+use crate::docs::LibraryDocs;
+
+const LIBRARIES : [&str;3] = ["libcore","libperegrine","libeoe"];
+
+fn make_compiler() -> Result<EardCompiler,String> {
+    let mut compiler = EardCompiler::new()?;
+    libperegrine_add(&mut compiler)?;
+    libeoe_add(&mut compiler)?;
+    Ok(compiler)
+}
+
+/* LSP positions count UTF-16 code units along a line, where eard counts chars */
+fn utf16_column(line: &str, chars: usize) -> usize {
+    line.chars().take(chars).map(|c| c.len_utf16()).sum()
+}
+
+fn char_column(line: &str, units: usize) -> usize {
+    let mut seen = 0;
+    line.chars().take_while(|c| { seen += c.len_utf16(); seen <= units }).count()
+}
+
+fn span_position(span: &SourceSpan, text: Option<&str>) -> Position {
+    let line = span.line.max(1)-1;
+    let column = (span.column.max(1)-1) as usize;
+    let column = match span.source.as_deref().or_else(|| text.and_then(|t| t.lines().nth(line as usize))) {
+        Some(source) => utf16_column(source,column),
+        None => column
+    };
+    Position::new(line,column as u32)
+}
+
+fn to_lsp_diagnostic(diagnostic: &Diagnostic, range: Range) -> LspDiagnostic {
+    let severity = match diagnostic.severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING
+    };
+    let mut message = diagnostic.message.clone();
+    for note in &diagnostic.notes {
+        message.push_str(&format!("\nnote: {}",note));
+    }
+    LspDiagnostic {
+        range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String(diagnostic.code.as_str().to_string())),
+        source: Some("eard".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+fn word_at(text: &str, pos: &Position) -> Option<String> {
+    let line = text.lines().nth(pos.line as usize)?;
+    let chars = line.chars().collect::<Vec<_>>();
+    /* includes dots so as to keep qualified names such as gu.label and tr.start whole */
+    let is_word = |c: &char| c.is_alphanumeric() || *c == '_' || *c == '.';
+    let at = char_column(line,pos.character as usize);
+    let mut start = at;
+    while start > 0 && is_word(&chars[start-1]) { start -= 1; }
+    let mut end = at;
+    while end < chars.len() && is_word(&chars[end]) { end += 1; }
+    let word = chars[start..end].iter().collect::<String>();
+    let word = word.trim_matches('.');
+    if word.is_empty() { return None; }
+    Some(word.to_string())
+}
+
+/* A name is plain, or alias.name for an export of a file included with an alias, or else
+ * var.field for a field of a record. The variable's record isn't known here, so every record
+ * with such a field is a match.
+ */
+fn find<'a>(defs: impl Iterator<Item=&'a Definition>, word: &str) -> Vec<&'a Definition> {
+    let defs = defs.collect::<Vec<_>>();
+    let (prefix,name) = match word.rsplit_once('.') {
+        Some(parts) => parts,
+        None => { return defs.into_iter().filter(|d| d.name == word).collect(); }
+    };
+    let qualified = defs.iter().filter(|d| d.name == name && d.namespaces.iter().any(|n| n == prefix)).cloned().collect::<Vec<_>>();
+    if !qualified.is_empty() { return qualified; }
+    defs.into_iter().filter(|d| d.kind == DefinitionKind::Record && d.fields.iter().any(|f| f == name)).collect()
+}
+
+fn completion_kind(kind: DefinitionKind) -> CompletionItemKind {
+    match kind {
+        DefinitionKind::Function => CompletionItemKind::FUNCTION,
+        DefinitionKind::Procedure => CompletionItemKind::METHOD,
+        DefinitionKind::Code => CompletionItemKind::OPERATOR,
+        DefinitionKind::Const => CompletionItemKind::CONSTANT,
+        DefinitionKind::Record => CompletionItemKind::STRUCT
+    }
+}
+
+pub(crate) struct Server {
+    sender: Sender<Message>,
+    compiler: EardCompiler,
+    docs: LibraryDocs,
+    library: Vec<Definition>,
+    library_paths: HashSet<String>,
+    open: HashMap<Url,String>,
+    published: HashMap<Url,HashSet<Url>>
+}
+
+impl Server {
+    pub(crate) fn new(sender: Sender<Message>) -> Result<Server,String> {
+        let compiler = make_compiler()?;
+        let mut library = vec![];
+        for name in &LIBRARIES {
+            let mut compilation = EardCompilation::new(&compiler)?;
+            let defs = compilation.definitions(name,true).map_err(|e| e.render())?;
+            library.extend(defs.into_iter().filter(|d| d.exported));
+        }
+        let library_paths = library.iter().filter_map(|d| d.span.as_ref().and_then(|s| s.path.clone())).collect();
+        Ok(Server {
+            sender, compiler, library, library_paths,
+            docs: LibraryDocs::new(),
+            open: HashMap::new(),
+            published: HashMap::new()
+        })
+    }
+
+    fn send(&self, message: Message) {
+        self.sender.send(message).ok();
+    }
+
+    /* Open files are compiled as they are in the editor, not as last saved. */
+    fn compilation(&self) -> Result<EardCompilation<'_>,String> {
+        let mut compilation = EardCompilation::new(&self.compiler)?;
+        for (uri,text) in &self.open {
+            if let Ok(path) = uri.to_file_path() {
+                compilation.add_buffer(&path,text);
+            }
+        }
+        Ok(compilation)
+    }
+
+    fn definitions(&self, uri: &Url) -> Vec<Definition> {
+        let path = match uri.to_file_path() { Ok(p) => p, Err(_) => { return vec![]; } };
+        let mut compilation = match self.compilation() {
+            Ok(c) => c,
+            Err(_) => { return vec![]; }
+        };
+        compilation.definitions(&path.to_string_lossy(),false).unwrap_or_default()
+    }
+
+    /* Definitions which can be referenced by name from inside the given file. */
+    fn visible(&self, uri: &Url) -> Vec<Definition> {
+        let path = uri.to_file_path().ok();
+        self.definitions(uri).into_iter().filter(|d| {
+            let here = d.span.as_ref().and_then(|s| s.path.as_ref()).map(|p| Some(Path::new(p)) == path.as_deref()).unwrap_or(false);
+            here || (d.exported && d.kind != DefinitionKind::Code)
+        }).collect()
+    }
+
+    /* A file as it is in the editor, if open, else as it is on disk. */
+    fn text(&self, uri: &Url) -> Option<String> {
+        match self.open.get(uri) {
+            Some(text) => Some(text.clone()),
+            None => std::fs::read_to_string(uri.to_file_path().ok()?).ok()
+        }
+    }
+
+    fn span_location(&self, span: &SourceSpan) -> Option<Location> {
+        let uri = Url::from_file_path(span.path.as_ref()?).ok()?;
+        let pos = span_position(span,self.text(&uri).as_deref());
+        Some(Location::new(uri,Range::new(pos,pos)))
+    }
+
+    /* The innermost position of a diagnostic which is in a file being worked on, eg the include
+     * of a library rather than the library itself.
+     */
+    fn diagnostic_location(&self, diagnostic: &Diagnostic) -> Option<Location> {
+        diagnostic.span.as_deref().into_iter().chain(diagnostic.chain.iter())
+            .filter(|s| s.path.as_ref().map(|p| !self.library_paths.contains(p)).unwrap_or(false))
+            .filter_map(|s| self.span_location(s)).next()
+    }
+
+    fn word(&self, params: &TextDocumentPositionParams) -> Option<String> {
+        let text = self.text(&params.text_document.uri)?;
+        word_at(&text,&params.position)
+    }
+
+    fn check(&mut self, uri: &Url) {
+        let path = match uri.to_file_path() { Ok(p) => p, Err(_) => { return; } };
+        let mut by_file : HashMap<Url,Vec<LspDiagnostic>> = HashMap::new();
+        by_file.insert(uri.clone(),vec![]);
+        /* most library files have no program header, and are only ever included */
+        let result = self.compilation().map_err(|e| Diagnostic::from(e).into())
+            .and_then(|mut c| c.check_include(&path.to_string_lossy()));
+        if let Err(diagnostics) = result {
+            for diagnostic in diagnostics.iter() {
+                let location = self.diagnostic_location(diagnostic)
+                    .unwrap_or_else(|| Location::new(uri.clone(),Range::default()));
+                let lsp = to_lsp_diagnostic(diagnostic,location.range);
+                by_file.entry(location.uri).or_default().push(lsp);
+            }
+        }
+        /* clear out files which had errors last time but don't now */
+        let files = by_file.keys().cloned().collect::<HashSet<_>>();
+        if let Some(old) = self.published.insert(uri.clone(),files) {
+            for old_uri in old {
+                by_file.entry(old_uri).or_default();
+            }
+        }
+        for (uri,diagnostics) in by_file {
+            let params = PublishDiagnosticsParams::new(uri,diagnostics,None);
+            self.send(Message::Notification(Notification::new("textDocument/publishDiagnostics".to_string(),params)));
+        }
+    }
+
+    fn goto_definition(&self, params: &TextDocumentPositionParams) -> Option<GotoDefinitionResponse> {
+        let word = self.word(params)?;
+        let visible = self.visible(&params.text_document.uri);
+        let locations = find(visible.iter(),&word).iter()
+            .filter_map(|d| d.span.as_ref().and_then(|s| self.span_location(s)))
+            .collect::<Vec<_>>();
+        if locations.is_empty() { return None; }
+        Some(GotoDefinitionResponse::Array(locations))
+    }
+
+    fn hover(&self, params: &TextDocumentPositionParams) -> Option<Hover> {
+        let word = self.word(params)?;
+        let mut signatures = vec![];
+        let visible = self.visible(&params.text_document.uri);
+        let found = find(visible.iter().chain(self.library.iter()),&word);
+        for def in &found {
+            if !signatures.contains(&def.signature) {
+                signatures.push(def.signature.clone());
+            }
+        }
+        /* library docs are by plain name */
+        let word = match word.rsplit_once('.') {
+            Some((_,name)) if found.iter().any(|d| d.name == name) => name.to_string(),
+            _ => word
+        };
+        let mut parts = vec![];
+        if !signatures.is_empty() {
+            parts.push(format!("```eard\n{}\n```",signatures.join("\n")));
+        }
+        if let Some(entry) = self.docs.get(&word) {
+            parts.push(entry.markdown());
+        }
+        if parts.is_empty() { return None; }
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value: parts.join("\n\n---\n\n") }),
+            range: None
+        })
+    }
+
+    fn completion(&self, params: &TextDocumentPositionParams) -> CompletionResponse {
+        let mut seen = HashSet::new();
+        let mut items = vec![];
+        let visible = self.visible(&params.text_document.uri);
+        for def in self.library.iter().chain(visible.iter()) {
+            if def.kind == DefinitionKind::Code || def.name.starts_with("__") { continue; }
+            if !seen.insert(def.name.clone()) { continue; }
+            items.push(CompletionItem {
+                label: def.name.clone(),
+                kind: Some(completion_kind(def.kind)),
+                detail: Some(def.signature.clone()),
+                ..Default::default()
+            });
+        }
+        CompletionResponse::Array(items)
+    }
+
+    /* Every request must get a response, so params which don't parse are reported back */
+    fn reply<T>(&self, request: Request, cb: impl FnOnce(&Self,&TextDocumentPositionParams) -> T) where T: serde::Serialize {
+        let response = match serde_json::from_value::<TextDocumentPositionParams>(request.params) {
+            Ok(params) => Response::new_ok(request.id,cb(self,&params)),
+            Err(e) => Response::new_err(request.id,ErrorCode::InvalidParams as i32,format!("bad params: {}",e))
+        };
+        self.send(Message::Response(response));
+    }
+
+    pub(crate) fn request(&mut self, request: Request) {
+        match request.method.as_str() {
+            "textDocument/definition" => { self.reply(request,|s,p| s.goto_definition(p)); },
+            "textDocument/hover" => { self.reply(request,|s,p| s.hover(p)); },
+            "textDocument/completion" => { self.reply(request,|s,p| s.completion(p)); },
+            _ => {
+                self.send(Message::Response(Response::new_err(request.id,ErrorCode::MethodNotFound as i32,format!("unsupported: {}",request.method))));
+            }
+        }
+    }
+
+    pub(crate) fn notification(&mut self, notification: Notification) {
+        let params = notification.params;
+        let doc = params.get("textDocument").cloned().unwrap_or_default();
+        let uri = match doc.get("uri").and_then(|u| u.as_str()).and_then(|u| Url::parse(u).ok()) {
+            Some(uri) => uri,
+            None => { return; }
+        };
+        match notification.method.as_str() {
+            "textDocument/didOpen" => {
+                if let Some(text) = doc.get("text").and_then(|t| t.as_str()) {
+                    self.open.insert(uri.clone(),text.to_string());
+                }
+                self.check(&uri);
+            },
+            "textDocument/didChange" => {
+                let text = params.get("contentChanges").and_then(|c| c.as_array())
+                    .and_then(|c| c.last()).and_then(|c| c.get("text")).and_then(|t| t.as_str());
+                if let Some(text) = text {
+                    self.open.insert(uri.clone(),text.to_string());
+                }
+                self.check(&uri);
+            },
+            "textDocument/didSave" => {
+                self.check(&uri);
+            },
+            "textDocument/didClose" => {
+                self.open.remove(&uri);
+            },
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::fs;
+use crossbeam_channel::{unbounded, Receiver};
+use lsp_server::{Message, Notification, Request, RequestId, Response};
+use lsp_types::{GotoDefinitionResponse, Hover, HoverContents, Url};
+use serde_json::{json, Value};
+use crate::server::Server;
+
+const SOURCE : &str = "program \"test\" \"test\" 1;\n\nfunction double(x) { x * 2 }\nprint(format(double(1)));\n";
+
+fn open(name: &str) -> (Server,Receiver<Message>,Url) {
+    let dir = std::env::temp_dir().join(format!("eard-lsp-test-{}-{}",std::process::id(),name));
+    fs::create_dir_all(&dir).expect("cannot create test dir");
+    let path = dir.join("test.eard");
+    fs::write(&path,SOURCE).expect("cannot write test file");
+    let uri = Url::from_file_path(&path).expect("bad path");
+    let (sender,receiver) = unbounded();
+    let mut server = Server::new(sender).expect("cannot create server");
+    server.notification(Notification::new("textDocument/didOpen".to_string(),json!({
+        "textDocument": { "uri": uri.as_str(), "languageId": "eard", "version": 1, "text": SOURCE }
+    })));
+    (server,receiver,uri)
+}
+
+fn request(server: &mut Server, receiver: &Receiver<Message>, method: &str, params: Value) -> Response {
+    server.request(Request::new(RequestId::from(1),method.to_string(),params));
+    for message in receiver.try_iter() {
+        if let Message::Response(response) = message {
+            return response;
+        }
+    }
+    panic!("no response to {}",method);
+}
+
+fn position(uri: &Url, line: u32, character: u32) -> Value {
+    json!({ "textDocument": { "uri": uri.as_str() }, "position": { "line": line, "character": character } })
+}
+
+#[test]
+fn test_hover() {
+    let (mut server,receiver,uri) = open("hover");
+    let response = request(&mut server,&receiver,"textDocument/hover",position(&uri,3,15));
+    let hover : Hover = serde_json::from_value(response.result.expect("missing result")).expect("bad hover");
+    match hover.contents {
+        HoverContents::Markup(markup) => { assert!(markup.value.contains("double(x)"),"{}",markup.value); },
+        _ => { panic!("unexpected hover contents"); }
+    }
+    let response = request(&mut server,&receiver,"textDocument/hover",position(&uri,3,1));
+    let hover : Hover = serde_json::from_value(response.result.expect("missing result")).expect("bad hover");
+    match hover.contents {
+        HoverContents::Markup(markup) => { assert!(markup.value.contains("print"),"{}",markup.value); },
+        _ => { panic!("unexpected hover contents"); }
+    }
+}
+
+#[test]
+fn test_definition() {
+    let (mut server,receiver,uri) = open("definition");
+    let response = request(&mut server,&receiver,"textDocument/definition",position(&uri,3,15));
+    let locations = match serde_json::from_value(response.result.expect("missing result")).expect("bad definition") {
+        GotoDefinitionResponse::Array(locations) => locations,
+        _ => { panic!("unexpected definition response"); }
+    };
+    assert_eq!(1,locations.len());
+    assert_eq!(uri,locations[0].uri);
+    assert_eq!(2,locations[0].range.start.line);
+}
+
+#[test]
+fn test_bad_params() {
+    let (mut server,receiver,_) = open("params");
+    let response = request(&mut server,&receiver,"textDocument/hover",json!({ "position": 3 }));
+    assert!(response.result.is_none());
+    assert_eq!(lsp_server::ErrorCode::InvalidParams as i32,response.error.expect("missing error").code);
+}
+
+fn change(server: &mut Server, receiver: &Receiver<Message>, uri: &Url, text: &str) -> Vec<Value> {
+    receiver.try_iter().count();
+    server.notification(Notification::new("textDocument/didChange".to_string(),json!({
+        "textDocument": { "uri": uri.as_str(), "version": 2 },
+        "contentChanges": [ { "text": text } ]
+    })));
+    receiver.try_iter().filter_map(|message| match message {
+        Message::Notification(n) if n.params["uri"] == uri.as_str() => n.params["diagnostics"].as_array().cloned(),
+        _ => None
+    }).flatten().collect()
+}
+
+#[test]
+fn test_unsaved() {
+    let (mut server,receiver,uri) = open("unsaved");
+    let diagnostics = change(&mut server,&receiver,&uri,"program \"test\" \"test\" 1;\nprint(1+\"a\");\n");
+    assert_eq!(1,diagnostics.len());
+    assert_eq!(1,diagnostics[0]["range"]["start"]["line"]);
+    let diagnostics = change(&mut server,&receiver,&uri,&SOURCE.replace("\n\n","\n\n\n\n"));
+    assert!(diagnostics.is_empty());
+    let response = request(&mut server,&receiver,"textDocument/definition",position(&uri,5,15));
+    let locations = match serde_json::from_value(response.result.expect("missing result")).expect("bad definition") {
+        GotoDefinitionResponse::Array(locations) => locations,
+        _ => { panic!("unexpected definition response"); }
+    };
+    assert_eq!(4,locations[0].range.start.line);
+}
+
+#[test]
+fn test_include_only() {
+    let (mut server,receiver,uri) = open("include");
+    let diagnostics = change(&mut server,&receiver,&uri,"export function triple(x) { x * 3 }\n");
+    assert!(diagnostics.is_empty(),"{:?}",diagnostics);
+    let diagnostics = change(&mut server,&receiver,&uri,"export function triple(x) { x * 3 }\nprint(1+\"a\");\n");
+    assert_eq!(1,diagnostics.len());
+    assert_eq!(1,diagnostics[0]["range"]["start"]["line"]);
+}
+
+#[test]
+fn test_utf16() {
+    let (mut server,receiver,uri) = open("utf16");
+    let text = "program \"test\" \"test\" 1;\n\nfunction double(x) { x * 2 }\nlet e = \"😀😀😀\"; print(1+\"a\");\n";
+    let diagnostics = change(&mut server,&receiver,&uri,text);
+    assert_eq!(1,diagnostics.len());
+    assert_eq!(json!({ "line": 3, "character": 18 }),diagnostics[0]["range"]["start"]);
+    change(&mut server,&receiver,&uri,"program \"test\" \"test\" 1;\n\nfunction double(x) { x * 2 }\nlet e = \"😀😀😀\"; print(format(double(1)));\n");
+    /* the last letter of double, which is three chars earlier than its UTF-16 offset */
+    let response = request(&mut server,&receiver,"textDocument/hover",position(&uri,3,36));
+    let hover : Hover = serde_json::from_value(response.result.expect("missing result")).expect("bad hover");
+    match hover.contents {
+        HoverContents::Markup(markup) => { assert!(markup.value.contains("double(x)"),"{}",markup.value); },
+        _ => { panic!("unexpected hover contents"); }
+    }
+}
+
+#[test]
+fn test_qualified() {
+    let (mut server,receiver,uri) = open("qualified");
+    let other = uri.to_file_path().expect("bad uri").with_file_name("other.eard");
+    fs::write(&other,"export function label(x) { x }\nexport record Tr { start: number, end: number }\n").expect("cannot write test file");
+    let diagnostics = change(&mut server,&receiver,&uri,"program \"test\" \"test\" 1;\ninclude \"other.eard\" as gu;\nlet tr = gu.Tr { start: 1, end: 2 };\nprint(format(gu.label(tr.start)));\n");
+    assert!(diagnostics.is_empty(),"{:?}",diagnostics);
+    let response = request(&mut server,&receiver,"textDocument/definition",position(&uri,3,17));
+    let locations = match serde_json::from_value(response.result.expect("missing result")).expect("bad definition") {
+        GotoDefinitionResponse::Array(locations) => locations,
+        _ => { panic!("unexpected definition response"); }
+    };
+    assert_eq!(vec![(Url::from_file_path(&other).expect("bad path"),0)],locations.iter().map(|l| (l.uri.clone(),l.range.start.line)).collect::<Vec<_>>());
+    let response = request(&mut server,&receiver,"textDocument/hover",position(&uri,3,26));
+    let hover : Hover = serde_json::from_value(response.result.expect("missing result")).expect("bad hover");
+    match hover.contents {
+        HoverContents::Markup(markup) => { assert!(markup.value.contains("record Tr"),"{}",markup.value); },
+        _ => { panic!("unexpected hover contents"); }
+    }
+}
+
+#[test]
+fn test_library_definition() {
+    let (mut server,receiver,uri) = open("library");
+    let response = request(&mut server,&receiver,"textDocument/definition",position(&uri,3,1));
+    let locations = match serde_json::from_value(response.result.expect("missing result")).expect("bad definition") {
+        GotoDefinitionResponse::Array(locations) => locations,
+        _ => { panic!("unexpected definition response"); }
+    };
+    assert!(!locations.is_empty());
+    for location in locations {
+        let source = fs::read_to_string(location.uri.to_file_path().expect("bad uri")).expect("missing library source");
+        let line = source.lines().nth(location.range.start.line as usize).expect("missing line");
+        assert!(line.contains("print"),"{}",line);
+    }
+}
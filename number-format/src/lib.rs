@@ -0,0 +1,192 @@
+/* Display already leaves ".0" off whole numbers. Only negative zero needs tidying. */
+pub fn reduce_num(n: f64) -> String {
+    if n == 0.0 {
+        "0".to_string()
+    } else {
+        format!("{}",n)
+    }
+}
+
+/* Any more and a spec could ask for gigabytes of zeros. */
+pub const MAX_PRECISION : usize = 100;
+
+/* Spec is [,][.precision]type[unit] where type is one of
+ *   f -- fixed, precision decimal places
+ *   g -- precision significant figures (default 6), trailing zeros removed
+ *   e -- scientific, precision decimal places in the mantissa
+ *   s -- scaled by k, M, G, T, P, E, precision decimal places (default 1), trailing zeros removed
+ * A leading comma puts thousand-separating commas into the integer part, and so isn't allowed with
+ * e, whose integer part is a single digit. Any text after the type
+ * is a unit appended to the result: for s it goes after the SI prefix, separated by a space, so
+ * ".1sb" gives "1.2 Mb" and "350 kb". The empty spec formats as format() does. Precision is at
+ * most MAX_PRECISION. The interpreter runs this and the compiler folds with it, so both agree.
+ */
+pub struct NumberFormat {
+    commas: bool,
+    precision: Option<usize>,
+    kind: Option<char>,
+    unit: String
+}
+
+impl NumberFormat {
+    pub fn new(spec: &str) -> Result<NumberFormat,String> {
+        let bad = || format!("bad number_format spec '{}'",spec);
+        let mut rest = spec;
+        let commas = rest.starts_with(',');
+        if commas { rest = &rest[1..]; }
+        let mut precision = None;
+        if rest.starts_with('.') {
+            let digits = rest[1..].chars().take_while(|c| c.is_ascii_digit()).count();
+            if digits == 0 { return Err(bad()); }
+            let value = rest[1..(digits+1)].parse::<usize>().map_err(|_| bad())?;
+            if value > MAX_PRECISION {
+                return Err(format!("number_format precision {} is more than {}",value,MAX_PRECISION));
+            }
+            precision = Some(value);
+            rest = &rest[(digits+1)..];
+        }
+        let mut chars = rest.chars();
+        let kind = chars.next();
+        match kind {
+            Some('e') if commas => { return Err(bad()); },
+            Some('f') | Some('g') | Some('e') | Some('s') => {},
+            None if !commas && precision.is_none() => {},
+            _ => { return Err(bad()); }
+        }
+        Ok(NumberFormat { commas, precision, kind, unit: chars.as_str().to_string() })
+    }
+
+    pub fn format(&self, number: f64) -> String {
+        if !number.is_finite() {
+            return format!("{}{}",number,self.unit);
+        }
+        match self.kind {
+            Some('f') => {
+                let out = if let Some(precision) = self.precision {
+                    format!("{:.*}",precision,number)
+                } else {
+                    reduce_num(number)
+                };
+                format!("{}{}",self.add_commas(out),self.unit)
+            },
+            Some('g') => format!("{}{}",self.significant(number),self.unit),
+            Some('e') => {
+                let out = if let Some(precision) = self.precision {
+                    format!("{:.*e}",precision,number)
+                } else {
+                    format!("{:e}",number)
+                };
+                format!("{}{}",out,self.unit)
+            },
+            Some('s') => {
+                let (out,prefix) = self.si(number);
+                let suffix = format!("{}{}",prefix,self.unit);
+                if suffix.is_empty() { out } else { format!("{} {}",out,suffix) }
+            },
+            _ => reduce_num(number)
+        }
+    }
+
+    fn significant(&self, number: f64) -> String {
+        let figures = self.precision.unwrap_or(6).max(1);
+        let sci = format!("{:.*e}",figures-1,number);
+        let exponent = sci.split('e').nth(1).and_then(|x| x.parse::<i64>().ok()).unwrap_or(0);
+        if exponent < -5 || exponent >= figures as i64 {
+            let mut parts = sci.split('e');
+            let mantissa = trim_zeros(parts.next().unwrap_or(""));
+            format!("{}e{}",mantissa,exponent)
+        } else {
+            let decimals = (figures as i64 - 1 - exponent).max(0) as usize;
+            self.add_commas(trim_zeros(&format!("{:.*}",decimals,number)))
+        }
+    }
+
+    fn si(&self, number: f64) -> (String,&'static str) {
+        let precision = self.precision.unwrap_or(1);
+        let mut scaled = number;
+        let mut prefix = 0;
+        loop {
+            let rounded = format!("{:.*}",precision,scaled).parse::<f64>().unwrap_or(scaled);
+            if rounded.abs() < 1000. || prefix == SI_PREFIXES.len()-1 { break; }
+            scaled /= 1000.;
+            prefix += 1;
+        }
+        (self.add_commas(trim_zeros(&format!("{:.*}",precision,scaled))),SI_PREFIXES[prefix])
+    }
+
+    fn add_commas(&self, number: String) -> String {
+        if !self.commas { return number; }
+        let (sign,unsigned) = match number.strip_prefix('-') { Some(rest) => ("-",rest), None => ("",&number[..]) };
+        let (int,frac) = match unsigned.find('.') {
+            Some(pos) => (&unsigned[..pos],&unsigned[pos..]),
+            None => (unsigned,"")
+        };
+        let mut out = String::new();
+        for (i,c) in int.chars().enumerate() {
+            if i > 0 && (int.len()-i) % 3 == 0 { out.push(','); }
+            out.push(c);
+        }
+        format!("{}{}{}",sign,out,frac)
+    }
+}
+
+const SI_PREFIXES : [&str;7] = ["","k","M","G","T","P","E"];
+
+fn trim_zeros(number: &str) -> String {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        number.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NumberFormat;
+
+    fn fmt(spec: &str, number: f64) -> String {
+        NumberFormat::new(spec).expect("bad spec").format(number)
+    }
+
+    #[test]
+    fn test_si() {
+        assert_eq!("1.2 Mb",fmt(".1sb",1234567.));
+        assert_eq!("350 kb",fmt(".1sb",350000.));
+        assert_eq!("12",fmt("s",12.));
+        assert_eq!("-1.5 k",fmt("s",-1500.));
+        assert_eq!("1 k",fmt(".1s",999.95));
+        assert_eq!("1 M",fmt(".1s",999950.));
+        assert_eq!("999.9 k",fmt(".1s",999940.));
+        assert_eq!("1,000 E",fmt(",.0s",1e21));
+    }
+
+    #[test]
+    fn test_significant() {
+        assert_eq!("123.457",fmt("g",123456.7e-3));
+        assert_eq!("1.23e6",fmt(".3g",1234567.));
+        assert_eq!("123",fmt(".3g",123.4));
+        assert_eq!("1e-6",fmt("g",0.000001));
+        assert_eq!("0.00001",fmt("g",0.00001));
+        assert_eq!("-12,345",fmt(",.5g",-12345.));
+    }
+
+    #[test]
+    fn test_fixed_and_exponent() {
+        assert_eq!("-1,234,567.89",fmt(",.2f",-1234567.891));
+        assert_eq!("-123.5",fmt(",.1f",-123.45));
+        assert_eq!("1,000",fmt(",f",1000.));
+        assert_eq!("1.23e3",fmt(".2e",1234.));
+        assert_eq!("2.5 bp",fmt(".1f bp",2.5));
+        assert_eq!("1.5",fmt("",1.5));
+        assert_eq!("inf%",fmt("f%",f64::INFINITY));
+    }
+
+    #[test]
+    fn test_bad_spec() {
+        for spec in [",",".",".2","x",".f",",e",",.2e"] {
+            assert_eq!(format!("bad number_format spec '{}'",spec),NumberFormat::new(spec).err().expect("accepted bad spec"));
+        }
+        assert_eq!("number_format precision 101 is more than 100",NumberFormat::new(".101f").err().expect("accepted bad spec"));
+        assert!(NumberFormat::new(".100f").is_ok());
+    }
+}